@@ -0,0 +1,63 @@
+use std::sync::mpsc::Receiver;
+
+use dioxus::prelude::*;
+
+use crate::utils::defaults::activate_firmware_defaults;
+use crate::utils::ipc::{CommandHandler, StatsBroadcaster};
+use crate::utils::settings::{read_profile_settings, write_profile_settings};
+use crate::utils::types::{CurrentStats, ProfileSettings};
+
+// Serves the command protocol on ryzone.sock straight from the GUI's signals,
+// so scripts get the same view of the running instance as the window does.
+pub struct AppHandler {
+    pub show_requested: SyncSignal<bool>,
+    pub stats: SyncSignal<CurrentStats>,
+    pub profile: SyncSignal<ProfileSettings>,
+    pub broadcaster: StatsBroadcaster,
+}
+
+impl CommandHandler for AppHandler {
+    fn show(&self) -> Result<(), String> {
+        let mut show_requested = self.show_requested;
+        show_requested.set(true);
+        Ok(())
+    }
+
+    fn current_stats(&self) -> Option<CurrentStats> {
+        Some(self.stats.peek().clone())
+    }
+
+    fn profile_settings(&self) -> ProfileSettings {
+        self.profile.peek().clone()
+    }
+
+    fn set_active_profile(&self, profile: &str) -> Result<(), String> {
+        let mut profile_signal = self.profile;
+        if !profile_signal.peek().has_profile(profile) {
+            return Err(format!("unknown profile '{}'", profile));
+        }
+        profile_signal.write().active_profile = profile.to_string();
+        let settings = profile_signal.peek().clone();
+        write_profile_settings(&settings).map_err(|e| e.to_string())
+    }
+
+    fn reload_config(&self) -> Result<(), String> {
+        let settings = read_profile_settings().map_err(|e| e.to_string())?;
+        let mut profile_signal = self.profile;
+        profile_signal.set(settings);
+        Ok(())
+    }
+
+    fn restore_defaults(&self) -> Result<(), String> {
+        let mut profile_signal = self.profile;
+        let mut settings = profile_signal.peek().clone();
+        activate_firmware_defaults(&mut settings)?;
+        write_profile_settings(&settings).map_err(|e| e.to_string())?;
+        profile_signal.set(settings);
+        Ok(())
+    }
+
+    fn subscribe(&self) -> Receiver<CurrentStats> {
+        self.broadcaster.subscribe()
+    }
+}
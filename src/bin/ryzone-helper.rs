@@ -0,0 +1,206 @@
+//! ryzone-helper: the only part of Ryzone that runs as root. Started through
+//! pkexec by the GUI, ryzoned or ryzonectl, it owns RyzenAdj / RyzenSmu and
+//! answers the narrow command set in `utils::helper` on a socket only the
+//! invoking user can open. Exits once it has been idle for a while.
+
+use std::io::{self, BufReader};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use libryzenadj::RyzenAdj;
+
+use ryzone::utils::backend::PowerBackend;
+use ryzone::utils::battery::write_charge_thresholds;
+use ryzone::utils::helper::{check_limits, is_root, HelperRequest, HelperResponse};
+use ryzone::utils::ipc::{helper_socket_path, read_message, write_json_line, Message};
+use ryzone::utils::pm_table::PmTable;
+use ryzone::utils::ryzen_smu::RyzenSmu;
+use ryzone::utils::sysfs::SysfsRoot;
+use ryzone::utils::types::{ChargeThresholds, PowerLimits};
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Job = (HelperRequest, Sender<HelperResponse>);
+
+fn main() {
+    if !is_root() {
+        eprintln!("[ryzone-helper] Must be started as root (through pkexec)");
+        std::process::exit(1);
+    }
+
+    // pkexec sets PKEXEC_UID; sudo is accepted for manual testing
+    let uid = ["PKEXEC_UID", "SUDO_UID"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().and_then(|v| v.parse::<u32>().ok()));
+    let Some(uid) = uid else {
+        eprintln!("[ryzone-helper] PKEXEC_UID not set; refusing to serve root");
+        std::process::exit(1);
+    };
+
+    let sock = match helper_socket_path(uid) {
+        Ok(sock) => sock,
+        Err(e) => {
+            eprintln!("[ryzone-helper] Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if UnixStream::connect(&sock).is_ok() {
+        eprintln!("[ryzone-helper] Already running ({})", sock.display());
+        return;
+    }
+    let listener = match bind_user_socket(&sock, uid) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("[ryzone-helper] Failed to bind {}: {}", sock.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    // RyzenAdj isn't Send, so a single worker thread owns the hardware handles
+    let (jobs, job_rx) = mpsc::channel::<Job>();
+    std::thread::spawn(move || {
+        let adj = RyzenAdj::new()
+            .map_err(|e| eprintln!("[ryzone-helper] RyzenAdj unavailable: {:?}", e))
+            .ok();
+        let smu = RyzenSmu::new().ok();
+        for (request, reply) in job_rx {
+            let _ = reply.send(handle(adj.as_ref(), smu.as_ref(), request));
+        }
+    });
+
+    serve(listener, jobs);
+    let _ = std::fs::remove_file(&sock);
+}
+
+// The socket lives in a directory the user owns, so never follow a symlink
+// here: create it 0600 via the umask and lchown it instead of chown.
+fn bind_user_socket(path: &Path, uid: u32) -> io::Result<UnixListener> {
+    let _ = std::fs::remove_file(path);
+    unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path)?;
+    std::os::unix::fs::lchown(path, Some(uid), None)?;
+    Ok(listener)
+}
+
+// ─── Accept loop ─────────────────────────────────────────────────────────────
+fn serve(listener: UnixListener, jobs: Sender<Job>) {
+    let active = Arc::new(AtomicUsize::new(0));
+    let idle_since = Arc::new(Mutex::new(Instant::now()));
+    listener
+        .set_nonblocking(true)
+        .expect("Failed to set socket non-blocking");
+
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let _ = stream.set_nonblocking(false);
+                active.fetch_add(1, Ordering::SeqCst);
+                let (jobs, active, idle_since) = (jobs.clone(), active.clone(), idle_since.clone());
+                std::thread::spawn(move || {
+                    let _ = serve_connection(stream, &jobs);
+                    if active.fetch_sub(1, Ordering::SeqCst) == 1 {
+                        *idle_since.lock().unwrap() = Instant::now();
+                    }
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                let idle = active.load(Ordering::SeqCst) == 0
+                    && idle_since.lock().unwrap().elapsed() > IDLE_TIMEOUT;
+                if idle {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            Err(e) => eprintln!("[ryzone-helper] accept error: {}", e),
+        }
+    }
+}
+
+fn serve_connection(stream: UnixStream, jobs: &Sender<Job>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let response = match read_message::<_, HelperRequest>(&mut reader) {
+            Ok(request) => {
+                let (reply, reply_rx) = mpsc::channel();
+                jobs.send((request, reply)).map_err(io::Error::other)?;
+                reply_rx.recv().map_err(io::Error::other)?
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => HelperResponse::Error {
+                message: e.to_string(),
+            },
+            Err(e) => return Err(e),
+        };
+        write_json_line(&mut writer, &Message::new(response))?;
+    }
+}
+
+// ─── Commands ────────────────────────────────────────────────────────────────
+fn handle(adj: Option<&RyzenAdj>, smu: Option<&RyzenSmu>, request: HelperRequest) -> HelperResponse {
+    let result = match request {
+        HelperRequest::ReadTelemetry => read_telemetry(adj),
+        HelperRequest::ApplyLimits { limits } => apply_limits(adj, &limits),
+        HelperRequest::SetChargeThresholds { battery, start_percent, end_percent } => {
+            set_charge_thresholds(&battery, start_percent, end_percent)
+        }
+        HelperRequest::ReadPmTable => read_pm_table(smu),
+    };
+    result.unwrap_or_else(|message| HelperResponse::Error { message })
+}
+
+fn read_telemetry(adj: Option<&RyzenAdj>) -> Result<HelperResponse, String> {
+    let adj = adj.ok_or("RyzenAdj unavailable")?;
+    PowerBackend::refresh(adj)?;
+    Ok(HelperResponse::Telemetry {
+        telemetry: adj.read_telemetry()?,
+        limits: adj.read_limits()?,
+    })
+}
+
+fn apply_limits(adj: Option<&RyzenAdj>, limits: &PowerLimits) -> Result<HelperResponse, String> {
+    check_limits(limits)?;
+    let adj = adj.ok_or("RyzenAdj unavailable")?;
+    if let Some(value) = limits.fast_mw {
+        PowerBackend::set_fast_limit(adj, value as u32)?;
+    }
+    if let Some(value) = limits.slow_mw {
+        PowerBackend::set_slow_limit(adj, value as u32)?;
+    }
+    if let Some(value) = limits.stapm_mw {
+        PowerBackend::set_stapm_limit(adj, value as u32)?;
+    }
+    if let Some(value) = limits.temp_c {
+        PowerBackend::set_tctl_temp(adj, value as u32)?;
+    }
+    Ok(HelperResponse::Ok)
+}
+
+// Only batteries under the sysfs power_supply class can be named, and the
+// values are range-checked before anything is written. The root follows
+// RYZONE_SYSFS_ROOT like the in-process backend; pkexec clears the
+// environment, so a helper it starts always writes to /sys.
+fn set_charge_thresholds(battery: &str, start_percent: Option<i32>, end_percent: i32) -> Result<HelperResponse, String> {
+    let thresholds = ChargeThresholds {
+        start_percent,
+        end_percent: Some(end_percent),
+        full_charge_once: false,
+    };
+    thresholds.validate()?;
+    write_charge_thresholds(&SysfsRoot::from_env(), battery, start_percent, end_percent)?;
+    Ok(HelperResponse::Ok)
+}
+
+fn read_pm_table(smu: Option<&RyzenSmu>) -> Result<HelperResponse, String> {
+    let smu = smu.ok_or("ryzen_smu driver not loaded")?;
+    let table = PmTable::read_smu(smu)?;
+    Ok(HelperResponse::PmTable {
+        codename: table.codename,
+        version: table.version,
+        values: table.values,
+    })
+}
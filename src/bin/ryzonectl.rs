@@ -0,0 +1,369 @@
+//! ryzonectl: command-line client for Ryzone. Talks to a running ryzoned or
+//! GUI instance over its socket, or works standalone on ~/.ryzone/profile_settings.toml.
+
+use serde_json::json;
+
+use ryzone::utils::capabilities::capabilities_for;
+use ryzone::utils::defaults::{activate_firmware_defaults, read_firmware_defaults, restore_firmware_defaults};
+use ryzone::utils::logs::flatten_fields;
+use ryzone::utils::ipc::{app_socket_path, daemon_socket_path, Client, Request, Response};
+use ryzone::utils::settings::{read_profile_settings, write_profile_settings};
+use ryzone::utils::stats::{init_readonly_backend, init_restore_backend, sample_current_stats, ProfileResolver};
+use ryzone::utils::sysfs::SysfsRoot;
+use ryzone::utils::types::{
+    format_limit_errors, PowerLimits, Profile, ProfileMode, ProfileSettings,
+};
+
+const USAGE: &str = "\
+Usage: ryzonectl [--json] [--standalone] <command>
+
+Commands:
+  stats                              Print current stats
+  profile list                       List profiles and their sub-profiles
+  profile set <profile>              Switch the active profile
+  profile create <name> [--mode <mode>] [--description <text>]
+                                     Add a profile; mode is platform_profile,
+                                     power_source or fixed (default)
+  profile rename <profile> <name>    Rename a profile
+  profile duplicate <profile> <name> Copy a profile under a new name
+  profile delete <profile>           Delete a profile
+  restore-defaults                   Switch to the firmware default limits
+  limits get <profile>.<sub>         Print the limits of a sub-profile, e.g. custom.batt
+  limits set <profile>.<sub> [--fast <W>] [--slow <W>] [--stapm <W>] [--temp <°C>]
+                                     Edit the limits of a sub-profile; pass
+                                     unset to leave a limit to the firmware
+
+Options:
+  --json         Print JSON instead of a plain table
+  --standalone   Don't contact a running instance; read and write the config directly
+";
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json = take_flag(&mut args, "--json");
+    let standalone = take_flag(&mut args, "--standalone");
+
+    if args.is_empty() || take_flag(&mut args, "--help") || take_flag(&mut args, "-h") {
+        print!("{}", USAGE);
+        std::process::exit(if args.is_empty() { 0 } else { 2 });
+    }
+
+    let instance = if standalone { None } else { connect_instance() };
+    let mut ctl = Ctl { json, instance };
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["stats"] => ctl.stats(),
+        ["profile"] | ["profile", "list"] => ctl.profile_list(),
+        ["profile", "set", profile] => ctl.profile_set(profile),
+        ["profile", "create", name, options @ ..] => ctl.profile_create(name, options),
+        ["profile", "rename", profile, name] => {
+            ctl.edit_profiles(|settings| settings.rename_profile(profile, name))
+        }
+        ["profile", "duplicate", profile, name] => {
+            ctl.edit_profiles(|settings| settings.duplicate_profile(profile, name))
+        }
+        ["profile", "delete", profile] => ctl.edit_profiles(|settings| settings.delete_profile(profile)),
+        ["restore-defaults"] => ctl.restore_defaults(),
+        ["limits", "get", target] => ctl.limits_get(target),
+        ["limits", "set", target, values @ ..] => ctl.limits_set(target, values),
+        _ => {
+            eprint!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("ryzonectl: {}", e);
+        std::process::exit(1);
+    }
+}
+
+const GLOBAL_FLAGS: &[&str] = &["--json", "--standalone", "--help", "-h"];
+
+// Global flags can go anywhere except where an option's value is expected.
+// Every subcommand option takes a value, so the word after one is left
+// alone: `profile create x --description --json` describes x as "--json".
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let mut found = false;
+    let mut is_value = false;
+    args.retain(|arg| {
+        let keep = is_value || arg != flag;
+        found |= !keep;
+        is_value = !is_value && arg.starts_with("--") && !GLOBAL_FLAGS.contains(&arg.as_str());
+        keep
+    });
+    found
+}
+
+// Prefers the daemon, since it is the one enforcing limits, then the GUI.
+fn connect_instance() -> Option<Client> {
+    [daemon_socket_path(), app_socket_path()]
+        .iter()
+        .find_map(|path| Client::connect(path).ok())
+}
+
+struct Ctl {
+    json: bool,
+    instance: Option<Client>,
+}
+
+impl Ctl {
+    fn request(&mut self, request: Request) -> Result<Option<Response>, String> {
+        let Some(client) = self.instance.as_mut() else {
+            return Ok(None);
+        };
+        match client.request(request).map_err(|e| e.to_string())? {
+            Response::Error { message } => Err(message),
+            response => Ok(Some(response)),
+        }
+    }
+
+    fn profile_settings(&mut self) -> Result<ProfileSettings, String> {
+        match self.request(Request::GetProfile)? {
+            Some(Response::Profile { profile }) => Ok(profile),
+            Some(_) => Err("unexpected response".to_string()),
+            None => read_profile_settings().map_err(|e| e.to_string()),
+        }
+    }
+
+    // ─── stats ───────────────────────────────────────────────────────────────
+    fn stats(&mut self) -> Result<(), String> {
+        let stats = match self.request(Request::GetStats)? {
+            Some(Response::Stats { stats }) => *stats,
+            Some(_) => return Err("unexpected response".to_string()),
+            None => {
+                // Standalone: sample once, but never enforce from the CLI.
+                // Without a running helper only battery stats are filled.
+                let settings = read_profile_settings().map_err(|e| e.to_string())?;
+                let backend = init_readonly_backend();
+                if backend.is_none() {
+                    eprintln!("ryzone-helper is not running; CPU/power stats unavailable");
+                }
+                sample_current_stats(
+                    backend.as_deref(),
+                    &SysfsRoot::from_env(),
+                    &settings,
+                    &mut ProfileResolver::default(),
+                )?
+                .0
+            }
+        };
+        self.print_fields(&stats)
+    }
+
+    // ─── profile ─────────────────────────────────────────────────────────────
+    fn profile_list(&mut self) -> Result<(), String> {
+        let settings = self.profile_settings()?;
+
+        if self.json {
+            let profiles: Vec<_> = settings
+                .profiles
+                .iter()
+                .map(|profile| {
+                    json!({
+                        "name": profile.name,
+                        "description": profile.description,
+                        "mode": profile.mode,
+                        "active": settings.active_profile == profile.name,
+                        "sub_profiles": profile.sub_profile_names(),
+                    })
+                })
+                .collect();
+            let output = json!({
+                "active_profile": settings.active_profile,
+                "profiles": profiles,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+            return Ok(());
+        }
+
+        let rows: Vec<(String, String)> = settings
+            .profiles
+            .iter()
+            .map(|profile| {
+                let marker = if settings.active_profile == profile.name { "* " } else { "  " };
+                let subs = profile.sub_profile_names().join(", ");
+                (format!("{}{}", marker, profile.name), format!("{}: {}", profile.mode.as_str(), subs))
+            })
+            .collect();
+        print_table(&rows);
+        Ok(())
+    }
+
+    fn profile_set(&mut self, profile: &str) -> Result<(), String> {
+        if self.request(Request::SetActiveProfile { profile: profile.to_string() })?.is_some() {
+            return Ok(());
+        }
+
+        let mut settings = read_profile_settings().map_err(|e| e.to_string())?;
+        if !settings.has_profile(profile) {
+            return Err(format!("unknown profile '{}'", profile));
+        }
+        settings.active_profile = profile.to_string();
+        write_profile_settings(&settings).map_err(|e| e.to_string())
+    }
+
+    fn profile_create(&mut self, name: &str, options: &[&str]) -> Result<(), String> {
+        let mut mode = ProfileMode::Fixed;
+        let mut description = String::new();
+
+        let mut iter = options.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("missing value for {}", flag))?;
+            match *flag {
+                "--mode" => {
+                    mode = ProfileMode::from_name(value)
+                        .ok_or_else(|| format!("unknown mode '{}'", value))?
+                }
+                "--description" => description = value.to_string(),
+                _ => return Err(format!("unknown option '{}'", flag)),
+            }
+        }
+
+        let profile = Profile::new(name, &description, mode);
+        self.edit_profiles(|settings| settings.add_profile(profile))
+    }
+
+    // Like limits, profiles are edited in the file and a running instance is
+    // asked to reload it.
+    fn edit_profiles<F>(&mut self, edit: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut ProfileSettings) -> Result<(), String>,
+    {
+        let mut settings = read_profile_settings().map_err(|e| e.to_string())?;
+        edit(&mut settings)?;
+        write_profile_settings(&settings).map_err(|e| e.to_string())?;
+        self.request(Request::ReloadConfig)?;
+        Ok(())
+    }
+
+    // ─── restore-defaults ────────────────────────────────────────────────────
+    // Without a running instance nothing enforces the profile, so the
+    // snapshot is also written to the hardware directly.
+    fn restore_defaults(&mut self) -> Result<(), String> {
+        if self.request(Request::RestoreDefaults)?.is_some() {
+            return Ok(());
+        }
+
+        // Only ever writes back a snapshot an earlier run took
+        read_firmware_defaults().ok_or("no firmware defaults captured yet; start Ryzone or ryzoned first")?;
+        self.edit_profiles(activate_firmware_defaults)?;
+        let backend = init_restore_backend().ok_or("no backend available to write the limits")?;
+        restore_firmware_defaults(backend.as_ref())
+    }
+
+    // ─── limits ──────────────────────────────────────────────────────────────
+    fn limits_get(&mut self, target: &str) -> Result<(), String> {
+        let (profile, sub_profile) = parse_target(target)?;
+        let settings = self.profile_settings()?;
+        let limits = settings
+            .limits(profile, sub_profile)
+            .ok_or_else(|| format!("unknown sub-profile '{}'", target))?;
+        self.print_fields(limits)
+    }
+
+    // Limits are always edited in the file; a running instance is then asked to
+    // reload it so the change applies straight away.
+    fn limits_set(&mut self, target: &str, values: &[&str]) -> Result<(), String> {
+        let (profile, sub_profile) = parse_target(target)?;
+        let mut settings = read_profile_settings().map_err(|e| e.to_string())?;
+        let limits = settings
+            .limits_mut(profile, sub_profile)
+            .ok_or_else(|| format!("unknown sub-profile '{}'", target))?;
+
+        apply_limit_args(limits, values)?;
+        limits
+            .validate()
+            .map_err(|errors| format_limit_errors(&errors))?;
+        let family_errors = capabilities_for(&SysfsRoot::from_env()).check(limits);
+        if !family_errors.is_empty() {
+            return Err(format_limit_errors(&family_errors));
+        }
+        let updated = limits.clone();
+        write_profile_settings(&settings).map_err(|e| e.to_string())?;
+        self.request(Request::ReloadConfig)?;
+
+        self.print_fields(&updated)
+    }
+
+    // ─── Output ──────────────────────────────────────────────────────────────
+    // Prints every field of a struct, in declaration order, as JSON or a table.
+    fn print_fields<T: serde::Serialize>(&self, value: &T) -> Result<(), String> {
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&value).unwrap());
+            return Ok(());
+        }
+
+        let Some(object) = value.as_object() else {
+            println!("{}", value);
+            return Ok(());
+        };
+        let mut fields = Vec::new();
+        for (key, value) in object {
+            flatten_fields(key, value, &mut fields);
+        }
+        let rows: Vec<(String, String)> = fields
+            .into_iter()
+            .map(|(key, value)| {
+                let text = match value {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => "-".to_string(),
+                    other => other.to_string(),
+                };
+                (key, text)
+            })
+            .collect();
+        print_table(&rows);
+        Ok(())
+    }
+}
+
+fn parse_target(target: &str) -> Result<(&str, &str), String> {
+    target
+        .split_once('.')
+        .ok_or_else(|| format!("expected <profile>.<sub>, got '{}'", target))
+}
+
+fn apply_limit_args(limits: &mut PowerLimits, values: &[&str]) -> Result<(), String> {
+    if values.is_empty() {
+        return Err("nothing to set; pass --fast, --slow, --stapm or --temp".to_string());
+    }
+
+    let mut iter = values.iter();
+    while let Some(flag) = iter.next() {
+        let raw = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        // "unset" leaves the limit to the firmware
+        let value: Option<f32> = if *raw == "unset" {
+            None
+        } else {
+            Some(
+                raw.parse()
+                    .map_err(|_| format!("invalid value '{}' for {}", raw, flag))?,
+            )
+        };
+        let watts_to_mw = value.map(|w| (w * 1000.0).round() as i32);
+
+        match *flag {
+            "--fast" => limits.fast_mw = watts_to_mw,
+            "--slow" => limits.slow_mw = watts_to_mw,
+            "--stapm" => limits.stapm_mw = watts_to_mw,
+            "--temp" => limits.temp_c = value.map(|c| c.round() as i32),
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+    Ok(())
+}
+
+fn print_table(rows: &[(String, String)]) {
+    let width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in rows {
+        println!("{:<width$}  {}", key, value, width = width);
+    }
+}
+
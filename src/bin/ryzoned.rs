@@ -0,0 +1,174 @@
+//! Ryzoned: headless background daemon. Samples stats and enforces the active
+//! profile on every tick, whether or not the GUI is open. On SIGTERM / SIGINT
+//! it puts the firmware's default limits back before exiting. Clients (the GUI,
+//! scripts, status bars) talk to it over the command protocol in `utils::ipc`.
+
+use std::os::unix::net::UnixStream;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use ryzone::utils::backend::PowerBackend;
+use ryzone::utils::defaults::{
+    activate_firmware_defaults, install_shutdown_handler, restore_firmware_defaults,
+    shutdown_requested,
+};
+use ryzone::utils::logs::{log_error, log_info, log_warn, StatsLogger};
+use ryzone::utils::metrics::{unix_time_ms, MetricSample, MetricsStore};
+use ryzone::utils::ipc::{bind_socket, daemon_socket_path, serve, CommandHandler, StatsBroadcaster};
+use ryzone::utils::settings::{
+    profile_settings_modified, read_app_settings, read_profile_settings, write_profile_settings,
+};
+use ryzone::utils::stats::{init_backend, read_current_stats, ProfileResolver};
+use ryzone::utils::sysfs::SysfsRoot;
+use ryzone::utils::types::{CurrentStats, ProfileSettings};
+
+struct Daemon {
+    stats: Mutex<Option<CurrentStats>>,
+    profile: Mutex<ProfileSettings>,
+    broadcaster: StatsBroadcaster,
+}
+
+impl CommandHandler for Daemon {
+    fn show(&self) -> Result<(), String> {
+        Err("ryzoned has no window".to_string())
+    }
+
+    fn current_stats(&self) -> Option<CurrentStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn profile_settings(&self) -> ProfileSettings {
+        self.profile.lock().unwrap().clone()
+    }
+
+    fn set_active_profile(&self, profile: &str) -> Result<(), String> {
+        let mut settings = self.profile.lock().unwrap();
+        if !settings.has_profile(profile) {
+            return Err(format!("unknown profile '{}'", profile));
+        }
+        settings.active_profile = profile.to_string();
+        write_profile_settings(&settings).map_err(|e| e.to_string())
+    }
+
+    fn reload_config(&self) -> Result<(), String> {
+        let settings = read_profile_settings().map_err(|e| e.to_string())?;
+        *self.profile.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    fn restore_defaults(&self) -> Result<(), String> {
+        let mut settings = self.profile.lock().unwrap();
+        activate_firmware_defaults(&mut settings)?;
+        write_profile_settings(&settings).map_err(|e| e.to_string())
+    }
+
+    fn subscribe(&self) -> Receiver<CurrentStats> {
+        self.broadcaster.subscribe()
+    }
+}
+
+fn main() {
+    let sock = daemon_socket_path();
+
+    if UnixStream::connect(&sock).is_ok() {
+        eprintln!("[ryzoned] Already running ({})", sock.display());
+        std::process::exit(1);
+    }
+
+    let app_settings = read_app_settings().expect("Failed to read app settings");
+    let profile_settings = read_profile_settings().expect("Failed to read profile settings");
+    let profile_modified = profile_settings_modified();
+    let interval_ms = app_settings.app.update_frequency_ms.max(250) as u64;
+
+    let daemon = Arc::new(Daemon {
+        stats: Mutex::new(None),
+        profile: Mutex::new(profile_settings),
+        broadcaster: StatsBroadcaster::default(),
+    });
+
+    let listener = bind_socket(&sock).expect("Failed to bind socket");
+    let server_daemon = daemon.clone();
+    std::thread::spawn(move || serve(listener, server_daemon));
+
+    let backend = init_backend();
+    let sysfs = SysfsRoot::from_env();
+    let metrics = match MetricsStore::open_default(&app_settings) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            log_warn("ryzoned", format!("Metrics history disabled: {}", e));
+            None
+        }
+    };
+    let logger = match StatsLogger::open_default() {
+        Ok(logger) => Some(logger),
+        Err(e) => {
+            log_warn("ryzoned", format!("Stats logging disabled: {}", e));
+            None
+        }
+    };
+    install_shutdown_handler();
+
+    // A panic in the loop still gets the firmware defaults put back below
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run(&daemon, backend.as_deref(), &sysfs, interval_ms, profile_modified, metrics, logger)
+    }));
+
+    if let Some(backend) = backend.as_deref() {
+        match restore_firmware_defaults(backend) {
+            Ok(()) => log_info("ryzoned", "Restored firmware default limits"),
+            Err(e) => log_error("ryzoned", format!("Failed to restore firmware defaults: {}", e)),
+        }
+    }
+    let _ = std::fs::remove_file(&sock);
+    if result.is_err() {
+        std::process::exit(101);
+    }
+}
+
+// Sample / enforce loop. Returns once SIGTERM or SIGINT is received.
+fn run(
+    daemon: &Daemon,
+    backend: Option<&dyn PowerBackend>,
+    sysfs: &SysfsRoot,
+    interval_ms: u64,
+    mut profile_modified: Option<SystemTime>,
+    mut metrics: Option<MetricsStore>,
+    mut logger: Option<StatsLogger>,
+) {
+    let mut resolver = ProfileResolver::default();
+    while !shutdown_requested() {
+        // The GUI saves straight to profile_settings.toml; reload whenever it changes
+        let modified = profile_settings_modified();
+        if modified != profile_modified {
+            if let Err(e) = daemon.reload_config() {
+                log_error("ryzoned", format!("Failed to reload profile settings: {}", e));
+            }
+            profile_modified = modified;
+        }
+
+        let profile_settings = daemon.profile_settings();
+        match read_current_stats(backend, sysfs, &profile_settings, &mut resolver) {
+            Ok(stats) => {
+                let now = unix_time_ms();
+                if let Some(store) = metrics.as_mut() {
+                    if let Err(e) = store.record(MetricSample::from_stats(&stats, now)) {
+                        log_error("ryzoned", format!("Failed to record metrics: {}", e));
+                    }
+                }
+                if let Some(logger) = logger.as_mut() {
+                    logger.reload_settings();
+                    if let Err(e) = logger.log(&stats, now) {
+                        log_error("ryzoned", format!("Failed to write stats log: {}", e));
+                    }
+                }
+                daemon.broadcaster.publish(&stats);
+                *daemon.stats.lock().unwrap() = Some(stats);
+            }
+            Err(e) => log_error("ryzoned", format!("stats read error: {}", e)),
+        }
+
+        std::thread::sleep(Duration::from_millis(interval_ms));
+    }
+}
@@ -1,28 +1,48 @@
 use dioxus::prelude::*;
 
+use crate::components::chart::{ChartSeries, LineChart};
 use crate::utils::conversions::{
     battery_health_label, battery_status_text, format_mwh, format_power_mw, minutes_to_hm_text,
 };
-use crate::utils::types::CurrentStats;
+use crate::utils::metrics::{
+    metrics_dir, read_aggregates, unix_time_ms, Aggregate, Metric, MetricsHistory, Resolution, HOUR_MS,
+    MINUTE_MS,
+};
+use crate::utils::settings::write_profile_settings;
+use crate::utils::types::{BatteryInfo, ChargeThresholds, CurrentStats, ProfileSettings};
 
 #[component]
 pub fn Battery() -> Element {
-    // Sample data for the chart (in a real app, this would come from your daemon)
-    let battery_history = vec![
-        (0, 92),
-        (1, 88),
-        (2, 85),
-        (3, 82),
-        (4, 78),
-        (5, 75),
-        (6, 72),
-        (7, 68),
-        (8, 65),
-        (9, 62),
-        (10, 58),
-        (11, 55),
-        (12, 52),
-    ];
+    // Recent samples recorded by the stats thread; longer ranges come from
+    // the aggregates in ~/.ryzone/metrics
+    let history = use_context::<SyncSignal<MetricsHistory>>();
+    let mut chart_metric = use_signal(|| Metric::ChargePercent);
+    let mut chart_range = use_signal(|| ChartRange::Hour);
+    // Only changes once a minute, so the files aren't re-read on every sample
+    let minute_tick = use_memo(move || history.read().latest().map(|s| s.timestamp_ms / MINUTE_MS));
+    let stored = use_memo(move || {
+        let _ = minute_tick();
+        load_aggregates(chart_range())
+    });
+    let metric = chart_metric();
+    let range = chart_range();
+    // Power is charted in watts
+    let scale = if metric == Metric::PowerDraw { 0.001 } else { 1.0 };
+    let chart_data: Vec<(i64, f64)> = if range == ChartRange::Hour {
+        history
+            .read()
+            .series(metric, 0)
+            .into_iter()
+            .map(|(timestamp_ms, value)| (timestamp_ms, value as f64 * scale))
+            .collect()
+    } else {
+        stored
+            .read()
+            .iter()
+            .map(|agg| (agg.start_ms, metric.value(&agg.avg) as f64 * scale))
+            .collect()
+    };
+    let chart_y_unit = if metric == Metric::PowerDraw { "W" } else { metric.unit() };
 
     let stats = use_context::<SyncSignal<CurrentStats>>();
     let stats_now = stats.read();
@@ -36,6 +56,13 @@ pub fn Battery() -> Element {
         "Time Remaining"
     };
     let power_draw_text = format_power_mw(stats_now.power_draw_mw);
+    let power_source_text = if !stats_now.on_ac {
+        "Battery".to_string()
+    } else if stats_now.ac_adapter_power_mw > 0 {
+        format!("AC ({})", format_power_mw(stats_now.ac_adapter_power_mw))
+    } else {
+        "AC".to_string()
+    };
     let design_capacity_text = if stats_now.batt_design_capacity_mwh > 0 {
         format_mwh(stats_now.batt_design_capacity_mwh)
     } else {
@@ -92,11 +119,15 @@ pub fn Battery() -> Element {
                 }
 
                 // Status indicators
-                div { class: "grid grid-cols-3 gap-4 text-sm",
+                div { class: "grid grid-cols-4 gap-4 text-sm",
                     div {
                         div { class: "text-[var(--color-base-content)]/70 mb-1", "Status" }
                         div { class: "font-semibold", "{status_text}" }
                     }
+                    div {
+                        div { class: "text-[var(--color-base-content)]/70 mb-1", "Power Source" }
+                        div { class: "font-semibold", "{power_source_text}" }
+                    }
                     div {
                         div { class: "text-[var(--color-base-content)]/70 mb-1", "{time_label}" }
                         div { class: "font-semibold", "{current_runtime}" }
@@ -174,19 +205,44 @@ pub fn Battery() -> Element {
 
                     // Chart tabs
                     div { class: "flex gap-2 mb-4",
-                        button { class: "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm",
-                            "Percentage"
-                        }
-                        button { class: "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80",
-                            "Runtime"
+                        for (tab, label) in [
+                            (Metric::ChargePercent, "Percentage"),
+                            (Metric::Runtime, "Runtime"),
+                            (Metric::PowerDraw, "Power Draw"),
+                        ] {
+                            button {
+                                class: if metric == tab {
+                                    "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                                } else {
+                                    "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                                },
+                                onclick: move |_| chart_metric.set(tab),
+                                "{label}"
+                            }
                         }
-                        button { class: "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80",
-                            "Power Draw"
+                        div { class: "flex-1" }
+                        for option in ChartRange::ALL {
+                            button {
+                                class: if range == option {
+                                    "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                                } else {
+                                    "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                                },
+                                onclick: move |_| chart_range.set(option),
+                                "{option.label()}"
+                            }
                         }
                     }
 
                     // Chart
-                    BatteryChart { data: battery_history.clone() }
+                    LineChart {
+                        series: vec![ChartSeries::new(metric.label(), "rgb(59, 130, 246)", chart_data)],
+                        y_label: metric.label().to_string(),
+                        y_unit: chart_y_unit.to_string(),
+                        x_label: range.x_label().to_string(),
+                        x_unit: range.x_unit().to_string(),
+                        x_scale_ms: range.x_scale_ms() as f64,
+                    }
                 }
             }
 
@@ -222,190 +278,284 @@ pub fn Battery() -> Element {
                     }
                 }
             }
+
+            ChargeThresholdsCard {}
+
+            // Per-battery breakdown
+            if !stats_now.batteries.is_empty() {
+                div { class: "mt-6 bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-4",
+                        if stats_now.batteries.len() > 1 { "BATTERIES" } else { "BATTERY DETAILS" }
+                    }
+                    div { class: "grid grid-cols-2 gap-6",
+                        for info in stats_now.batteries.iter() {
+                            BatteryDetails { key: "{info.name}", info: info.clone() }
+                        }
+                    }
+                }
+            }
         }
     }
 }
 
-#[derive(Clone, PartialEq, Props)]
-struct BatteryChartProps {
-    data: Vec<(i32, i32)>,
-    #[props(default = "Battery %".to_string())]
-    y_label: String,
-    #[props(default = "Time (hours ago)".to_string())]
-    x_label: String,
-    #[props(default = "%".to_string())]
-    y_unit: String,
-    #[props(default = "h".to_string())]
-    x_unit: String,
+// Applies `edit` to the charge thresholds and saves them; ryzoned (or the
+// app's own stats thread) writes them to the batteries on its next tick.
+fn save_thresholds<F>(mut profile_settings: SyncSignal<ProfileSettings>, mut error: Signal<Option<String>>, edit: F)
+where
+    F: FnOnce(&mut ChargeThresholds),
+{
+    let mut updated = profile_settings.peek().clone();
+    edit(&mut updated.charge_thresholds);
+    let result = updated
+        .charge_thresholds
+        .validate()
+        .and_then(|_| write_profile_settings(&updated).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => {
+            profile_settings.set(updated);
+            error.set(None);
+        }
+        Err(e) => error.set(Some(e)),
+    }
 }
 
 #[component]
-fn BatteryChart(props: BatteryChartProps) -> Element {
-    let BatteryChartProps { data, y_label, x_label, y_unit, x_unit } = props;
-
-    // Chart dimensions
-    let width = 800.0;
-    let height = 300.0;
-    let padding = 40.0;
-
-    let chart_width = width - 2.0 * padding;
-    let chart_height = height - 2.0 * padding;
-
-    // Calculate min/max values
-    let min_val = data.iter().map(|(_, v)| v).min().unwrap_or(&0);
-    let max_val = data.iter().map(|(_, v)| v).max().unwrap_or(&100);
-    let min_time = data.first().map(|(t, _)| t).unwrap_or(&0);
-    let max_time = data.last().map(|(t, _)| t).unwrap_or(&12);
-
-    // Generate path for the line chart
-    let points: Vec<String> = data.iter().map(|(time, value)| {
-        let x = padding + ((*time - min_time) as f64 / (*max_time - min_time) as f64) * chart_width;
-        let y = height - padding - ((value - min_val) as f64 / (max_val - min_val) as f64) * chart_height;
-        format!("{},{}", x, y)
-    }).collect();
-
-    let path_data = format!("M {}", points.join(" L "));
-
-    // Generate gradient area path
-    let first_point = points.first().unwrap().split(',').collect::<Vec<_>>();
-    let last_point = points.last().unwrap().split(',').collect::<Vec<_>>();
-    let area_path = format!(
-        "M {} L {},{} L {},{} Z",
-        points.join(" L "),
-        last_point[0], height - padding,
-        first_point[0], height - padding
-    );
+fn ChargeThresholdsCard() -> Element {
+    let stats = use_context::<SyncSignal<CurrentStats>>();
+    let profile_settings = use_context::<SyncSignal<ProfileSettings>>();
+
+    let configured = profile_settings.peek().charge_thresholds.clone();
+    let mut start = use_signal(|| configured.start_percent);
+    let mut end = use_signal(|| configured.end_percent);
+    let error = use_signal(|| None::<String>);
+
+    let stats_now = stats.read();
+    let thresholds = profile_settings().charge_thresholds.clone();
+    let supported: Vec<BatteryInfo> = stats_now
+        .batteries
+        .iter()
+        .filter(|b| b.charge_end_threshold_percent.is_some())
+        .cloned()
+        .collect();
+    let start_supported = supported.iter().any(|b| b.charge_start_threshold_percent.is_some());
+    let hardware_error = stats_now.charge_threshold_error.clone();
+
+    let input_class = "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]";
+    let parse = |value: String| value.trim().parse::<i32>().ok();
 
     rsx! {
-        div { class: "w-full overflow-x-auto",
-            svg {
-                width: "{width}",
-                height: "{height}",
-                view_box: "0 0 {width} {height}",
-                class: "w-full h-auto",
-
-                // Gradient definition
-                defs {
-                    linearGradient {
-                        id: "batteryGradient",
-                        x1: "0%",
-                        y1: "0%",
-                        x2: "0%",
-                        y2: "100%",
-                        stop { offset: "0%", stop_color: "rgba(59, 130, 246, 0.3)" }
-                        stop { offset: "100%", stop_color: "rgba(59, 130, 246, 0.05)" }
-                    }
+        div { class: "mb-6 bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+            div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-4",
+                "CHARGE THRESHOLDS"
+            }
+
+            if supported.is_empty() {
+                p { class: "text-sm text-[var(--color-base-content)]/70",
+                    "This battery or firmware doesn't expose charge thresholds."
+                }
+            } else {
+                p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
+                    "Keep the battery between two levels to extend its life on machines that are mostly plugged in. Leave a field empty to keep the firmware's value."
                 }
 
-                // Grid lines (horizontal)
-                for i in 0..5 {
-                    {
-                        let y = padding + (i as f64 * chart_height / 4.0);
-                        let val = max_val - ((i as f64 * (*max_val - min_val) as f64 / 4.0) as i32);
-                        rsx! {
-                            line {
-                                x1: "{padding}",
-                                y1: "{y}",
-                                x2: "{width - padding}",
-                                y2: "{y}",
-                                stroke: "rgba(128, 128, 128, 0.2)",
-                                stroke_width: "1"
-                            }
-                            text {
-                                x: "{padding - 5.0}",
-                                y: "{y + 4.0}",
-                                text_anchor: "end",
-                                fill: "var(--color-base-content)",
-                                opacity: "0.7",
-                                font_size: "12",
-                                "{val}{y_unit}"
-                            }
+                div { class: "grid grid-cols-2 gap-4 mb-4",
+                    div {
+                        label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                            "Start Charging Below (%)"
+                        }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            max: "99",
+                            class: input_class,
+                            disabled: !start_supported,
+                            placeholder: if start_supported { "Unchanged" } else { "Not supported" },
+                            value: start().map(|v| v.to_string()).unwrap_or_default(),
+                            oninput: move |evt| start.set(parse(evt.value())),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                            "Stop Charging At (%)"
+                        }
+                        input {
+                            r#type: "number",
+                            min: "1",
+                            max: "100",
+                            class: input_class,
+                            placeholder: "Unchanged",
+                            value: end().map(|v| v.to_string()).unwrap_or_default(),
+                            oninput: move |evt| end.set(parse(evt.value())),
                         }
                     }
                 }
 
-                // Grid lines (vertical)
-                for i in 0..(max_time - min_time + 1) {
-                    {
-                        let x = padding + (i as f64 * chart_width / (*max_time - min_time) as f64);
-                        rsx! {
-                            line {
-                                x1: "{x}",
-                                y1: "{padding}",
-                                x2: "{x}",
-                                y2: "{height - padding}",
-                                stroke: "rgba(128, 128, 128, 0.2)",
-                                stroke_width: "1"
-                            }
-                            text {
-                                x: "{x}",
-                                y: "{height - padding + 20.0}",
-                                text_anchor: "middle",
-                                fill: "var(--color-base-content)",
-                                opacity: "0.7",
-                                font_size: "12",
-                                "{i}{x_unit}"
+                // What the batteries report right now
+                div { class: "space-y-1 mb-4",
+                    for battery in supported.iter() {
+                        div { key: "{battery.name}", class: "flex justify-between text-sm",
+                            span { class: "text-[var(--color-base-content)]/70", "{battery.name}" }
+                            span { class: "font-semibold",
+                                {
+                                    match battery.charge_start_threshold_percent {
+                                        Some(start) => format!("{}% - {}%", start, battery.charge_end_threshold_percent.unwrap_or(100)),
+                                        None => format!("up to {}%", battery.charge_end_threshold_percent.unwrap_or(100)),
+                                    }
+                                }
                             }
                         }
                     }
                 }
 
-                // Area under the line
-                path {
-                    d: "{area_path}",
-                    fill: "url(#batteryGradient)"
+                if thresholds.full_charge_once {
+                    div { class: "mb-4 px-4 py-3 rounded-lg bg-[var(--color-warning)]/20 text-sm",
+                        "Charging to 100% once. The thresholds come back when the battery is full."
+                    }
                 }
 
-                // Line
-                path {
-                    d: "{path_data}",
-                    stroke: "rgb(59, 130, 246)",
-                    stroke_width: "2",
-                    fill: "none",
-                    stroke_linejoin: "round",
-                    stroke_linecap: "round"
+                if let Some(message) = error() {
+                    div { class: "mb-4 px-4 py-3 rounded-lg bg-[var(--color-error)]/20 text-sm", "{message}" }
+                }
+                if !hardware_error.is_empty() {
+                    div { class: "mb-4 px-4 py-3 rounded-lg bg-[var(--color-error)]/20 text-sm", "{hardware_error}" }
                 }
 
-                // Data points
-                for (time, value) in &data {
-                    {
-                        let x = padding + ((time - min_time) as f64 / (*max_time - min_time) as f64) * chart_width;
-                        let y = height - padding - ((value - min_val) as f64 / (max_val - min_val) as f64) * chart_height;
-                        rsx! {
-                            circle {
-                                cx: "{x}",
-                                cy: "{y}",
-                                r: "4",
-                                fill: "rgb(59, 130, 246)",
-                                stroke: "white",
-                                stroke_width: "2"
-                            }
-                        }
+                div { class: "flex gap-3",
+                    button {
+                        class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                        onclick: move |_| {
+                            let (start, end) = (start(), end());
+                            save_thresholds(profile_settings, error, |t| {
+                                t.start_percent = start;
+                                t.end_percent = end;
+                            });
+                        },
+                        "Save"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-[var(--color-base-300)] text-[var(--color-base-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                        title: "Lift the thresholds until the battery is full, then restore them",
+                        onclick: move |_| {
+                            let once = !thresholds.full_charge_once;
+                            save_thresholds(profile_settings, error, |t| t.full_charge_once = once);
+                        },
+                        if thresholds.full_charge_once { "Cancel Full Charge" } else { "Charge to 100% Once" }
                     }
                 }
+            }
+        }
+    }
+}
 
-                // Axes labels
-                text {
-                    x: "{width / 2.0}",
-                    y: "{height - 2.0}",
-                    text_anchor: "middle",
-                    fill: "var(--color-base-content)",
-                    opacity: "0.6",
-                    font_size: "11",
-                    "{x_label}"
-                }
+#[component]
+fn BatteryDetails(info: BatteryInfo) -> Element {
+    let or_na = |value: &str| if value.is_empty() { "N/A".to_string() } else { value.to_string() };
+    let capacity_text = if info.full_charge_capacity_mwh > 0 && info.design_capacity_mwh > 0 {
+        format!("{} / {}", format_mwh(info.full_charge_capacity_mwh), format_mwh(info.design_capacity_mwh))
+    } else {
+        "N/A".to_string()
+    };
+    let health_text = if info.health_percent >= 0 {
+        format!("{}%", info.health_percent)
+    } else {
+        "N/A".to_string()
+    };
+    let rows = [
+        ("Status", battery_status_text(&info.charge_status).to_string()),
+        ("Manufacturer", or_na(&info.manufacturer)),
+        ("Model", or_na(&info.model)),
+        ("Serial", or_na(&info.serial)),
+        ("Technology", or_na(&info.technology)),
+        ("Full / Design Capacity", capacity_text),
+        ("Health", health_text),
+        ("Cycle Count", info.cycle_count_cycles.to_string()),
+        ("Power Draw", format_power_mw(info.power_draw_mw)),
+    ];
 
-                text {
-                    x: "{8.0}",
-                    y: "{height / 2.0}",
-                    text_anchor: "middle",
-                    fill: "var(--color-base-content)",
-                    opacity: "0.6",
-                    font_size: "11",
-                    transform: "rotate(-90, 8, {height / 2.0})",
-                    "{y_label}"
+    rsx! {
+        div {
+            div { class: "flex justify-between items-baseline mb-3",
+                span { class: "text-lg font-semibold", "{info.name}" }
+                span { class: "text-2xl font-bold text-[var(--color-primary)]", "{info.charge_percent}%" }
+            }
+            div { class: "space-y-2",
+                for (label, value) in rows {
+                    div { class: "flex justify-between items-center",
+                        span { class: "text-sm text-[var(--color-base-content)]/70", "{label}" }
+                        span { class: "text-sm font-semibold", "{value}" }
+                    }
                 }
             }
         }
     }
 }
+
+// How far back the battery chart goes
+#[derive(Clone, Copy, PartialEq)]
+enum ChartRange {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl ChartRange {
+    const ALL: [ChartRange; 4] = [ChartRange::Hour, ChartRange::Day, ChartRange::Week, ChartRange::Month];
+
+    fn label(self) -> &'static str {
+        match self {
+            ChartRange::Hour => "1h",
+            ChartRange::Day => "24h",
+            ChartRange::Week => "7d",
+            ChartRange::Month => "30d",
+        }
+    }
+
+    fn duration_ms(self) -> i64 {
+        match self {
+            ChartRange::Hour => HOUR_MS,
+            ChartRange::Day => 24 * HOUR_MS,
+            ChartRange::Week => 7 * 24 * HOUR_MS,
+            ChartRange::Month => 30 * 24 * HOUR_MS,
+        }
+    }
+
+    // Unit of the time axis
+    fn x_scale_ms(self) -> i64 {
+        match self {
+            ChartRange::Hour => MINUTE_MS,
+            ChartRange::Day => HOUR_MS,
+            ChartRange::Week | ChartRange::Month => 24 * HOUR_MS,
+        }
+    }
+
+    fn x_unit(self) -> &'static str {
+        match self {
+            ChartRange::Hour => "m",
+            ChartRange::Day => "h",
+            ChartRange::Week | ChartRange::Month => "d",
+        }
+    }
+
+    fn x_label(self) -> &'static str {
+        match self {
+            ChartRange::Hour => "Time (minutes ago)",
+            ChartRange::Day => "Time (hours ago, 1-minute averages)",
+            ChartRange::Week | ChartRange::Month => "Time (days ago, 1-hour averages)",
+        }
+    }
+}
+
+// Stored aggregates for the range; the last hour is charted from memory instead
+fn load_aggregates(range: ChartRange) -> Vec<Aggregate> {
+    let resolution = match range {
+        ChartRange::Hour => return Vec::new(),
+        ChartRange::Day => Resolution::Minute,
+        ChartRange::Week | ChartRange::Month => Resolution::Hour,
+    };
+    let since_ms = unix_time_ms() - range.duration_ms();
+    metrics_dir()
+        .ok()
+        .and_then(|dir| read_aggregates(&dir, resolution, since_ms).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,434 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dioxus::prelude::*;
+
+// Gradient ids have to be unique across the page
+static NEXT_CHART_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Hover targets across the width of the chart
+const HOVER_COLUMNS: usize = 60;
+
+// One line of a chart: (unix timestamp in ms, value), oldest first
+#[derive(Clone, PartialEq)]
+pub struct ChartSeries {
+    pub label: String,
+    pub color: String, // any CSS color
+    pub points: Vec<(i64, f64)>,
+}
+
+impl ChartSeries {
+    pub fn new(label: impl Into<String>, color: &str, points: Vec<(i64, f64)>) -> Self {
+        Self {
+            label: label.into(),
+            color: color.to_string(),
+            points,
+        }
+    }
+}
+
+// A dashed horizontal line, e.g. the limit a value is held under
+#[derive(Clone, PartialEq)]
+pub struct ChartThreshold {
+    pub label: String,
+    pub color: String,
+    pub value: f64,
+}
+
+#[derive(Clone, PartialEq, Props)]
+pub struct LineChartProps {
+    series: Vec<ChartSeries>,
+    #[props(default)]
+    thresholds: Vec<ChartThreshold>,
+    // Fixed y range; each end that's unset fits the data and thresholds
+    #[props(default)]
+    y_min: Option<f64>,
+    #[props(default)]
+    y_max: Option<f64>,
+    // Show this much time, ending at the newest point; everything when unset
+    #[props(default)]
+    x_span_ms: Option<i64>,
+    // Milliseconds per x_unit
+    #[props(default = 60_000.0)]
+    x_scale_ms: f64,
+    #[props(default = "m".to_string())]
+    x_unit: String,
+    #[props(default)]
+    x_label: String,
+    #[props(default)]
+    y_unit: String,
+    #[props(default)]
+    y_label: String,
+    #[props(default = 800.0)]
+    width: f64,
+    #[props(default = 300.0)]
+    height: f64,
+    // Sparkline: no grid, axis labels or dots
+    #[props(default)]
+    compact: bool,
+}
+
+// Axis labels: whole numbers unless the range is small enough for decimals to matter
+pub fn format_axis_value(value: f64, range: f64) -> String {
+    if range < 10.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.0}", value)
+    }
+}
+
+// The point of `points` closest in time to `time`
+fn nearest_point(points: &[(i64, f64)], time: i64) -> Option<(i64, f64)> {
+    points.iter().copied().min_by_key(|(t, _)| (t - time).abs())
+}
+
+#[component]
+pub fn LineChart(props: LineChartProps) -> Element {
+    let LineChartProps {
+        series,
+        thresholds,
+        y_min,
+        y_max,
+        x_span_ms,
+        x_scale_ms,
+        x_unit,
+        x_label,
+        y_unit,
+        y_label,
+        width,
+        height,
+        compact,
+    } = props;
+    let chart_id = use_hook(|| NEXT_CHART_ID.fetch_add(1, Ordering::Relaxed));
+    let mut hovered = use_signal(|| None::<i64>);
+
+    // Time window
+    let max_time = series.iter().filter_map(|s| s.points.last()).map(|(t, _)| *t).max();
+    let min_time = match (max_time, x_span_ms) {
+        (Some(max_time), Some(span)) => Some(max_time - span),
+        _ => series.iter().filter_map(|s| s.points.first()).map(|(t, _)| *t).min(),
+    };
+    let series: Vec<ChartSeries> = series
+        .into_iter()
+        .map(|mut s| {
+            s.points.retain(|(t, _)| Some(*t) >= min_time);
+            s
+        })
+        .collect();
+
+    if series.iter().all(|s| s.points.len() < 2) {
+        return rsx! {
+            div {
+                class: "flex items-center justify-center text-sm text-[var(--color-base-content)]/60",
+                style: "height: {height}px",
+                "Collecting data…"
+            }
+        };
+    }
+    let (min_time, max_time) = (min_time.unwrap_or(0), max_time.unwrap_or(0));
+
+    // Chart dimensions
+    let padding = if compact { 4.0 } else { 40.0 };
+    let chart_width = width - 2.0 * padding;
+    let chart_height = height - 2.0 * padding;
+
+    // Value range; a flat line sits in the middle of the chart
+    let values = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|(_, v)| *v))
+        .chain(thresholds.iter().map(|t| t.value));
+    let (data_min, data_max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    let mut min_val = y_min.unwrap_or(data_min);
+    let mut max_val = y_max.unwrap_or(data_max);
+    if max_val < min_val + f64::EPSILON {
+        min_val -= 1.0;
+        max_val += 1.0;
+    }
+    let time_span = (max_time - min_time).max(1) as f64;
+    let span_units = time_span / x_scale_ms;
+
+    let to_x = move |time: i64| padding + ((time - min_time) as f64 / time_span) * chart_width;
+    let to_y = move |value: f64| {
+        let value = value.clamp(min_val, max_val);
+        height - padding - ((value - min_val) / (max_val - min_val)) * chart_height
+    };
+
+    // Paths for each series: (line, area under it)
+    let paths: Vec<(String, String)> = series
+        .iter()
+        .map(|s| {
+            let points: Vec<String> = s
+                .points
+                .iter()
+                .map(|(t, v)| format!("{},{}", to_x(*t), to_y(*v)))
+                .collect();
+            let first_x = s.points.first().map(|(t, _)| to_x(*t)).unwrap_or(padding);
+            let last_x = s.points.last().map(|(t, _)| to_x(*t)).unwrap_or(padding);
+            (
+                format!("M {}", points.join(" L ")),
+                format!(
+                    "M {} L {},{} L {},{} Z",
+                    points.join(" L "),
+                    last_x, height - padding,
+                    first_x, height - padding
+                ),
+            )
+        })
+        .collect();
+
+    // Dots only while they're far enough apart to tell apart
+    let show_points = !compact && series.iter().all(|s| s.points.len() <= 60);
+
+    // Hovered point of every series, and the tooltip lines
+    let hover = hovered().map(|time| {
+        let points: Vec<(String, (i64, f64))> = series
+            .iter()
+            .filter_map(|s| Some((s.color.clone(), nearest_point(&s.points, time)?)))
+            .collect();
+        let x = points.first().map(|(_, (t, _))| to_x(*t)).unwrap_or(padding);
+        let ago = points
+            .first()
+            .map(|(_, (t, _))| (max_time - t) as f64 / x_scale_ms)
+            .unwrap_or(0.0);
+        let mut lines = vec![format!("{} {} ago", format_axis_value(ago, span_units), x_unit)];
+        for (s, (_, (_, value))) in series.iter().zip(&points) {
+            lines.push(format!("{}: {}{}", s.label, format_axis_value(*value, max_val - min_val), y_unit));
+        }
+        for threshold in &thresholds {
+            lines.push(format!(
+                "{}: {}{}",
+                threshold.label,
+                format_axis_value(threshold.value, max_val - min_val),
+                y_unit
+            ));
+        }
+        (x, points, lines)
+    });
+    let font_size = if compact { 10.0 } else { 12.0 };
+
+    rsx! {
+        div { class: "w-full overflow-x-auto",
+            svg {
+                width: "{width}",
+                height: "{height}",
+                view_box: "0 0 {width} {height}",
+                class: "w-full h-auto",
+                onmouseleave: move |_| hovered.set(None),
+
+                // Gradient definitions, one per series
+                defs {
+                    for (i, s) in series.iter().enumerate() {
+                        linearGradient {
+                            id: "chart{chart_id}-{i}",
+                            x1: "0%",
+                            y1: "0%",
+                            x2: "0%",
+                            y2: "100%",
+                            stop { offset: "0%", stop_color: "{s.color}", stop_opacity: "0.3" }
+                            stop { offset: "100%", stop_color: "{s.color}", stop_opacity: "0.05" }
+                        }
+                    }
+                }
+
+                if !compact {
+                    // Grid lines (horizontal)
+                    for i in 0..5 {
+                        {
+                            let y = padding + (i as f64 * chart_height / 4.0);
+                            let val = format_axis_value(
+                                max_val - i as f64 * (max_val - min_val) / 4.0,
+                                max_val - min_val,
+                            );
+                            rsx! {
+                                line {
+                                    x1: "{padding}",
+                                    y1: "{y}",
+                                    x2: "{width - padding}",
+                                    y2: "{y}",
+                                    stroke: "rgba(128, 128, 128, 0.2)",
+                                    stroke_width: "1"
+                                }
+                                text {
+                                    x: "{padding - 5.0}",
+                                    y: "{y + 4.0}",
+                                    text_anchor: "end",
+                                    fill: "var(--color-base-content)",
+                                    opacity: "0.7",
+                                    font_size: "12",
+                                    "{val}{y_unit}"
+                                }
+                            }
+                        }
+                    }
+
+                    // Grid lines (vertical), labelled with how long ago relative to the newest sample
+                    for i in 0..5 {
+                        {
+                            let x = padding + (i as f64 * chart_width / 4.0);
+                            let ago = format_axis_value(span_units * (4 - i) as f64 / 4.0, span_units);
+                            rsx! {
+                                line {
+                                    x1: "{x}",
+                                    y1: "{padding}",
+                                    x2: "{x}",
+                                    y2: "{height - padding}",
+                                    stroke: "rgba(128, 128, 128, 0.2)",
+                                    stroke_width: "1"
+                                }
+                                text {
+                                    x: "{x}",
+                                    y: "{height - padding + 20.0}",
+                                    text_anchor: "middle",
+                                    fill: "var(--color-base-content)",
+                                    opacity: "0.7",
+                                    font_size: "12",
+                                    "{ago}{x_unit}"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for (i, (s, (line_path, area_path))) in series.iter().zip(&paths).enumerate() {
+                    // Area under the line
+                    path {
+                        d: "{area_path}",
+                        fill: "url(#chart{chart_id}-{i})"
+                    }
+
+                    // Line
+                    path {
+                        d: "{line_path}",
+                        stroke: "{s.color}",
+                        stroke_width: "2",
+                        fill: "none",
+                        stroke_linejoin: "round",
+                        stroke_linecap: "round"
+                    }
+
+                    // Data points
+                    if show_points {
+                        for (time, value) in s.points.iter() {
+                            circle {
+                                cx: "{to_x(*time)}",
+                                cy: "{to_y(*value)}",
+                                r: "4",
+                                fill: "{s.color}",
+                                stroke: "white",
+                                stroke_width: "2"
+                            }
+                        }
+                    }
+                }
+
+                // Thresholds
+                for threshold in thresholds.iter() {
+                    line {
+                        x1: "{padding}",
+                        y1: "{to_y(threshold.value)}",
+                        x2: "{width - padding}",
+                        y2: "{to_y(threshold.value)}",
+                        stroke: "{threshold.color}",
+                        stroke_width: "1.5",
+                        stroke_dasharray: "4 3"
+                    }
+                }
+
+                // Hovered point and tooltip
+                if let Some((x, points, lines)) = hover {
+                    line {
+                        x1: "{x}",
+                        y1: "{padding}",
+                        x2: "{x}",
+                        y2: "{height - padding}",
+                        stroke: "var(--color-base-content)",
+                        stroke_opacity: "0.4",
+                        stroke_width: "1"
+                    }
+                    for (color, (time, value)) in points.iter() {
+                        circle {
+                            cx: "{to_x(*time)}",
+                            cy: "{to_y(*value)}",
+                            r: "3.5",
+                            fill: "{color}",
+                            stroke: "white",
+                            stroke_width: "1.5"
+                        }
+                    }
+                    {
+                        let line_height = font_size + 4.0;
+                        let box_width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as f64
+                            * font_size * 0.6 + 12.0;
+                        let box_height = lines.len() as f64 * line_height + 8.0;
+                        // Keep the box inside the chart
+                        let box_x = if x + 8.0 + box_width > width { x - 8.0 - box_width } else { x + 8.0 };
+                        let box_y = padding.max(4.0);
+                        rsx! {
+                            rect {
+                                x: "{box_x}",
+                                y: "{box_y}",
+                                width: "{box_width}",
+                                height: "{box_height}",
+                                rx: "4",
+                                fill: "var(--color-base-300)",
+                                fill_opacity: "0.95"
+                            }
+                            for (i, line) in lines.iter().enumerate() {
+                                text {
+                                    x: "{box_x + 6.0}",
+                                    y: "{box_y + 4.0 + (i + 1) as f64 * line_height - 4.0}",
+                                    fill: "var(--color-base-content)",
+                                    font_size: "{font_size}",
+                                    "{line}"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Hover targets, one column each
+                for col in 0..HOVER_COLUMNS {
+                    {
+                        let column_width = chart_width / HOVER_COLUMNS as f64;
+                        let time = min_time + ((col as f64 + 0.5) / HOVER_COLUMNS as f64 * time_span) as i64;
+                        rsx! {
+                            rect {
+                                x: "{padding + col as f64 * column_width}",
+                                y: "0",
+                                width: "{column_width}",
+                                height: "{height}",
+                                fill: "transparent",
+                                onmouseenter: move |_| hovered.set(Some(time)),
+                            }
+                        }
+                    }
+                }
+
+                if !compact {
+                    // Axes labels
+                    text {
+                        x: "{width / 2.0}",
+                        y: "{height - 2.0}",
+                        text_anchor: "middle",
+                        fill: "var(--color-base-content)",
+                        opacity: "0.6",
+                        font_size: "11",
+                        "{x_label}"
+                    }
+
+                    text {
+                        x: "{8.0}",
+                        y: "{height / 2.0}",
+                        text_anchor: "middle",
+                        fill: "var(--color-base-content)",
+                        opacity: "0.6",
+                        font_size: "11",
+                        transform: "rotate(-90, 8, {height / 2.0})",
+                        "{y_label}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use dioxus::prelude::*;
+
+use crate::utils::conversions::{format_frequency_mhz, format_power, format_temp};
+use crate::utils::types::{AppSettings, CurrentStats};
+
+#[derive(Clone, Copy, PartialEq)]
+enum CoreMetric {
+    Clock,
+    Power,
+    Temperature,
+}
+
+impl CoreMetric {
+    const ALL: [CoreMetric; 3] = [CoreMetric::Clock, CoreMetric::Power, CoreMetric::Temperature];
+
+    fn label(self) -> &'static str {
+        match self {
+            CoreMetric::Clock => "Clock",
+            CoreMetric::Power => "Power",
+            CoreMetric::Temperature => "Temperature",
+        }
+    }
+
+    fn values(self, stats: &CurrentStats) -> &[i32] {
+        match self {
+            CoreMetric::Clock => &stats.core_clocks_mhz,
+            CoreMetric::Power => &stats.core_powers_mw,
+            CoreMetric::Temperature => &stats.core_temperatures_c,
+        }
+    }
+}
+
+// One heatmap cell
+#[derive(Clone, PartialEq)]
+struct CoreCell {
+    index: usize,
+    clock_mhz: i32,
+    power_mw: Option<i32>,
+    temp_c: Option<i32>,
+    heat_percent: i32, // share of the hottest core for the selected metric
+}
+
+#[component]
+pub fn Cpu() -> Element {
+    let settings = use_context::<Signal<AppSettings>>();
+    let stats = use_context::<SyncSignal<CurrentStats>>();
+    let mut heat_metric = use_signal(|| CoreMetric::Clock);
+    let stats_now = stats.read();
+
+    let power_unit = settings().units.power.clone();
+    let temp_unit = settings().units.temp.clone();
+    let metric = heat_metric();
+
+    // Fall back to clocks if the table doesn't report the selected metric
+    let heat_values = match metric.values(&stats_now) {
+        [] => &stats_now.core_clocks_mhz,
+        values => values,
+    };
+    let heat_max = heat_values.iter().copied().max().unwrap_or(0).max(1);
+
+    let cells: Vec<CoreCell> = stats_now
+        .core_clocks_mhz
+        .iter()
+        .enumerate()
+        .map(|(index, clock_mhz)| CoreCell {
+            index,
+            clock_mhz: *clock_mhz,
+            power_mw: stats_now.core_powers_mw.get(index).copied(),
+            temp_c: stats_now.core_temperatures_c.get(index).copied(),
+            heat_percent: heat_values.get(index).map_or(0, |v| (v * 100 / heat_max).clamp(0, 100)),
+        })
+        .collect();
+
+    // Cores grouped by L3 complex, in the order they first appear
+    let mut groups: Vec<(Option<i32>, Vec<CoreCell>)> = Vec::new();
+    for cell in cells {
+        let ccx = stats_now.core_ccx.get(cell.index).copied().filter(|id| *id >= 0);
+        match groups.iter_mut().find(|(id, _)| *id == ccx) {
+            Some((_, members)) => members.push(cell),
+            None => groups.push((ccx, vec![cell])),
+        }
+    }
+
+    let active = stats_now.core_clocks_mhz.iter().filter(|clk| **clk > 0).count();
+    let total = stats_now.core_clocks_mhz.len();
+    let profile_text = format!("{} / {}", stats_now.profile, stats_now.sub_profile);
+    let max_clock_text = format_frequency_mhz(stats_now.cpu_frequency_mhz);
+
+    rsx! {
+        div { class: "p-8 max-w-[1600px] mx-auto",
+            div { class: "grid grid-cols-3 gap-6 mb-6",
+                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2", "ACTIVE CORES" }
+                    div { class: "text-4xl font-bold text-[var(--color-primary)]", "{active} / {total}" }
+                }
+                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2", "HIGHEST CLOCK" }
+                    div { class: "text-4xl font-bold text-[var(--color-primary)]", "{max_clock_text}" }
+                }
+                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2", "PROFILE" }
+                    div { class: "text-xl font-bold text-[var(--color-primary)]", "{profile_text}" }
+                }
+            }
+
+            div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                div { class: "flex items-center justify-between mb-4",
+                    div { class: "text-sm font-semibold text-[var(--color-secondary)]", "PER-CORE HEATMAP" }
+                    div { class: "flex gap-2",
+                        for option in CoreMetric::ALL {
+                            button {
+                                class: if metric == option {
+                                    "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                                } else {
+                                    "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                                },
+                                disabled: option.values(&stats_now).is_empty(),
+                                onclick: move |_| heat_metric.set(option),
+                                "{option.label()}"
+                            }
+                        }
+                    }
+                }
+
+                if !stats_now.ryzenadj_available {
+                    div { class: "py-8 text-center text-sm text-[var(--color-base-content)]/60",
+                        "Unavailable: RyzenAdj could not be initialised"
+                    }
+                } else if total == 0 {
+                    div { class: "py-8 text-center text-sm text-[var(--color-base-content)]/60",
+                        "This CPU's SMU table doesn't report per-core values"
+                    }
+                } else {
+                    div { class: "space-y-4",
+                        for (ccx, members) in groups {
+                            div { key: "{ccx:?}",
+                                div { class: "text-xs text-[var(--color-base-content)]/70 mb-2",
+                                    {match ccx {
+                                        Some(id) => format!("CCX {}", id),
+                                        None => "Cores".to_string(),
+                                    }}
+                                }
+                                div { class: "grid grid-cols-8 gap-2",
+                                    for cell in members {
+                                        CoreTile {
+                                            key: "{cell.index}",
+                                            cell,
+                                            power_unit: power_unit.clone(),
+                                            temp_unit: temp_unit.clone(),
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CoreTile(cell: CoreCell, power_unit: String, temp_unit: String) -> Element {
+    let parked = cell.clock_mhz <= 0;
+    // Mix the primary color into the card background by how hot the core is
+    let style = if parked {
+        "background: var(--color-base-300); opacity: 0.5".to_string()
+    } else {
+        format!(
+            "background: color-mix(in srgb, var(--color-primary) {}%, var(--color-base-300))",
+            cell.heat_percent.max(10)
+        )
+    };
+    let clock_text = if parked { "parked".to_string() } else { format_frequency_mhz(cell.clock_mhz) };
+
+    rsx! {
+        div { class: "rounded-lg p-3 text-[var(--color-base-content)]", style: "{style}",
+            div { class: "text-xs opacity-70", "Core {cell.index}" }
+            div { class: "text-lg font-bold", "{clock_text}" }
+            if let Some(power_mw) = cell.power_mw {
+                div { class: "text-xs", "{format_power(power_mw, &power_unit)}" }
+            }
+            if let Some(temp_c) = cell.temp_c {
+                div { class: "text-xs", "{format_temp(temp_c, &temp_unit)}" }
+            }
+        }
+    }
+}
@@ -1,58 +1,105 @@
 use dioxus::prelude::*;
 
+use crate::components::chart::{ChartSeries, ChartThreshold, LineChart};
+use crate::utils::conversions::{
+    battery_status_text, format_frequency_mhz, format_power, format_temp, power_unit_label,
+    temp_conversion, temp_unit_label,
+};
+use crate::utils::metrics::{Metric, MetricsHistory};
+use crate::utils::types::{AppSettings, CurrentStats};
+
+const UNAVAILABLE_TEXT: &str = "RyzenAdj could not be initialised";
+
+// Sparkline windows, in seconds
+const WINDOWS_S: [i64; 3] = [60, 300, 600];
+const LINE_COLOR: &str = "rgb(59, 130, 246)";
+const LIMIT_COLOR: &str = "rgb(239, 68, 68)";
+
+// Green below half, amber below 80%, red above
+fn bar_class(percent: i32) -> &'static str {
+    match percent {
+        i32::MIN..=49 => "h-full bg-[var(--color-success)] rounded-full",
+        50..=79 => "h-full bg-[var(--color-warning)] rounded-full",
+        _ => "h-full bg-[var(--color-error)] rounded-full",
+    }
+}
+
 #[component]
 pub fn Dashboard() -> Element {
+    let settings = use_context::<Signal<AppSettings>>();
+    let stats = use_context::<SyncSignal<CurrentStats>>();
+    let history = use_context::<SyncSignal<MetricsHistory>>();
+    let mut window_s = use_signal(|| 300i64);
+    let stats_now = stats.read();
+
+    let power_unit = settings().units.power.clone();
+    let temp_unit = settings().units.temp.clone();
+    let available = stats_now.ryzenadj_available;
+
+    // Sparkline data from the stats thread, in the user's units
+    let window_ms = window_s() * 1000;
+    let to_power = |mw: i32| if power_unit == "milliwatt" { mw as f64 } else { mw as f64 / 1000.0 };
+    let to_temp = |c: i32| temp_conversion(c as f32, "celsius", &temp_unit) as f64;
+    let history_now = history.read();
+    let since = history_now.latest().map(|s| s.timestamp_ms - window_ms).unwrap_or(0);
+    let points = |metric: Metric, convert: &dyn Fn(i32) -> f64| -> Vec<(i64, f64)> {
+        history_now
+            .series(metric, since)
+            .into_iter()
+            .map(|(t, v)| (t, convert(v)))
+            .collect()
+    };
+    let as_is = |v: i32| v as f64;
+    let power_label = power_unit_label(&power_unit);
+    let temp_label = temp_unit_label(&temp_unit);
+
+    let battery_percent = stats_now.batt_charge_percent.clamp(0, 100);
+    let status_text = battery_status_text(&stats_now.batt_charge_status);
+    let power_draw_text = format_power(stats_now.power_draw_mw, &power_unit);
+
     rsx! {
         div { class: "p-8 max-w-[1600px] mx-auto",
 
+            // Sparkline window
+            div { class: "flex justify-end gap-2 mb-4",
+                for option in WINDOWS_S {
+                    button {
+                        class: if window_s() == option {
+                            "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                        } else {
+                            "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                        },
+                        onclick: move |_| window_s.set(option),
+                        "{option / 60} min"
+                    }
+                }
+            }
+
             // Stats Grid - Row 1
             div { class: "grid grid-cols-3 gap-6 mb-6",
                 // CPU Card
-                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
-                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
-                        "CPU"
-                    }
-                    div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
-                        "Frequency"
-                    }
-                    div { class: "text-4xl font-bold text-[var(--color-primary)] mb-4",
-                        "4.2 GHz"
-                    }
-                    div { class: "space-y-2 text-sm text-[var(--color-base-content)]/70",
-                        div { "Temperature: 62 °C" }
-                        div { "Load: 45%" }
-                    }
-                    // Progress bar
-                    div { class: "mt-4",
-                        div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                            div { class: "h-full bg-[var(--color-success)] rounded-full", style: "width: 20%" }
-                        }
-                    }
+                UsageCard {
+                    title: "CPU",
+                    available,
+                    frequency_text: format_frequency_mhz(stats_now.cpu_frequency_mhz),
+                    temp_text: format_temp(stats_now.cpu_temperature_c, &temp_unit),
+                    load_percent: stats_now.cpu_load_percent,
+                    points: points(Metric::CpuLoad, &as_is),
+                    window_ms,
                 }
 
                 // GPU Card
-                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
-                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
-                        "GPU"
-                    }
-                    div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
-                        "Frequency"
-                    }
-                    div { class: "text-4xl font-bold text-[var(--color-primary)] mb-4",
-                        "1.8 GHz"
-                    }
-                    div { class: "space-y-2 text-sm text-[var(--color-base-content)]/70",
-                        div { "Temperature: 58 °C" }
-                        div { "Load: 72 %" }
-                    }
-                    div { class: "mt-4",
-                        div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                            div { class: "h-full bg-[var(--color-warning)] rounded-full", style: "width: 50%" }
-                        }
-                    }
+                UsageCard {
+                    title: "GPU",
+                    available,
+                    frequency_text: format_frequency_mhz(stats_now.gpu_frequency_mhz),
+                    temp_text: format_temp(stats_now.gpu_temperature_c, &temp_unit),
+                    load_percent: stats_now.gpu_load_percent,
+                    points: points(Metric::GpuLoad, &as_is),
+                    window_ms,
                 }
 
-                // SOC Card
+                // Power Card, from the battery so it works without RyzenAdj
                 div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
                     div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
                         "POWER"
@@ -61,78 +108,213 @@ pub fn Dashboard() -> Element {
                         "Load"
                     }
                     div { class: "text-4xl font-bold text-[var(--color-primary)] mb-4",
-                        "0 W"
+                        "{power_draw_text}"
                     }
                     div { class: "space-y-2 text-sm text-[var(--color-base-content)]/70",
-                        div { "Status: Charging" }
-                        div { "Charge: 80 %" }
+                        div { "Status: {status_text}" }
+                        div { "Charge: {battery_percent} %" }
                     }
                     div { class: "mt-4",
                         div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                            div { class: "h-full bg-[var(--color-error)] rounded-full", style: "width: 90%" }
+                            div { class: "h-full bg-[var(--color-success)] rounded-full", style: "width: {battery_percent}%" }
                         }
                     }
+                    Sparkline {
+                        series: ChartSeries::new("Power draw", LINE_COLOR, points(Metric::PowerDraw, &to_power)),
+                        y_unit: power_label,
+                        window_ms,
+                    }
                 }
             }
 
             // Stats Grid - Row 2
-            div { class: "grid grid-cols-3 gap-6 mb-8",
-                // Fast Limit
-                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
-                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
-                        "FAST LIMIT"
-                    }
-                    div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
-                        "Current Value:"
-                    }
-                    div { class: "text-2xl font-bold text-[var(--color-primary)] mb-2",
-                        "15 W"
-                    }
-                    div { class: "text-sm text-[var(--color-base-content)]/70 mb-2",
-                        "Limit: 25 W"
-                    }
-                    div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                        div { class: "h-full bg-[var(--color-success)] rounded-full", style: "width: 77%" }
-                    }
+            div { class: "grid grid-cols-4 gap-6 mb-8",
+                LimitCard {
+                    title: "FAST LIMIT",
+                    available,
+                    value_text: format_power(stats_now.curr_fast_value_mw, &power_unit),
+                    limit_text: format_power(stats_now.curr_fast_limit_mw, &power_unit),
+                    percent: stats_now.curr_fast_percent,
+                    points: points(Metric::FastValue, &to_power),
+                    limit: to_power(stats_now.curr_fast_limit_mw),
+                    unit: power_label,
+                    window_ms,
+                }
+                LimitCard {
+                    title: "SLOW LIMIT",
+                    available,
+                    value_text: format_power(stats_now.curr_slow_value_mw, &power_unit),
+                    limit_text: format_power(stats_now.curr_slow_limit_mw, &power_unit),
+                    percent: stats_now.curr_slow_percent,
+                    points: points(Metric::SlowValue, &to_power),
+                    limit: to_power(stats_now.curr_slow_limit_mw),
+                    unit: power_label,
+                    window_ms,
                 }
+                LimitCard {
+                    title: "STAPM LIMIT",
+                    available,
+                    value_text: format_power(stats_now.curr_stapm_value_mw, &power_unit),
+                    limit_text: format_power(stats_now.curr_stapm_limit_mw, &power_unit),
+                    percent: stats_now.curr_stapm_percent,
+                    points: points(Metric::StapmValue, &to_power),
+                    limit: to_power(stats_now.curr_stapm_limit_mw),
+                    unit: power_label,
+                    window_ms,
+                }
+                LimitCard {
+                    title: "TCTL LIMIT",
+                    available,
+                    value_text: format_temp(stats_now.curr_tctl_value_c, &temp_unit),
+                    limit_text: format_temp(stats_now.curr_tctl_limit_c, &temp_unit),
+                    percent: stats_now.curr_tctl_percent,
+                    points: points(Metric::TctlValue, &to_temp),
+                    limit: to_temp(stats_now.curr_tctl_limit_c),
+                    unit: temp_label,
+                    window_ms,
+                }
+            }
+        }
+    }
+}
 
-                // Slow Limit
-                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
-                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
-                        "SLOW LIMIT"
-                    }
-                    div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
-                        "Current Value"
-                    }
-                    div { class: "text-2xl font-bold text-[var(--color-primary)] mb-2",
-                        "15 W"
-                    }
-                    div { class: "text-sm text-[var(--color-base-content)]/70 mb-2",
-                        "Limit: 25 W"
-                    }
+// CPU / GPU card
+#[component]
+fn UsageCard(
+    title: &'static str,
+    available: bool,
+    frequency_text: String,
+    temp_text: String,
+    load_percent: i32,
+    points: Vec<(i64, f64)>,
+    window_ms: i64,
+) -> Element {
+    let load_percent = load_percent.clamp(0, 100);
+
+    rsx! {
+        div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+            div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
+                "{title}"
+            }
+            div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
+                "Frequency"
+            }
+            if available {
+                div { class: "text-4xl font-bold text-[var(--color-primary)] mb-4",
+                    "{frequency_text}"
+                }
+                div { class: "space-y-2 text-sm text-[var(--color-base-content)]/70",
+                    div { "Temperature: {temp_text}" }
+                    div { "Load: {load_percent} %" }
+                }
+                div { class: "mt-4",
                     div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                        div { class: "h-full bg-[var(--color-warning)] rounded-full", style: "width: 90%" }
+                        div { class: bar_class(load_percent), style: "width: {load_percent}%" }
                     }
                 }
+                Sparkline {
+                    series: ChartSeries::new("Load", LINE_COLOR, points),
+                    y_unit: "%",
+                    y_range: (0.0, 100.0),
+                    window_ms,
+                }
+            } else {
+                UnavailableBody {}
+            }
+        }
+    }
+}
 
-                // STAPM Limit
-                div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
-                    div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
-                        "STAPM LIMIT"
-                    }
-                    div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
-                        "Current Value"
-                    }
-                    div { class: "text-2xl font-bold text-[var(--color-primary)] mb-2",
-                        "15 W"
-                    }
-                    div { class: "text-sm text-[var(--color-base-content)]/70 mb-2",
-                        "Limit: 25 W"
-                    }
-                    div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
-                        div { class: "h-full bg-[var(--color-error)] rounded-full", style: "width: 77%" }
-                    }
+#[component]
+fn LimitCard(
+    title: &'static str,
+    available: bool,
+    value_text: String,
+    limit_text: String,
+    percent: i32,
+    points: Vec<(i64, f64)>,
+    limit: f64,
+    unit: &'static str,
+    window_ms: i64,
+) -> Element {
+    let percent = percent.clamp(0, 100);
+
+    rsx! {
+        div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+            div { class: "text-sm font-semibold text-[var(--color-secondary)] mb-2",
+                "{title}"
+            }
+            div { class: "text-xs text-[var(--color-base-content)]/70 mb-1",
+                "Current Value"
+            }
+            if available {
+                div { class: "text-2xl font-bold text-[var(--color-primary)] mb-2",
+                    "{value_text}"
+                }
+                div { class: "text-sm text-[var(--color-base-content)]/70 mb-2",
+                    "Limit: {limit_text}"
+                }
+                div { class: "w-full h-2 bg-[var(--color-base-300)] rounded-full overflow-hidden",
+                    div { class: bar_class(percent), style: "width: {percent}%" }
                 }
+                Sparkline {
+                    series: ChartSeries::new("Value", LINE_COLOR, points),
+                    limit,
+                    y_unit: unit,
+                    window_ms,
+                }
+            } else {
+                UnavailableBody {}
+            }
+        }
+    }
+}
+
+#[component]
+fn UnavailableBody() -> Element {
+    rsx! {
+        div { class: "text-2xl font-bold text-[var(--color-base-content)]/40 mb-2",
+            "Unavailable"
+        }
+        div { class: "text-sm text-[var(--color-base-content)]/60",
+            "{UNAVAILABLE_TEXT}"
+        }
+    }
+}
+
+// Rolling history under a card; `limit` is drawn as a threshold line
+#[component]
+fn Sparkline(
+    series: ChartSeries,
+    #[props(default)] limit: Option<f64>,
+    y_unit: &'static str,
+    #[props(default)] y_range: Option<(f64, f64)>,
+    window_ms: i64,
+) -> Element {
+    let thresholds: Vec<ChartThreshold> = limit
+        .filter(|limit| *limit > 0.0)
+        .map(|value| ChartThreshold {
+            label: "Limit".to_string(),
+            color: LIMIT_COLOR.to_string(),
+            value,
+        })
+        .into_iter()
+        .collect();
+
+    rsx! {
+        div { class: "mt-4",
+            LineChart {
+                series: vec![series],
+                thresholds,
+                y_min: y_range.map(|(min, _)| min),
+                y_max: y_range.map(|(_, max)| max),
+                x_span_ms: window_ms,
+                x_scale_ms: 1000.0,
+                x_unit: "s",
+                y_unit: " {y_unit}",
+                width: 300.0,
+                height: 60.0,
+                compact: true,
             }
         }
     }
@@ -119,7 +119,7 @@ pub fn Info() -> Element {
                                 "Use this app to monitor your Ryzen processor / SOC power draw, battery and change power profiles."
                             }
                             div { class: "text-sm text-[var(--color-primary)]/70",
-                                "The app is only used to view / change settings. Once settings are applied, this app can be saftely closed"
+                                "The app is only used to view / change settings. With the Ryzoned daemon running, this app can be safely closed; without it, closing the app restores the firmware defaults"
                             }
                         }
                     }
@@ -128,7 +128,7 @@ pub fn Info() -> Element {
                         title: "Daemon",
                         content: rsx! {
                             div { class: "text-sm text-[var(--color-base-content)]/70 mb-2",
-                                "All implementation and tracking of the settings are done via the Ryzoned systemd background daemon. The Ryzoned daemon works in the background and is set to start on log in. You do not need to keep this app open to have the settings work"
+                                "While this app is open it applies the active profile itself. To keep the settings applied after closing it, run the Ryzoned background daemon: enable the bundled systemd user service (dist/ryzoned.service, see the README) to start it on log in. When the daemon is running, this app leaves enforcement to it"
                             }
                         }
                     }
@@ -0,0 +1,107 @@
+use dioxus::prelude::*;
+
+use crate::utils::logs::{event_log_path, read_event_log, LogEntry, LogLevel};
+use crate::utils::metrics::MetricsHistory;
+
+const REFRESH_MS: i64 = 5000;
+
+fn level_class(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Error => "px-2 py-0.5 rounded text-xs font-semibold uppercase bg-[var(--color-error)]/20 text-[var(--color-error)]",
+        LogLevel::Warn => "px-2 py-0.5 rounded text-xs font-semibold uppercase bg-[var(--color-warning)]/20 text-[var(--color-warning)]",
+        LogLevel::Info => "px-2 py-0.5 rounded text-xs font-semibold uppercase bg-[var(--color-primary)]/20 text-[var(--color-primary)]",
+        LogLevel::Debug => "px-2 py-0.5 rounded text-xs font-semibold uppercase bg-[var(--color-base-300)] text-[var(--color-base-content)]/70",
+    }
+}
+
+#[component]
+pub fn Log() -> Element {
+    // Re-read the file every few seconds, piggybacking on the stats samples
+    let history = use_context::<SyncSignal<MetricsHistory>>();
+    let tick = use_memo(move || history.read().latest().map(|s| s.timestamp_ms / REFRESH_MS));
+    let mut refresh = use_signal(|| 0u32);
+    let entries = use_memo(move || {
+        let _ = (tick(), refresh());
+        read_event_log().map_err(|e| e.to_string())
+    });
+
+    let mut shown_levels = use_signal(|| LogLevel::ALL.to_vec());
+    let path_text = event_log_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+
+    let (visible, error): (Vec<LogEntry>, Option<String>) = match &*entries.read() {
+        Ok(all) => (
+            all.iter()
+                .rev()
+                .filter(|entry| shown_levels.read().contains(&entry.level))
+                .cloned()
+                .collect(),
+            None,
+        ),
+        Err(e) => (Vec::new(), Some(e.clone())),
+    };
+
+    rsx! {
+        div { class: "p-8 max-w-[1600px] mx-auto",
+            div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                div { class: "flex items-center justify-between mb-4",
+                    div {
+                        div { class: "text-sm font-semibold text-[var(--color-secondary)]", "EVENT LOG" }
+                        div { class: "text-xs text-[var(--color-base-content)]/60", "{path_text}" }
+                    }
+
+                    // Level filters
+                    div { class: "flex gap-2",
+                        for level in LogLevel::ALL {
+                            button {
+                                class: if shown_levels.read().contains(&level) {
+                                    "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm capitalize"
+                                } else {
+                                    "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm capitalize hover:bg-[var(--color-base-300)]/80"
+                                },
+                                onclick: move |_| {
+                                    let mut levels = shown_levels.write();
+                                    match levels.iter().position(|l| *l == level) {
+                                        Some(i) => {
+                                            levels.remove(i);
+                                        }
+                                        None => levels.push(level),
+                                    }
+                                },
+                                "{level.as_str()}"
+                            }
+                        }
+                        button {
+                            class: "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80",
+                            onclick: move |_| refresh += 1,
+                            "Refresh"
+                        }
+                    }
+                }
+
+                if let Some(message) = error {
+                    div { class: "mb-4 px-4 py-3 rounded-lg bg-[var(--color-error)]/20 text-sm", "{message}" }
+                }
+
+                if visible.is_empty() {
+                    div { class: "py-8 text-center text-sm text-[var(--color-base-content)]/60",
+                        "No entries"
+                    }
+                } else {
+                    // Newest first
+                    div { class: "divide-y divide-[var(--color-base-300)]",
+                        for (i, entry) in visible.into_iter().enumerate() {
+                            div { key: "{i}", class: "grid grid-cols-[14rem_5rem_8rem_1fr] gap-3 py-2 text-sm items-start",
+                                span { class: "font-mono text-xs text-[var(--color-base-content)]/70", "{entry.timestamp}" }
+                                span { span { class: level_class(entry.level), "{entry.level.as_str()}" } }
+                                span { class: "text-[var(--color-base-content)]/70", "{entry.context}" }
+                                span { class: "break-words", "{entry.message}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
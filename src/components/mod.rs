@@ -3,16 +3,23 @@
 //! component  to be used in our app.
 
 
+mod chart;
+mod cpu;
 mod dashboard;
 mod profiles;
 mod settings;
 mod battery;
 mod info;
+mod log;
+mod pm_table;
 mod navbar;
 
 pub use dashboard::Dashboard;
+pub use cpu::Cpu;
 pub use profiles::Profiles;
 pub use settings::Settings;
 pub use battery::Battery;
 pub use info::Info;
+pub use log::Log;
+pub use pm_table::PmTableExplorer;
 pub use navbar::Navbar;
@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 use crate::utils::types::{AppSettings, ProfileSettings};
-use crate::utils::settings::{write_app_settings, write_profile_settings};
+use crate::utils::settings::write_profile_settings;
+use super::settings::save_app_settings;
 
 
 #[component]
@@ -18,6 +19,7 @@ pub fn Navbar(mut active_tab: Signal<String>) -> Element {
 
     // Active profile
     let active_profile = profile().active_profile.clone();
+    let profile_names = profile().profile_names();
 
     rsx! {
         // Top Navigation Bar
@@ -35,6 +37,15 @@ pub fn Navbar(mut active_tab: Signal<String>) -> Element {
                     onclick: move |_| active_tab.set("dashboard".to_string()),
                     "Dashboard"
                 }
+                button {
+                    class: if active_tab() == "cpu" {
+                        "px-6 py-3 font-semibold border-b-2 border-[var(--color-primary)] text-[var(--color-base-content)] bg-[var(--color-base-200)] transition-colors"
+                    } else {
+                        "px-6 py-3 font-semibold border-b-2 border-transparent text-[var(--color-base-content)]/70 hover:text-[var(--color-base-content)] hover:border-[var(--color-base-content)]/30 transition-colors"
+                    },
+                    onclick: move |_| active_tab.set("cpu".to_string()),
+                    "CPU"
+                }
                 button {
                     class: if active_tab() == "battery" {
                         "px-6 py-3 font-semibold border-b-2 border-[var(--color-primary)] text-[var(--color-base-content)] bg-[var(--color-base-200)] transition-colors"
@@ -62,6 +73,24 @@ pub fn Navbar(mut active_tab: Signal<String>) -> Element {
                     onclick: move |_| active_tab.set("settings".to_string()),
                     "Settings"
                 }
+                button {
+                    class: if active_tab() == "log" {
+                        "px-6 py-3 font-semibold border-b-2 border-[var(--color-primary)] text-[var(--color-base-content)] bg-[var(--color-base-200)] transition-colors"
+                    } else {
+                        "px-6 py-3 font-semibold border-b-2 border-transparent text-[var(--color-base-content)]/70 hover:text-[var(--color-base-content)] hover:border-[var(--color-base-content)]/30 transition-colors"
+                    },
+                    onclick: move |_| active_tab.set("log".to_string()),
+                    "Log"
+                }
+                button {
+                    class: if active_tab() == "pm_table" {
+                        "px-6 py-3 font-semibold border-b-2 border-[var(--color-primary)] text-[var(--color-base-content)] bg-[var(--color-base-200)] transition-colors"
+                    } else {
+                        "px-6 py-3 font-semibold border-b-2 border-transparent text-[var(--color-base-content)]/70 hover:text-[var(--color-base-content)] hover:border-[var(--color-base-content)]/30 transition-colors"
+                    },
+                    onclick: move |_| active_tab.set("pm_table".to_string()),
+                    "PM Table"
+                }
                 button {
                     class: if active_tab() == "info" {
                         "px-6 py-3 font-semibold border-b-2 border-[var(--color-primary)] text-[var(--color-base-content)] bg-[var(--color-base-200)] transition-colors"
@@ -81,7 +110,7 @@ pub fn Navbar(mut active_tab: Signal<String>) -> Element {
                     title: "Toggle Theme",
                     onclick: move |_| {
                         settings.write().style.theme_mode = if theme_mode == "dark" { "light".to_string() } else { "dark".to_string() };
-                        let _ = write_app_settings(&settings());
+                        save_app_settings(&settings());
                     },
                     // Sun/Moon icon using SVG
                     if theme_mode == "dark" {
@@ -139,42 +168,26 @@ pub fn Navbar(mut active_tab: Signal<String>) -> Element {
                             class: "fixed inset-0 z-40",
                             onclick: move |_| show_profile_dropdown.set(false),
                         }
-                        div { class: "absolute right-0 mt-2 w-40 bg-[var(--color-base-200)] border border-[var(--color-base-300)] rounded-lg shadow-lg z-50",
-                            button {
-                                class: "w-full text-left px-4 py-2 hover:bg-[var(--color-base-300)] transition-colors rounded-t-lg",
-                                onclick: move |_| {
-                                    profile.write().active_profile = "system".to_string();
-                                    let _ = write_profile_settings(&profile());
-                                    show_profile_dropdown.set(false);
-                                },
-                                "System"
-                            }
-                            button {
-                                class: "w-full text-left px-4 py-2 hover:bg-[var(--color-base-300)] transition-colors",
-                                onclick: move |_| {
-                                    profile.write().active_profile = "custom".to_string();
-                                    let _ = write_profile_settings(&profile());
-                                    show_profile_dropdown.set(false);
-                                },
-                                "Custom"
-                            }
-                            button {
-                                class: "w-full text-left px-4 py-2 hover:bg-[var(--color-base-300)] transition-colors",
-                                onclick: move |_| {
-                                    profile.write().active_profile = "turbo".to_string();
-                                    let _ = write_profile_settings(&profile());
-                                    show_profile_dropdown.set(false);
-                                },
-                                "Turbo"
-                            }
-                            button {
-                                class: "w-full text-left px-4 py-2 hover:bg-[var(--color-base-300)] transition-colors rounded-b-lg",
-                                onclick: move |_| {
-                                    profile.write().active_profile = "fixed".to_string();
-                                    let _ = write_profile_settings(&profile());
-                                    show_profile_dropdown.set(false);
-                                },
-                                "Fixed"
+                        div { class: "absolute right-0 mt-2 w-48 max-h-96 overflow-y-auto bg-[var(--color-base-200)] border border-[var(--color-base-300)] rounded-lg shadow-lg z-50 py-1",
+                            for name in profile_names {
+                                button {
+                                    key: "{name}",
+                                    class: if name == active_profile {
+                                        "w-full text-left px-4 py-2 bg-[var(--color-base-300)] font-semibold capitalize transition-colors"
+                                    } else {
+                                        "w-full text-left px-4 py-2 hover:bg-[var(--color-base-300)] capitalize transition-colors"
+                                    },
+                                    onclick: {
+                                        let name = name.clone();
+                                        move |_| {
+                                            profile.write().active_profile = name.clone();
+                                            // Logged by write_profile_settings on failure
+                                            let _ = write_profile_settings(&profile());
+                                            show_profile_dropdown.set(false);
+                                        }
+                                    },
+                                    "{name}"
+                                }
                             }
                         }
                     }
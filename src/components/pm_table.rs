@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex};
+
+use dioxus::prelude::*;
+
+use crate::utils::metrics::MetricsHistory;
+use crate::utils::pm_table::{decode, format_pm_version, raw_values, PmTable, PmTableReader};
+
+const REFRESH_MS: i64 = 2000;
+
+// Latest read plus the one before it, for change highlighting
+#[derive(Clone, Default)]
+struct PmSnapshot {
+    current: Option<Result<PmTable, String>>,
+    previous: Option<PmTable>,
+}
+
+fn row_class(changed: bool) -> &'static str {
+    if changed {
+        "grid grid-cols-[1fr_5rem_7rem] gap-3 px-2 py-1 text-sm font-mono bg-[var(--color-warning)]/20"
+    } else {
+        "grid grid-cols-[1fr_5rem_7rem] gap-3 px-2 py-1 text-sm font-mono"
+    }
+}
+
+#[component]
+pub fn PmTableExplorer() -> Element {
+    // Re-read every few seconds, piggybacking on the stats samples
+    let history = use_context::<SyncSignal<MetricsHistory>>();
+    let tick = use_memo(move || history.read().latest().map(|s| s.timestamp_ms / REFRESH_MS));
+    let mut refresh = use_signal(|| 0u32);
+    let mut paused = use_signal(|| false);
+    let mut show_unverified = use_signal(|| false);
+
+    // Reads block on the helper, so they run off the UI thread over one
+    // connection kept for the page; a read still in flight skips the next tick.
+    let reader = use_hook(|| Arc::new(Mutex::new(PmTableReader::default())));
+    let snapshot = use_signal_sync(PmSnapshot::default);
+    let mut reading = use_signal_sync(|| false);
+    use_effect(move || {
+        let _ = (tick(), refresh());
+        if *paused.peek() || *reading.peek() {
+            return;
+        }
+        reading.set(true);
+        let mut snapshot = snapshot;
+        let reader = reader.clone();
+        std::thread::spawn(move || {
+            let result = reader.lock().unwrap().read();
+            let mut snap = snapshot.write();
+            snap.previous = match snap.current.take() {
+                Some(Ok(table)) => Some(table),
+                _ => snap.previous.take(),
+            };
+            snap.current = Some(result);
+            drop(snap);
+            reading.set(false);
+        });
+    });
+
+    let snap = snapshot.read();
+    let previous = snap.previous.as_ref();
+    // Only compare against a read of the same layout
+    let changed = |table: &PmTable, index: usize, value: f32| {
+        previous
+            .filter(|prev| prev.version == table.version)
+            .and_then(|prev| prev.values.get(index))
+            .is_some_and(|old| old.to_bits() != value.to_bits())
+    };
+
+    rsx! {
+        div { class: "p-8 max-w-[1600px] mx-auto",
+            div { class: "bg-[var(--color-base-200)] rounded-xl p-6 border border-[var(--color-base-300)]",
+                div { class: "flex items-center justify-between mb-4",
+                    div {
+                        div { class: "text-sm font-semibold text-[var(--color-secondary)]", "PM TABLE" }
+                        if let Some(Ok(table)) = &snap.current {
+                            div { class: "text-xs text-[var(--color-base-content)]/60",
+                                {format!(
+                                    "Layout: {} · Version: {} · Codename: {} · {} entries",
+                                    table.layout().map_or("unknown", |layout| layout.name),
+                                    format_pm_version(table.version),
+                                    table.codename.map_or("unknown".to_string(), |c| c.to_string()),
+                                    table.values.len(),
+                                )}
+                            }
+                        }
+                    }
+                    div { class: "flex gap-2",
+                        button {
+                            class: if show_unverified() {
+                                "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                            } else {
+                                "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                            },
+                            title: "Also decode offsets borrowed from a related table, which may be wrong for this one",
+                            onclick: move |_| show_unverified.set(!show_unverified()),
+                            "Unverified"
+                        }
+                        button {
+                            class: if paused() {
+                                "px-3 py-1 rounded bg-[var(--color-primary)] text-white text-sm"
+                            } else {
+                                "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80"
+                            },
+                            onclick: move |_| paused.set(!paused()),
+                            if paused() { "Resume" } else { "Pause" }
+                        }
+                        button {
+                            class: "px-3 py-1 rounded bg-[var(--color-base-300)] text-[var(--color-base-content)] text-sm hover:bg-[var(--color-base-300)]/80",
+                            onclick: move |_| {
+                                paused.set(false);
+                                refresh += 1;
+                            },
+                            "Refresh"
+                        }
+                    }
+                }
+
+                match &snap.current {
+                    None => rsx! {
+                        div { class: "py-8 text-center text-sm text-[var(--color-base-content)]/60", "Reading PM table…" }
+                    },
+                    Some(Err(message)) => rsx! {
+                        div { class: "px-4 py-3 rounded-lg bg-[var(--color-error)]/20 text-sm",
+                            "Could not read the PM table: {message}. It needs the ryzen_smu driver."
+                        }
+                    },
+                    Some(Ok(table)) => rsx! {
+                        div { class: "grid grid-cols-2 gap-6",
+                            // Decoded
+                            div {
+                                div { class: "text-xs font-semibold text-[var(--color-base-content)]/70 mb-2", "DECODED" }
+                                {
+                                    let decoded = decode(table, show_unverified());
+                                    if decoded.is_empty() {
+                                        rsx! {
+                                            div { class: "py-8 text-center text-sm text-[var(--color-base-content)]/60",
+                                                "No known layout for this table version; see the raw values"
+                                            }
+                                        }
+                                    } else {
+                                        rsx! {
+                                            div { class: "divide-y divide-[var(--color-base-300)]",
+                                                for entry in decoded {
+                                                    div {
+                                                        key: "{entry.offset}",
+                                                        class: row_class(changed(table, entry.offset / 4, entry.value)),
+                                                        span { class: "font-sans", title: "{entry.group.label()}",
+                                                            "{entry.name}"
+                                                            if !entry.verified {
+                                                                span { class: "ml-2 text-xs text-[var(--color-warning)]", "unverified" }
+                                                            }
+                                                        }
+                                                        span { class: "text-[var(--color-base-content)]/60", {format!("0x{:03X}", entry.offset)} }
+                                                        span { class: "text-right", {format!("{:.3} {}", entry.value, entry.unit)} }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            // Raw
+                            div {
+                                div { class: "text-xs font-semibold text-[var(--color-base-content)]/70 mb-2", "RAW" }
+                                div { class: "divide-y divide-[var(--color-base-300)] max-h-[70vh] overflow-auto",
+                                    for (index, offset, value) in raw_values(table) {
+                                        div {
+                                            key: "{index}",
+                                            class: row_class(changed(table, index, value)),
+                                            span { "[{index}]" }
+                                            span { class: "text-[var(--color-base-content)]/60", {format!("0x{:03X}", offset)} }
+                                            span { class: "text-right", {format!("{:.4}", value)} }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
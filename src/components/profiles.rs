@@ -1,13 +1,23 @@
 use dioxus::prelude::*;
-use crate::utils::types::{AppSettings, ProfileSettings};
+use crate::utils::capabilities::Capabilities;
+use crate::utils::types::{AppSettings, LimitField, LimitRange, PowerLimits, Profile, ProfileMode, ProfileSettings, Weekday};
+use crate::utils::schedule::{off_grid_entries, schedule_from_grid, schedule_grid};
+use crate::utils::defaults::activate_firmware_defaults;
 use crate::utils::settings::write_profile_settings;
 use crate::utils::conversions::{power_unit_label, temp_unit_label, power_conversion, temp_conversion};
 
 #[component]
-fn PowerInput(label: String, value_mw: Signal<i32>, power_unit: String) -> Element {
-    let display_val = power_conversion(value_mw(), "milliwatt", &power_unit);
+// An empty input leaves the limit unset
+// `range` is None when the chip doesn't have this limit
+fn PowerInput(label: String, value_mw: Signal<Option<i32>>, power_unit: String, range: Option<LimitRange>, family: &'static str, error: Option<String>) -> Element {
+    let display_val = value_mw()
+        .map(|mw| power_conversion(mw, "milliwatt", &power_unit).to_string())
+        .unwrap_or_default();
     let unit_label = power_unit_label(&power_unit);
     let pu = power_unit.clone();
+    let (min, max) = range
+        .map(|r| (power_conversion(r.min, "milliwatt", &power_unit).to_string(), power_conversion(r.max, "milliwatt", &power_unit).to_string()))
+        .unwrap_or_default();
 
     rsx! {
         div {
@@ -16,11 +26,25 @@ fn PowerInput(label: String, value_mw: Signal<i32>, power_unit: String) -> Eleme
             }
             input {
                 r#type: "number",
-                class: "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                class: if error.is_some() { "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-error)] rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-error)]" } else { "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]" },
                 value: "{display_val}",
+                min: "{min}",
+                max: "{max}",
+                disabled: range.is_none(),
+                placeholder: if range.is_some() { "Unchanged" } else { "Not supported" },
                 oninput: move |evt| {
-                    let input_val: i32 = evt.value().parse().unwrap_or(0);
-                    value_mw.set(power_conversion(input_val, &pu, "milliwatt"));
+                    let input_val: Option<i32> = evt.value().trim().parse().ok();
+                    value_mw.set(input_val.map(|v| power_conversion(v, &pu, "milliwatt")));
+                }
+            }
+            if range.is_none() {
+                p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
+                    "Not supported on {family}"
+                }
+            }
+            if let Some(message) = error.as_ref() {
+                p { class: "text-xs text-[var(--color-error)] mt-1",
+                    "{message}"
                 }
             }
         }
@@ -28,14 +52,21 @@ fn PowerInput(label: String, value_mw: Signal<i32>, power_unit: String) -> Eleme
 }
 
 #[component]
-fn TempInput(label: String, value_c: Signal<i32>, temp_unit: String) -> Element {
-    let canonical_display = temp_conversion(value_c() as f32, "celsius", &temp_unit).round() as i32;
-    let mut input_text = use_signal(|| canonical_display.to_string());
+fn TempInput(label: String, value_c: Signal<Option<i32>>, temp_unit: String, range: Option<LimitRange>, family: &'static str, error: Option<String>) -> Element {
+    let canonical_display_text = value_c()
+        .map(|c| (temp_conversion(c as f32, "celsius", &temp_unit).round() as i32).to_string())
+        .unwrap_or_default();
+    let mut input_text = use_signal(|| canonical_display_text.clone());
     let mut is_editing = use_signal(|| false);
-    let canonical_display_text = canonical_display.to_string();
     let unit_label = temp_unit_label(&temp_unit);
     let tu = temp_unit.clone();
     let canonical_for_effect = canonical_display_text.clone();
+    let (min, max) = range
+        .map(|r| {
+            let convert = |c: i32| (temp_conversion(c as f32, "celsius", &temp_unit).round() as i32).to_string();
+            (convert(r.min), convert(r.max))
+        })
+        .unwrap_or_default();
 
     use_effect(move || {
         if !is_editing() {
@@ -51,8 +82,12 @@ fn TempInput(label: String, value_c: Signal<i32>, temp_unit: String) -> Element
             input {
                 r#type: "number",
                 step: "1",
-                class: "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                class: if error.is_some() { "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-error)] rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-error)]" } else { "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]" },
                 value: "{input_text()}",
+                min: "{min}",
+                max: "{max}",
+                disabled: range.is_none(),
+                placeholder: if range.is_some() { "Unchanged" } else { "Not supported" },
                 onfocus: move |_| {
                     is_editing.set(true);
                 },
@@ -60,570 +95,656 @@ fn TempInput(label: String, value_c: Signal<i32>, temp_unit: String) -> Element
                     input_text.set(evt.value());
                 },
                 onblur: move |_| {
-                    if let Ok(input_val) = input_text().trim().parse::<i32>() {
-                        value_c.set(temp_conversion(input_val as f32, &tu, "celsius").round() as i32);
+                    let text = input_text();
+                    if text.trim().is_empty() {
+                        value_c.set(None);
+                    } else if let Ok(input_val) = text.trim().parse::<i32>() {
+                        value_c.set(Some(temp_conversion(input_val as f32, &tu, "celsius").round() as i32));
                     } else {
                         input_text.set(canonical_display_text.clone());
                     }
                     is_editing.set(false);
                 }
             }
+            if range.is_none() {
+                p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
+                    "Not supported on {family}"
+                }
+            }
+            if let Some(message) = error.as_ref() {
+                p { class: "text-xs text-[var(--color-error)] mt-1",
+                    "{message}"
+                }
+            }
         }
     }
 }
 
-pub fn Profiles() -> Element {
-    // Import context
-    let mut settings = use_context::<Signal<AppSettings>>();
-    let mut profile = use_context::<SyncSignal<ProfileSettings>>();
-
-    // State for tracking which profile section is expanded (only one at a time)
-    let mut expanded_section = use_signal(|| "".to_string());
-
-    // Active profile
-    let active_profile = profile().active_profile.clone();
-    let mut low_batt_threshold_percent = use_signal(|| profile().low_batt_threshold_percent.clone());
-    let power_unit = settings().units.power.clone();
-    let temp_unit = settings().units.temp.clone();
-
-    // System profile signals
-    let mut sys_perf_fast_mw = use_signal(|| profile().system.performance.fast_mw.clone());
-    let mut sys_perf_slow_mw = use_signal(|| profile().system.performance.slow_mw.clone());
-    let mut sys_perf_stapm_mw = use_signal(|| profile().system.performance.stapm_mw.clone());
-    let mut sys_perf_temp_c = use_signal(|| profile().system.performance.temp_c.clone());
-    let mut sys_bal_fast_mw = use_signal(|| profile().system.balanced.fast_mw.clone());
-    let mut sys_bal_slow_mw = use_signal(|| profile().system.balanced.slow_mw.clone());
-    let mut sys_bal_stapm_mw = use_signal(|| profile().system.balanced.stapm_mw.clone());
-    let mut sys_bal_temp_c = use_signal(|| profile().system.balanced.temp_c.clone());
-    let mut sys_power_saver_fast_mw = use_signal(|| profile().system.power_saver.fast_mw.clone());
-    let mut sys_power_saver_slow_mw = use_signal(|| profile().system.power_saver.slow_mw.clone());
-    let mut sys_power_saver_stapm_mw = use_signal(|| profile().system.power_saver.stapm_mw.clone());
-    let mut sys_power_saver_temp_c = use_signal(|| profile().system.power_saver.temp_c.clone());
-
-    // Custom profile signals
-    let mut custom_ac_fast_mw = use_signal(|| profile().custom.ac.fast_mw.clone());
-    let mut custom_ac_slow_mw = use_signal(|| profile().custom.ac.slow_mw.clone());
-    let mut custom_ac_stapm_mw = use_signal(|| profile().custom.ac.stapm_mw.clone());
-    let mut custom_ac_temp_c = use_signal(|| profile().custom.ac.temp_c.clone());
-    let mut custom_batt_fast_mw = use_signal(|| profile().custom.batt.fast_mw.clone());
-    let mut custom_batt_slow_mw = use_signal(|| profile().custom.batt.slow_mw.clone());
-    let mut custom_batt_stapm_mw = use_signal(|| profile().custom.batt.stapm_mw.clone());
-    let mut custom_batt_temp_c = use_signal(|| profile().custom.batt.temp_c.clone());
-    let mut custom_low_batt_fast_mw = use_signal(|| profile().custom.low_batt.fast_mw.clone());
-    let mut custom_low_batt_slow_mw = use_signal(|| profile().custom.low_batt.slow_mw.clone());
-    let mut custom_low_batt_stapm_mw = use_signal(|| profile().custom.low_batt.stapm_mw.clone());
-    let mut custom_low_batt_temp_c = use_signal(|| profile().custom.low_batt.temp_c.clone());
-
-    // Turbo profile signals
-    let mut turbo_fast_mw = use_signal(|| profile().turbo.turbo.fast_mw.clone());
-    let mut turbo_slow_mw = use_signal(|| profile().turbo.turbo.slow_mw.clone());
-    let mut turbo_stapm_mw = use_signal(|| profile().turbo.turbo.stapm_mw.clone());
-    let mut turbo_temp_c = use_signal(|| profile().turbo.turbo.temp_c.clone());
-
-    // Fixed profile signals
-    let mut fixed_fast_mw = use_signal(|| profile().fixed.fixed.fast_mw.clone());
-    let mut fixed_slow_mw = use_signal(|| profile().fixed.fixed.slow_mw.clone());
-    let mut fixed_stapm_mw = use_signal(|| profile().fixed.fixed.stapm_mw.clone());
-    let mut fixed_temp_c = use_signal(|| profile().fixed.fixed.temp_c.clone());
-
-    let mut save_system_profile = move || {
-        let mut p = profile.write();
-        p.system.performance.fast_mw = sys_perf_fast_mw();
-        p.system.performance.slow_mw = sys_perf_slow_mw();
-        p.system.performance.stapm_mw = sys_perf_stapm_mw();
-        p.system.performance.temp_c = sys_perf_temp_c();
-        p.system.balanced.fast_mw = sys_bal_fast_mw();
-        p.system.balanced.slow_mw = sys_bal_slow_mw();
-        p.system.balanced.stapm_mw = sys_bal_stapm_mw();
-        p.system.balanced.temp_c = sys_bal_temp_c();
-        p.system.power_saver.fast_mw = sys_power_saver_fast_mw();
-        p.system.power_saver.slow_mw = sys_power_saver_slow_mw();
-        p.system.power_saver.stapm_mw = sys_power_saver_stapm_mw();
-        p.system.power_saver.temp_c = sys_power_saver_temp_c();
-        drop(p);
-        let _ = write_profile_settings(&profile());
-    };
+// Display name and explanation of each sub-profile
+fn sub_profile_label(sub_profile: &str) -> &'static str {
+    match sub_profile {
+        "performance" => "Performance",
+        "balanced" => "Balanced",
+        "power_saver" => "Power Saver",
+        "ac" => "AC",
+        "batt" => "Battery",
+        "low_batt" => "Low Battery",
+        _ => "Limits",
+    }
+}
 
-    let mut save_custom_profile = move || {
-        let mut p = profile.write();
-        p.custom.ac.fast_mw = custom_ac_fast_mw();
-        p.custom.ac.slow_mw = custom_ac_slow_mw();
-        p.custom.ac.stapm_mw = custom_ac_stapm_mw();
-        p.custom.ac.temp_c = custom_ac_temp_c();
-        p.custom.batt.fast_mw = custom_batt_fast_mw();
-        p.custom.batt.slow_mw = custom_batt_slow_mw();
-        p.custom.batt.stapm_mw = custom_batt_stapm_mw();
-        p.custom.batt.temp_c = custom_batt_temp_c();
-        p.custom.low_batt.fast_mw = custom_low_batt_fast_mw();
-        p.custom.low_batt.slow_mw = custom_low_batt_slow_mw();
-        p.custom.low_batt.stapm_mw = custom_low_batt_stapm_mw();
-        p.custom.low_batt.temp_c = custom_low_batt_temp_c();
-        p.low_batt_threshold_percent = low_batt_threshold_percent();
-        drop(p);
-        let _ = write_profile_settings(&profile());
-    };
+fn sub_profile_description(sub_profile: &str) -> &'static str {
+    match sub_profile {
+        "performance" => "Set power and temp limits for when the system \"Performance\" profile is selected on your operating system",
+        "balanced" => "Set power and temp limits for when the system \"Balanced\" profile is selected on your operating system",
+        "power_saver" => "Set power and temp limits for when the system \"Power Saver\" profile is selected on your operating system",
+        "ac" => "Set power and temp limits for when this profile is active and laptop is in AC mode",
+        "batt" => "Set power and temp limits for when this profile is active and laptop is in battery mode",
+        "low_batt" => "Set power and temp limits for when this profile is active and laptop is in battery mode below a specified threshold",
+        _ => "Set power and temp limits applied whenever this profile is active",
+    }
+}
 
-    let mut save_turbo_profile = move || {
-        let mut p = profile.write();
-        p.turbo.turbo.fast_mw = turbo_fast_mw();
-        p.turbo.turbo.slow_mw = turbo_slow_mw();
-        p.turbo.turbo.stapm_mw = turbo_stapm_mw();
-        p.turbo.turbo.temp_c = turbo_temp_c();
-        drop(p);
-        let _ = write_profile_settings(&profile());
-    };
+#[component]
+fn SubProfileEditor(sub_profile: String, mut draft: Signal<Profile>, power_unit: String, temp_unit: String) -> Element {
+    let limits = draft.peek().limits.get(&sub_profile).cloned().unwrap_or_default();
+    let fast_mw = use_signal(|| limits.fast_mw);
+    let slow_mw = use_signal(|| limits.slow_mw);
+    let stapm_mw = use_signal(|| limits.stapm_mw);
+    let temp_c = use_signal(|| limits.temp_c);
+
+    // Copy edits back into the profile draft
+    let sub = sub_profile.clone();
+    use_effect(move || {
+        let limits = PowerLimits {
+            fast_mw: fast_mw(),
+            slow_mw: slow_mw(),
+            stapm_mw: stapm_mw(),
+            temp_c: temp_c(),
+        };
+        if let Some(target) = draft.write().limits_mut(&sub) {
+            *target = limits;
+        }
+    });
 
-    let mut save_fixed_profile = move || {
-        let mut p = profile.write();
-        p.fixed.fixed.fast_mw = fixed_fast_mw();
-        p.fixed.fixed.slow_mw = fixed_slow_mw();
-        p.fixed.fixed.stapm_mw = fixed_stapm_mw();
-        p.fixed.fixed.temp_c = fixed_temp_c();
-        drop(p);
-        let _ = write_profile_settings(&profile());
+    let low_batt_threshold_percent = draft().low_batt_threshold_percent;
+    let low_batt_hysteresis_percent = draft().low_batt_hysteresis_percent;
+
+    // First error per input, shown inline; family ranges are checked after the generic ones
+    let capabilities = use_context::<Capabilities>();
+    let family = capabilities.family;
+    let current = PowerLimits {
+        fast_mw: fast_mw(),
+        slow_mw: slow_mw(),
+        stapm_mw: stapm_mw(),
+        temp_c: temp_c(),
     };
-
-    let mut set_as_active_profile = move |new_active_profile: String| {
-        profile.write().active_profile = new_active_profile;
-        let _ = write_profile_settings(&profile());
+    let mut errors = current.validate().err().unwrap_or_default();
+    errors.extend(capabilities.check(&current));
+    let error_for = |field: LimitField| {
+        errors
+            .iter()
+            .find(|e| e.field() == field)
+            .map(|e| e.to_string())
     };
 
-
-
     rsx! {
-        div { class: "p-8 max-w-[1600px] mx-auto",
+        div {
+            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
 
-            p { class: "text-[var(--color-base-content)]/70 mb-8",
-                "Configure power profiles here to manage your system's power settings. Each profile allows you to set power limits and temperature thresholds. Select profile and set as active or choose a profile from the navbar"
+            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
+                "{sub_profile_label(&sub_profile)}"
+            }
+            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
+                "{sub_profile_description(&sub_profile)}"
             }
 
-            // System Profiles Section
-            div { class: if active_profile == "system" {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border-2 border-[var(--color-primary)] relative"
-            } else {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]"
-            },
-
-                if active_profile == "system" {
-                    div { class: "absolute top-4 right-16 px-3 py-1 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-full text-xs font-semibold z-10",
-                        "Active"
-                    }
-                }
+            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
+                PowerInput { label: "Fast Limit", value_mw: fast_mw, power_unit: power_unit.clone(), range: capabilities.range(LimitField::Fast), family, error: error_for(LimitField::Fast) }
+                PowerInput { label: "Slow Limit", value_mw: slow_mw, power_unit: power_unit.clone(), range: capabilities.range(LimitField::Slow), family, error: error_for(LimitField::Slow) }
+                PowerInput { label: "STAPM Limit", value_mw: stapm_mw, power_unit: power_unit.clone(), range: capabilities.range(LimitField::Stapm), family, error: error_for(LimitField::Stapm) }
+                TempInput { label: "TCTL Temp Limit", value_c: temp_c, temp_unit: temp_unit.clone(), range: capabilities.range(LimitField::Temp), family, error: error_for(LimitField::Temp) }
+            }
 
-                button {
-                    class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
-                    onclick: move |_| {
-                        if expanded_section() == "system" {
-                            expanded_section.set("".to_string());
-                        } else {
-                            expanded_section.set("system".to_string());
+            if sub_profile == "low_batt" {
+                div { class: "mt-4 flex flex-wrap gap-6",
+                    // Battery Level Threshold
+                    div {
+                        label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                            "Battery Level Threshold (%)"
+                        }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            max: "100",
+                            class: "w-full md:w-48 px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                            value: "{low_batt_threshold_percent}",
+                            oninput: move |evt| draft.write().low_batt_threshold_percent = evt.value().parse().unwrap_or(0)
+                        }
+                        p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
+                            "Profile activates when battery drops below this level"
                         }
-                    },
-
-                    h3 { class: "text-xl font-bold text-[var(--color-base-content)]",
-                        "System"
-                    }
-
-                    svg {
-                        class: if expanded_section() == "system" { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
-                        view_box: "0 0 24 24",
-                        fill: "none",
-                        stroke: "currentColor",
-                        stroke_width: "2",
-                        path { d: "M19 9l-7 7-7-7" }
                     }
-                }
-
-                if expanded_section() == "system" {
-                    div { class: "px-4 pb-4 space-y-3",
-
-                        // Performance Sub-profile
-                        div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "Performance"
-                            }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the system \"Performance\" profile is selected on your operating system"
-                            }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: sys_perf_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: sys_perf_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: sys_perf_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: sys_perf_temp_c, temp_unit: temp_unit.clone() }
-                            }
+                    // Hysteresis
+                    div {
+                        label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                            "Hysteresis (%)"
                         }
-
-                        // Balanced Sub-profile
-                        div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "Balanced"
-                            }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the system \"Balanced\" profile is selected on your operating system"
-                            }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: sys_bal_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: sys_bal_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: sys_bal_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: sys_bal_temp_c, temp_unit: temp_unit.clone() }
-                            }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            max: "50",
+                            class: "w-full md:w-48 px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                            value: "{low_batt_hysteresis_percent}",
+                            oninput: move |evt| draft.write().low_batt_hysteresis_percent = evt.value().parse().unwrap_or(0)
                         }
+                        p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
+                            "Profile is left once battery is back above {low_batt_threshold_percent + low_batt_hysteresis_percent}%"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
 
-                        // Power Saver Sub-profile
-                        div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "Power Saver"
-                            }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the system \"Power Saver\" profile is selected on your operating system"
-                            }
+// Applies `edit` to a copy of the settings and saves it, or shows the error
+fn apply_edit<F>(mut profile_settings: SyncSignal<ProfileSettings>, mut error: Signal<Option<String>>, edit: F) -> bool
+where
+    F: FnOnce(&mut ProfileSettings) -> Result<(), String>,
+{
+    let mut updated = profile_settings.peek().clone();
+    let result = edit(&mut updated).and_then(|_| {
+        write_profile_settings(&updated).map_err(|e| e.to_string())
+    });
+    match result {
+        Ok(()) => {
+            profile_settings.set(updated);
+            error.set(None);
+            true
+        }
+        Err(e) => {
+            error.set(Some(e));
+            false
+        }
+    }
+}
 
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: sys_power_saver_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: sys_power_saver_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: sys_power_saver_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: sys_power_saver_temp_c, temp_unit: temp_unit.clone() }
-                            }
+fn save_draft(profile_settings: SyncSignal<ProfileSettings>, error: Signal<Option<String>>, name: &str, draft: Profile) -> bool {
+    apply_edit(profile_settings, error, |settings| {
+        draft.validate()?;
+        settings.update_profile(name, draft)
+    })
+}
 
+#[component]
+fn ProfileCard(profile: Profile, active: bool, mut expanded_section: Signal<String>, error: Signal<Option<String>>) -> Element {
+    // Import context
+    let settings = use_context::<Signal<AppSettings>>();
+    let profile_settings = use_context::<SyncSignal<ProfileSettings>>();
+    let capabilities = use_context::<Capabilities>();
 
-                        }
+    let power_unit = settings().units.power.clone();
+    let temp_unit = settings().units.temp.clone();
 
-                        div { class: "flex gap-3 mt-4",
-                            button {
-                                class: "px-4 py-2 bg-[var(--color-secondary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                onclick: move |_| save_system_profile(),
-                                "Save Changes"
-                            }
+    // Edits stay in the draft until saved
+    let mut draft = use_signal(|| profile.clone());
+    let mut confirm_delete = use_signal(|| false);
+    let name = profile.name.clone();
+    let is_expanded = expanded_section() == name;
+    let current = draft();
+    // Out of this chip's range counts too, or the limits would be saved and then skipped
+    let has_errors = !current.limit_errors().is_empty()
+        || current
+            .sub_profile_names()
+            .iter()
+            .filter_map(|sub| current.limits(sub))
+            .any(|limits| !capabilities.check(limits).is_empty());
+
+    let header_name = name.clone();
+    let save_name = name.clone();
+    let activate_name = name.clone();
+    let duplicate_name = name.clone();
+    let delete_name = name.clone();
 
-                            button {
-                                class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                onclick: move |_| {
-                                    save_system_profile();
-                                    set_as_active_profile("system".to_string());
-                                },
-                                "Set as Active"
-                            }
-                        }
-                    }
+    rsx! {
+        div { class: if active {
+            "mb-4 bg-[var(--color-base-200)] rounded-xl border-2 border-[var(--color-primary)] relative"
+        } else {
+            "mb-4 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]"
+        },
+
+            if active {
+                div { class: "absolute top-4 right-16 px-3 py-1 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-full text-xs font-semibold z-10",
+                    "Active"
                 }
             }
 
-            // Custom Profiles Section
-            div { class: if active_profile == "custom" {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border-2 border-[var(--color-primary)] relative"
-            } else {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]"
-            },
-
-                if active_profile == "custom" {
-                    div { class: "absolute top-4 right-16 px-3 py-1 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-full text-xs font-semibold z-10",
-                        "Active"
+            button {
+                class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
+                onclick: move |_| {
+                    if expanded_section() == header_name {
+                        expanded_section.set("".to_string());
+                    } else {
+                        expanded_section.set(header_name.clone());
                     }
-                }
+                },
 
-                button {
-                    class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
-                    onclick: move |_| {
-                        if expanded_section() == "custom" {
-                            expanded_section.set("".to_string());
-                        } else {
-                            expanded_section.set("custom".to_string());
+                div { class: "text-left",
+                    h3 { class: "text-xl font-bold text-[var(--color-base-content)] capitalize",
+                        "{profile.name}"
+                    }
+                    if !profile.description.is_empty() {
+                        p { class: "text-sm text-[var(--color-base-content)]/70",
+                            "{profile.description}"
                         }
-                    },
-
-                    h3 { class: "text-xl font-bold text-[var(--color-base-content)]",
-                        "Custom"
                     }
+                }
 
-                    svg {
-                        class: if expanded_section() == "custom" { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
-                        view_box: "0 0 24 24",
-                        fill: "none",
-                        stroke: "currentColor",
-                        stroke_width: "2",
-                        path { d: "M19 9l-7 7-7-7" }
-                    }
+                svg {
+                    class: if is_expanded { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
+                    view_box: "0 0 24 24",
+                    fill: "none",
+                    stroke: "currentColor",
+                    stroke_width: "2",
+                    path { d: "M19 9l-7 7-7-7" }
                 }
+            }
 
-                if expanded_section() == "custom" {
-                    div { class: "px-4 pb-4 space-y-3",
+            if is_expanded {
+                div { class: "px-4 pb-4 space-y-3",
 
-                        // AC Sub-profile
+                    // Name, description and mode
+                    div { class: "grid grid-cols-1 md:grid-cols-3 gap-4",
                         div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "AC"
-                            }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the Custom profile is active and laptop is in AC mode"
+                            label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                                "Name"
                             }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: custom_ac_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: custom_ac_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: custom_ac_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: custom_ac_temp_c, temp_unit: temp_unit.clone() }
+                            input {
+                                r#type: "text",
+                                class: "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                                value: "{current.name}",
+                                oninput: move |evt| draft.write().name = evt.value(),
                             }
-
                         }
-
-                        // Battery Sub-profile
                         div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "Battery"
+                            label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                                "Description"
                             }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the Custom profile is active and laptop is in battery mode"
+                            input {
+                                r#type: "text",
+                                class: "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                                value: "{current.description}",
+                                oninput: move |evt| draft.write().description = evt.value(),
                             }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: custom_batt_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: custom_batt_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: custom_batt_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: custom_batt_temp_c, temp_unit: temp_unit.clone() }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                                "Switches Limits By"
+                            }
+                            select {
+                                class: "w-full px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                                value: "{current.mode.as_str()}",
+                                onchange: move |evt| {
+                                    if let Some(mode) = ProfileMode::from_name(&evt.value()) {
+                                        draft.write().set_mode(mode);
+                                    }
+                                },
+                                for mode in ProfileMode::ALL {
+                                    option {
+                                        value: "{mode.as_str()}",
+                                        selected: mode == current.mode,
+                                        "{mode.label()}"
+                                    }
+                                }
                             }
-
                         }
+                    }
 
-                        
-
-                        // Low Battery Sub-profile
+                    if current.mode == ProfileMode::PowerSource {
                         div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            h4 { class: "text-lg font-bold text-[var(--color-base-content)] mb-2",
-                                "Low Battery"
+                            label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
+                                "Minimum Dwell Time (s)"
                             }
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Set power and temp limits for when the Custom profile is active and laptop is in battery mode below a specified thereshold"
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                class: "w-full md:w-48 px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
+                                value: "{current.min_dwell_s}",
+                                oninput: move |evt| draft.write().min_dwell_s = evt.value().parse().unwrap_or(0),
                             }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4 mb-4",
-                                PowerInput { label: "Fast Limit", value_mw: custom_low_batt_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: custom_low_batt_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: custom_low_batt_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: custom_low_batt_temp_c, temp_unit: temp_unit.clone() }
+                            p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
+                                "How long a change of power source or battery level must last before the limits switch"
                             }
+                        }
+                    }
 
-                            // Battery Level Threshold
-                            div { class: "mb-4",
-                                label { class: "block text-sm font-medium text-[var(--color-base-content)]/70 mb-2",
-                                    "Battery Level Threshold (%)"
-                                }
-                                input {
-                                    r#type: "number",
-                                    min: "0",
-                                    max: "100",
-                                    class: "w-full md:w-48 px-3 py-2 bg-[var(--color-base-300)] border border-[var(--color-base-content)]/20 rounded-lg text-[var(--color-base-content)] focus:outline-none focus:border-[var(--color-primary)]",
-                                    value: "{low_batt_threshold_percent}",
-                                    oninput: move |evt| low_batt_threshold_percent.set(evt.value().parse().unwrap_or(0))
-                                }
-                                p { class: "text-xs text-[var(--color-base-content)]/60 mt-1",
-                                    "Profile activates when battery drops below this level"
+                    for sub_profile in current.sub_profile_names() {
+                        SubProfileEditor {
+                            key: "{sub_profile}",
+                            sub_profile: sub_profile.to_string(),
+                            draft,
+                            power_unit: power_unit.clone(),
+                            temp_unit: temp_unit.clone(),
+                        }
+                    }
+
+                    div { class: "flex gap-3 mt-4",
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-secondary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity disabled:opacity-40 disabled:cursor-not-allowed",
+                            disabled: has_errors,
+                            onclick: move |_| {
+                                if save_draft(profile_settings, error, &save_name, draft()) {
+                                    expanded_section.set(draft.peek().name.clone());
                                 }
-                            }
+                            },
+                            "Save Changes"
+                        }
 
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity disabled:opacity-40 disabled:cursor-not-allowed",
+                            disabled: has_errors,
+                            onclick: move |_| {
+                                if save_draft(profile_settings, error, &activate_name, draft()) {
+                                    let new_active = draft.peek().name.clone();
+                                    expanded_section.set(new_active.clone());
+                                    apply_edit(profile_settings, error, |settings| {
+                                        settings.active_profile = new_active;
+                                        Ok(())
+                                    });
+                                }
+                            },
+                            "Set as Active"
                         }
 
-                        div { class: "flex gap-3 mt-4",
-                            button {
-                                class: "px-4 py-2 bg-[var(--color-secondary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                onclick: move |_| save_custom_profile(),
-                                "Save Changes"
-                            }
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-base-300)] text-[var(--color-base-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                            onclick: move |_| {
+                                let copy_name = profile_settings.peek().unused_name(&format!("{} copy", duplicate_name));
+                                if apply_edit(profile_settings, error, |settings| settings.duplicate_profile(&duplicate_name, &copy_name)) {
+                                    expanded_section.set(copy_name.clone());
+                                }
+                            },
+                            "Duplicate"
+                        }
 
-                            button {
-                                class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                onclick: move |_| {
-                                    save_custom_profile();
-                                    set_as_active_profile("custom".to_string());
-                                },
-                                "Set as Active"
-                            }
+                        button {
+                            class: "ml-auto px-4 py-2 bg-[var(--color-error)] text-[var(--color-error-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity disabled:opacity-40 disabled:cursor-not-allowed",
+                            disabled: active,
+                            title: if active { "Activate another profile before deleting this one" } else { "" },
+                            onclick: move |_| {
+                                if !confirm_delete() {
+                                    confirm_delete.set(true);
+                                    return;
+                                }
+                                confirm_delete.set(false);
+                                apply_edit(profile_settings, error, |settings| settings.delete_profile(&delete_name));
+                            },
+                            if confirm_delete() { "Confirm Delete" } else { "Delete" }
                         }
                     }
                 }
             }
+        }
+    }
+}
 
-            // Turbo Profile
-            div { class: if active_profile == "turbo" {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border-2 border-[var(--color-primary)] relative"
-            } else {
-                "mb-4 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]"
-            },
+// Colours for the profiles in the schedule grid, by position in the profile list
+const SCHEDULE_COLORS: [&str; 6] = [
+    "bg-[var(--color-primary)]",
+    "bg-[var(--color-secondary)]",
+    "bg-[var(--color-warning)]",
+    "bg-[var(--color-success)]",
+    "bg-[var(--color-error)]",
+    "bg-[var(--color-neutral)]",
+];
+
+fn schedule_color(profile_names: &[String], profile: &str) -> &'static str {
+    profile_names
+        .iter()
+        .position(|name| name == profile)
+        .map(|i| SCHEDULE_COLORS[i % SCHEDULE_COLORS.len()])
+        .unwrap_or("bg-[var(--color-base-300)]")
+}
 
-                if active_profile == "turbo" {
-                    div { class: "absolute top-4 right-16 px-3 py-1 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-full text-xs font-semibold z-10",
-                        "Active"
-                    }
-                }
+#[component]
+// Weekly schedule as a day x hour grid. Pick a profile (or "None") and click
+// or drag over hours to paint them; nothing is written until saved.
+fn ScheduleEditor(error: Signal<Option<String>>) -> Element {
+    let profile_settings = use_context::<SyncSignal<ProfileSettings>>();
+
+    let mut expanded = use_signal(|| false);
+    let mut draft = use_signal(|| schedule_grid(&profile_settings.peek()));
+    let mut brush = use_signal(|| None::<String>);
+    let mut painting = use_signal(|| false);
+
+    let profile_names = profile_settings().profile_names();
+    // Saving from the grid would round these, so it's blocked while they exist
+    let off_grid: Vec<String> = off_grid_entries(&profile_settings().schedule)
+        .into_iter()
+        .map(|entry| format!("{} ({})", entry.label(), entry.profile))
+        .collect();
+    let scheduled_hours = draft().iter().flatten().filter(|cell| cell.is_some()).count();
+
+    let mut paint = move |day: usize, hour: usize| {
+        let value = brush();
+        if draft.peek()[day][hour] != value {
+            draft.write()[day][hour] = value;
+        }
+    };
 
-                button {
-                    class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
-                    onclick: move |_| {
-                        if expanded_section() == "turbo" {
-                            expanded_section.set("".to_string());
+    rsx! {
+        div { class: "mb-4 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]",
+
+            button {
+                class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
+                onclick: move |_| expanded.toggle(),
+
+                div { class: "text-left",
+                    h3 { class: "text-xl font-bold text-[var(--color-base-content)]", "Schedule" }
+                    p { class: "text-sm text-[var(--color-base-content)]/70",
+                        if scheduled_hours == 0 {
+                            "Use profiles at set times of the week"
                         } else {
-                            expanded_section.set("turbo".to_string());
+                            "{scheduled_hours} of 168 hours scheduled"
                         }
-                    },
-
-                    h3 { class: "text-xl font-bold text-[var(--color-base-content)]",
-                        "Turbo"
                     }
+                }
 
-                    svg {
-                        class: if expanded_section() == "turbo" { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
-                        view_box: "0 0 24 24",
-                        fill: "none",
-                        stroke: "currentColor",
-                        stroke_width: "2",
-                        path { d: "M19 9l-7 7-7-7" }
-                    }
+                svg {
+                    class: if expanded() { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
+                    view_box: "0 0 24 24",
+                    fill: "none",
+                    stroke: "currentColor",
+                    stroke_width: "2",
+                    path { d: "M19 9l-7 7-7-7" }
                 }
+            }
 
-                if expanded_section() == "turbo" {
-                    div { class: "px-4 pb-4",
-                        div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
+            if expanded() {
+                div { class: "px-4 pb-4 space-y-4",
+                    p { class: "text-sm text-[var(--color-base-content)]/70",
+                        "Scheduled hours override the active profile, in local time. Programs matched by app rules still take priority. Hours left empty use the active profile."
+                    }
 
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Maximum performance mode with highest power limits"
-                            }
+                    if !off_grid.is_empty() {
+                        div { class: "px-4 py-3 rounded-lg bg-[var(--color-warning)]/20 text-sm",
+                            "The schedule has entries that don't start and end on the hour: {off_grid.join(\", \")}. The grid can't show them exactly, so saving here is disabled; edit them in profile_settings.toml."
+                        }
+                    }
 
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: turbo_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: turbo_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: turbo_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: turbo_temp_c, temp_unit: temp_unit.clone() }
+                    // Brush
+                    div { class: "flex flex-wrap gap-2",
+                        button {
+                            class: if brush().is_none() {
+                                "px-3 py-1 rounded-lg text-sm border-2 border-[var(--color-base-content)] bg-[var(--color-base-300)] text-[var(--color-base-content)]"
+                            } else {
+                                "px-3 py-1 rounded-lg text-sm border-2 border-transparent bg-[var(--color-base-300)] text-[var(--color-base-content)]"
+                            },
+                            onclick: move |_| brush.set(None),
+                            "None"
+                        }
+                        for name in profile_names.clone() {
+                            button {
+                                key: "{name}",
+                                class: format!(
+                                    "px-3 py-1 rounded-lg text-sm border-2 text-[var(--color-base-content)] capitalize flex items-center gap-2 {}",
+                                    if brush().as_deref() == Some(name.as_str()) { "border-[var(--color-base-content)]" } else { "border-transparent" }
+                                ),
+                                onclick: {
+                                    let name = name.clone();
+                                    move |_| brush.set(Some(name.clone()))
+                                },
+                                span { class: format!("w-3 h-3 rounded-sm {}", schedule_color(&profile_names, &name)) }
+                                "{name}"
                             }
+                        }
+                    }
 
-                            div { class: "flex gap-3 mt-4",
-                                // button {
-                                //     class: "px-4 py-2 bg-red-600 text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity flex items-center gap-2",
-                                //     // onclick: move |_| active_profile.set("system_powersaver".to_string()),
-                                //     svg {
-                                //         xmlns: "http://www.w3.org/2000/svg",
-                                //         width: "20",
-                                //         height: "20",
-                                //         view_box: "0 0 24 24",
-                                //         fill: "none",
-                                //         stroke: "currentColor",
-                                //         stroke_width: "2",
-                                //         stroke_linecap: "round",
-                                //         stroke_linejoin: "round",
-                                //         path { d: "m21.73 18-8-14a2 2 0 0 0-3.48 0l-8 14A2 2 0 0 0 4 21h16a2 2 0 0 0 1.73-3" }
-                                //         path { d: "M12 9v4" }
-                                //         path { d: "M12 17h.01" }
-                                //     }
-                                //     "Find Max Turbo"
-                                // }
-
-                                button {
-                                    class: "px-4 py-2 bg-[var(--color-secondary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                    onclick: move |_| save_turbo_profile(),
-                                    "Save Changes"
-                                }
+                    // Grid
+                    div {
+                        class: "select-none",
+                        onmouseup: move |_| painting.set(false),
+                        onmouseleave: move |_| painting.set(false),
+
+                        div { class: "grid grid-cols-[3rem_repeat(24,minmax(0,1fr))] gap-px text-xs text-[var(--color-base-content)]/60",
+                            div {}
+                            for hour in 0..24 {
+                                div { class: "text-center", if hour % 3 == 0 { "{hour:02}" } }
+                            }
+                        }
 
-                                button {
-                                    class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                    onclick: move |_| {
-                                        save_turbo_profile();
-                                        set_as_active_profile("turbo".to_string());
-                                    },
-                                    "Set as Active"
+                        for day in Weekday::ALL {
+                            div { class: "grid grid-cols-[3rem_repeat(24,minmax(0,1fr))] gap-px mt-px",
+                                div { class: "text-xs text-[var(--color-base-content)]/70 flex items-center", "{day.label()}" }
+                                for hour in 0..24 {
+                                    div {
+                                        class: format!(
+                                            "h-6 rounded-sm cursor-pointer hover:opacity-70 {}",
+                                            match &draft()[day.index()][hour] {
+                                                Some(profile) => schedule_color(&profile_names, profile),
+                                                None => "bg-[var(--color-base-300)]",
+                                            }
+                                        ),
+                                        title: format!(
+                                            "{} {:02}:00 - {}",
+                                            day.label(),
+                                            hour,
+                                            draft()[day.index()][hour].clone().unwrap_or_else(|| "active profile".to_string())
+                                        ),
+                                        onmousedown: move |_| {
+                                            painting.set(true);
+                                            paint(day.index(), hour);
+                                        },
+                                        onmouseenter: move |_| {
+                                            if painting() {
+                                                paint(day.index(), hour);
+                                            }
+                                        },
+                                    }
                                 }
                             }
                         }
                     }
+
+                    div { class: "flex gap-3",
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity disabled:opacity-40 disabled:cursor-not-allowed",
+                            disabled: !off_grid.is_empty(),
+                            onclick: move |_| {
+                                let grid = draft();
+                                apply_edit(profile_settings, error, |settings| {
+                                    let mut schedule = schedule_from_grid(&grid);
+                                    schedule.retain(|entry| settings.has_profile(&entry.profile));
+                                    settings.schedule = schedule;
+                                    Ok(())
+                                });
+                            },
+                            "Save Schedule"
+                        }
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-base-300)] text-[var(--color-base-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                            onclick: move |_| draft.set(schedule_grid(&profile_settings.peek())),
+                            "Revert"
+                        }
+                        button {
+                            class: "px-4 py-2 bg-[var(--color-base-300)] text-[var(--color-base-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                            onclick: move |_| draft.set(Default::default()),
+                            "Clear"
+                        }
+                    }
                 }
             }
+        }
+    }
+}
 
-            // Fixed Profile
-            div { class: if active_profile == "fixed" {
-                "mb-8 bg-[var(--color-base-200)] rounded-xl border-2 border-[var(--color-primary)] relative"
-            } else {
-                "mb-8 bg-[var(--color-base-200)] rounded-xl border border-[var(--color-base-300)]"
-            },
+pub fn Profiles() -> Element {
+    // Import context
+    let profile = use_context::<SyncSignal<ProfileSettings>>();
 
-                if active_profile == "fixed" {
-                    div { class: "absolute top-4 right-16 px-3 py-1 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-full text-xs font-semibold z-10",
-                        "Active"
-                    }
-                }
+    // State for tracking which profile section is expanded (only one at a time)
+    let mut expanded_section = use_signal(|| "".to_string());
+    let error = use_signal(|| None::<String>);
 
-                button {
-                    class: "w-full flex items-center justify-between p-4 hover:bg-[var(--color-base-300)]/50 transition-colors rounded-xl",
-                    onclick: move |_| {
-                        if expanded_section() == "fixed" {
-                            expanded_section.set("".to_string());
-                        } else {
-                            expanded_section.set("fixed".to_string());
-                        }
-                    },
+    let profiles = profile().profiles.clone();
+    let active_profile = profile().active_profile.clone();
+    let capabilities = use_context::<Capabilities>();
 
-                    h3 { class: "text-xl font-bold text-[var(--color-base-content)]",
-                        "Fixed"
-                    }
+    let add_profile = move |_| {
+        let name = profile.peek().unused_name("new profile");
+        let new_profile = Profile::new(&name, "", ProfileMode::Fixed);
+        if apply_edit(profile, error, |settings| settings.add_profile(new_profile)) {
+            expanded_section.set(name);
+        }
+    };
 
-                    svg {
-                        class: if expanded_section() == "fixed" { "w-6 h-6 transform rotate-180 transition-transform" } else { "w-6 h-6 transition-transform" },
-                        view_box: "0 0 24 24",
-                        fill: "none",
-                        stroke: "currentColor",
-                        stroke_width: "2",
-                        path { d: "M19 9l-7 7-7-7" }
+    rsx! {
+        div { class: "p-8 max-w-[1600px] mx-auto",
+
+            div { class: "flex items-start justify-between gap-6 mb-8",
+                div {
+                    p { class: "text-[var(--color-base-content)]/70",
+                        "Configure power profiles here to manage your system's power settings. Each profile allows you to set power limits and temperature thresholds. Select profile and set as active or choose a profile from the navbar"
+                    }
+                    p { class: "text-xs text-[var(--color-base-content)]/60 mt-2",
+                        "Detected CPU: {capabilities.family} ({capabilities.mailbox.as_str()} mailbox)"
                     }
                 }
+                div { class: "flex gap-3 shrink-0",
+                    button {
+                        class: "px-4 py-2 bg-[var(--color-base-300)] text-[var(--color-base-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                        title: "Activate the limits your firmware had before Ryzone changed them",
+                        onclick: move |_| {
+                            apply_edit(profile, error, activate_firmware_defaults);
+                        },
+                        "Restore Defaults"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
+                        onclick: add_profile,
+                        "New Profile"
+                    }
+                }
+            }
 
-                if expanded_section() == "fixed" {
-                    div { class: "px-4 pb-4",
-                        div {
-                            class: "bg-[var(--color-base-300)] rounded-lg p-5 border border-[var(--color-base-content)]/10",
-
-                            p { class: "text-sm text-[var(--color-base-content)]/70 mb-4",
-                                "Constant power delivery with fixed limits"
-                            }
-
-                            div { class: "grid grid-cols-2 md:grid-cols-4 gap-4",
-                                PowerInput { label: "Fast Limit", value_mw: fixed_fast_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "Slow Limit", value_mw: fixed_slow_mw, power_unit: power_unit.clone() }
-                                PowerInput { label: "STAPM Limit", value_mw: fixed_stapm_mw, power_unit: power_unit.clone() }
-                                TempInput { label: "TCTL Temp Limit", value_c: fixed_temp_c, temp_unit: temp_unit.clone() }
-                            }
+            if let Some(message) = error() {
+                div { class: "mb-4 px-4 py-3 rounded-lg bg-[var(--color-error)]/20 text-[var(--color-base-content)]",
+                    "{message}"
+                }
+            }
 
-                            div { class: "flex gap-3 mt-4",
-                                button {
-                                    class: "px-4 py-2 bg-[var(--color-secondary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                    onclick: move |_| save_fixed_profile(),
-                                    "Save Changes"
-                                }
+            ScheduleEditor { error }
 
-                                button {
-                                    class: "px-4 py-2 bg-[var(--color-primary)] text-[var(--color-primary-content)] rounded-lg font-semibold hover:opacity-90 transition-opacity",
-                                    onclick: move |_| {
-                                        save_fixed_profile();
-                                        set_as_active_profile("fixed".to_string());
-                                    },
-                                    "Set as Active"
-                                }
-                            }
-                        }
-                    }
+            for p in profiles {
+                ProfileCard {
+                    key: "{p.name}",
+                    active: p.name == active_profile,
+                    profile: p,
+                    expanded_section,
+                    error,
                 }
             }
-
         }
     }
 }
@@ -78,7 +78,7 @@ pub fn Settings() -> Element {
                                     class: if theme_light_palette == "winter" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_light_palette = "winter".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_light_palette_dropdown.set(false);
                                     },
                                     "Winter"
@@ -88,7 +88,7 @@ pub fn Settings() -> Element {
                                     class: if theme_light_palette == "black" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_light_palette = "black".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_light_palette_dropdown.set(false);
                                     },
                                     "Black"
@@ -98,7 +98,7 @@ pub fn Settings() -> Element {
                                     class: if theme_light_palette == "nord" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_light_palette = "nord".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_light_palette_dropdown.set(false);
                                     },
                                     "Nord"
@@ -141,7 +141,7 @@ pub fn Settings() -> Element {
                                     class: if theme_dark_palette == "dracula" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_dark_palette = "dracula".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_dark_palette_dropdown.set(false);
                                     },
                                     "Dracula"
@@ -151,7 +151,7 @@ pub fn Settings() -> Element {
                                     class: if theme_dark_palette == "night" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_dark_palette = "night".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_dark_palette_dropdown.set(false);
                                     },
                                     "Night"
@@ -161,7 +161,7 @@ pub fn Settings() -> Element {
                                     class: if theme_dark_palette == "dim" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().style.theme_dark_palette = "dim".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_dark_palette_dropdown.set(false);
                                     },
                                     "Dim"
@@ -211,7 +211,7 @@ pub fn Settings() -> Element {
                                     class: if temp_unit == "celsius" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().units.temp = "celsius".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_temp_unit_dropdown.set(false);
                                     },
                                     "Celsius (°C)"
@@ -221,7 +221,7 @@ pub fn Settings() -> Element {
                                     class: if temp_unit == "fahrenheit" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().units.temp = "fahrenheit".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_temp_unit_dropdown.set(false);
                                     },
                                     "Fahrenheit (°F)"
@@ -264,7 +264,7 @@ pub fn Settings() -> Element {
                                     class: if power_unit == "watt" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().units.power = "watt".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_power_unit_dropdown.set(false);
                                     },
                                     "Watt (W)"
@@ -274,7 +274,7 @@ pub fn Settings() -> Element {
                                     class: if power_unit == "milliwatt" { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                     onclick: move |_| {
                                         settings.write().units.power = "milliwatt".to_string();
-                                        let _ = write_app_settings(&settings());
+                                        save_app_settings(&settings());
                                         show_power_unit_dropdown.set(false);
                                     },
                                     "Milliwatt (mW)"
@@ -307,7 +307,7 @@ pub fn Settings() -> Element {
                         checked: start_on_login,
                         onchange: move |_| {
                             settings.write().app.start_on_login = !settings().app.start_on_login;
-                            let _ = write_app_settings(&settings());
+                            save_app_settings(&settings());
                         },
                     }
                 }
@@ -328,7 +328,7 @@ pub fn Settings() -> Element {
                         checked: minimize_to_tray,
                         onchange: move |_| {
                             settings.write().app.minimize_to_tray = !settings().app.minimize_to_tray;
-                            let _ = write_app_settings(&settings());
+                            save_app_settings(&settings());
                         },
                     }
                 }
@@ -349,7 +349,7 @@ pub fn Settings() -> Element {
                         checked: enable_logging,
                         onchange: move |_| {
                             settings.write().app.enable_logging = !settings().app.enable_logging;
-                            let _ = write_app_settings(&settings());
+                            save_app_settings(&settings());
                         },
                     }
                 }
@@ -394,7 +394,7 @@ pub fn Settings() -> Element {
                                 class: if update_frequency_ms == 1000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.update_frequency_ms = 1000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_update_freq_dropdown.set(false);
                                 },
                                 "1 second"
@@ -404,7 +404,7 @@ pub fn Settings() -> Element {
                                 class: if update_frequency_ms == 5000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.update_frequency_ms = 5000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_update_freq_dropdown.set(false);
                                 },
                                 "5 seconds"
@@ -414,7 +414,7 @@ pub fn Settings() -> Element {
                                 class: if update_frequency_ms == 10000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.update_frequency_ms = 10000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_update_freq_dropdown.set(false);
                                 },
                                 "10 seconds"
@@ -465,7 +465,7 @@ pub fn Settings() -> Element {
                                 class: if logging_frequency_ms == 1000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.logging_frequency_ms = 1000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_logging_freq_dropdown.set(false);
                                 },
                                 "1 second"
@@ -475,7 +475,7 @@ pub fn Settings() -> Element {
                                 class: if logging_frequency_ms == 5000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.logging_frequency_ms = 5000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_logging_freq_dropdown.set(false);
                                 },
                                 "5 seconds"
@@ -485,7 +485,7 @@ pub fn Settings() -> Element {
                                 class: if logging_frequency_ms == 10000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.logging_frequency_ms = 10000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_logging_freq_dropdown.set(false);
                                 },
                                 "10 seconds"
@@ -495,7 +495,7 @@ pub fn Settings() -> Element {
                                 class: if logging_frequency_ms == 30000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.logging_frequency_ms = 30000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_logging_freq_dropdown.set(false);
                                 },
                                 "30 seconds"
@@ -505,7 +505,7 @@ pub fn Settings() -> Element {
                                 class: if logging_frequency_ms == 60000 { "w-full text-left px-4 py-3 bg-[var(--color-primary)] text-[var(--color-neutral)] font-medium transition-all duration-150" } else { "w-full text-left px-4 py-3 hover:bg-[var(--color-base-300)] text-[var(--color-base-content)] transition-all duration-150" },
                                 onclick: move |_| {
                                     settings.write().app.logging_frequency_ms = 60000;
-                                    let _ = write_app_settings(&settings());
+                                    save_app_settings(&settings());
                                     show_logging_freq_dropdown.set(false);
                                 },
                                 "1 minute"
@@ -526,4 +526,10 @@ pub fn Settings() -> Element {
             // }
         }
     }
-}
\ No newline at end of file
+}
+
+// Every control saves as soon as it changes; write_app_settings logs a
+// failed save, which is all that happens with it
+pub(super) fn save_app_settings(settings: &AppSettings) {
+    let _ = write_app_settings(settings);
+}
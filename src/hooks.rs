@@ -0,0 +1,231 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use dioxus::prelude::*;
+
+use crate::utils::backend::PowerBackend;
+use crate::utils::defaults::{request_shutdown, restore_firmware_defaults, shutdown_requested};
+use crate::utils::logs::{log_error, log_warn, StatsLogger};
+use crate::utils::metrics::{unix_time_ms, MetricSample, MetricsHistory, MetricsStore};
+use crate::utils::ipc::{daemon_socket_path, Client, Request, Response, StatsBroadcaster};
+use crate::utils::settings::{profile_settings_modified, read_app_settings, read_profile_settings};
+use crate::utils::stats::{init_backend, read_current_stats, ProfileResolver};
+use crate::utils::sysfs::SysfsRoot;
+use crate::utils::types::{CurrentStats, ProfileSettings};
+
+// ─── Dioxus hook: entry point ────────────────────────────────────────────────
+// Spawns the background stats thread. The GUI is a client of ryzoned: while the
+// daemon is running, stats are streamed from its socket and the daemon does all
+// the enforcement. If no daemon is reachable the thread samples and enforces
+// locally, and keeps checking for a daemon to hand over to.
+// Every sample is also published to `broadcaster` for clients of ryzone.sock,
+// and recorded in `history` for the charts. Samples taken locally also go to
+// the on-disk MetricsStore and the stats log, which ryzoned keeps while it runs.
+// On shutdown, or if the thread panics, limits enforced locally are reset to
+// the firmware defaults before the process exits.
+pub fn use_current_stats_signal(
+    update_frequency_ms: i32,
+    profile_settings: SyncSignal<ProfileSettings>,
+    history: SyncSignal<MetricsHistory>,
+    broadcaster: StatsBroadcaster,
+) -> SyncSignal<CurrentStats> {
+    let stats_signal = use_signal_sync(CurrentStats::default);
+    let mut worker_signal = stats_signal;
+    let mut worker_history = history;
+    let mut worker_profile = profile_settings;
+    let interval_ms = update_frequency_ms.max(250) as u64;
+
+    // Closing the window (without minimize to tray) ends the app; wait for the
+    // stats thread to put the firmware defaults back first.
+    use_drop(|| {
+        request_shutdown();
+        wait_for_stats_thread();
+    });
+
+    use_hook(move || {
+        let spawned = std::thread::Builder::new().name(STATS_THREAD.to_string()).spawn(move || {
+            // Only created if we ever have to sample without the daemon
+            let mut local_backend: Option<Option<Box<dyn PowerBackend>>> = None;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                run_stats_thread(
+                    &mut local_backend,
+                    interval_ms,
+                    profile_settings,
+                    &mut worker_signal,
+                    &mut worker_history,
+                    &mut worker_profile,
+                    &broadcaster,
+                )
+            }));
+
+            // Only restore what this process enforced itself; the daemon
+            // handles its own shutdown.
+            if let Some(backend) = local_backend.as_ref().and_then(Option::as_deref) {
+                if let Err(e) = restore_firmware_defaults(backend) {
+                    log_error("defaults", format!("Failed to restore firmware defaults: {}", e));
+                }
+            }
+            finish_stats_thread();
+            if result.is_err() {
+                log_error("stats", "Stats thread panicked; restored firmware defaults and exiting");
+                std::process::exit(101);
+            }
+            std::process::exit(0);
+        });
+        if let Err(e) = spawned {
+            log_error("stats", format!("Failed to start the stats thread: {}", e));
+        }
+    });
+
+    stats_signal
+}
+
+// ─── Stats thread ────────────────────────────────────────────────────────────
+// Returns once shutdown is requested.
+fn run_stats_thread(
+    local_backend: &mut Option<Option<Box<dyn PowerBackend>>>,
+    interval_ms: u64,
+    profile_settings: SyncSignal<ProfileSettings>,
+    worker_signal: &mut SyncSignal<CurrentStats>,
+    worker_history: &mut SyncSignal<MetricsHistory>,
+    worker_profile: &mut SyncSignal<ProfileSettings>,
+    broadcaster: &StatsBroadcaster,
+) {
+    let sysfs = SysfsRoot::from_env();
+    let mut metrics_store: Option<Option<MetricsStore>> = None;
+    let mut stats_logger: Option<Option<StatsLogger>> = None;
+    let mut resolver = ProfileResolver::default();
+    let mut profile_modified = profile_settings_modified();
+
+    // Profiles can also be changed by other clients of the daemon or
+    // by hand; keep the GUI's copy in sync with the file.
+    let mut sync_profile = move || {
+        let modified = profile_settings_modified();
+        if modified != profile_modified {
+            if let Ok(settings) = read_profile_settings() {
+                worker_profile.set(settings);
+            }
+            profile_modified = modified;
+        }
+    };
+
+    loop {
+        if let Ok(mut client) = Client::connect(&daemon_socket_path()) {
+            // The daemon records from here on; reopen afterwards so the
+            // store picks up what it wrote
+            metrics_store = None;
+            if client.send(Request::Subscribe).is_ok() {
+                while let Ok(Response::Stats { stats }) = client.read_response() {
+                    if shutdown_requested() {
+                        break;
+                    }
+                    sync_profile();
+                    broadcaster.publish(&stats);
+                    worker_history.write().record(&stats);
+                    worker_signal.set(*stats);
+                }
+            }
+            // Daemon went away; fall back to local sampling below
+        }
+
+        if shutdown_requested() {
+            return;
+        }
+
+        sync_profile();
+        let backend = local_backend.get_or_insert_with(init_backend);
+        ENFORCING_LOCALLY.store(backend.is_some(), Ordering::SeqCst);
+        let ps = profile_settings.peek().clone();
+        if let Ok(next_stats) = read_current_stats(backend.as_deref(), &sysfs, &ps, &mut resolver) {
+            broadcaster.publish(&next_stats);
+            let now = unix_time_ms();
+            let sample = MetricSample::from_stats(&next_stats, now);
+            worker_history.write().push(sample);
+            if let Some(store) = metrics_store.get_or_insert_with(open_metrics_store) {
+                if let Err(e) = store.record(sample) {
+                    log_error("metrics", format!("Failed to record metrics: {}", e));
+                }
+            }
+            if let Some(logger) = stats_logger.get_or_insert_with(open_stats_logger) {
+                logger.reload_settings();
+                if let Err(e) = logger.log(&next_stats, now) {
+                    log_error("logs", format!("Failed to write stats log: {}", e));
+                }
+            }
+            worker_signal.set(next_stats);
+        }
+        sleep_unless_shutdown(Duration::from_millis(interval_ms));
+    }
+}
+
+const STATS_THREAD: &str = "ryzone-stats";
+// Longest a window close or a panic waits for the defaults to be restored
+const RESTORE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Set while the stats thread holds limits it enforced itself
+static ENFORCING_LOCALLY: AtomicBool = AtomicBool::new(false);
+static STATS_THREAD_DONE: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+fn finish_stats_thread() {
+    let (done, finished) = &STATS_THREAD_DONE;
+    *done.lock().unwrap() = true;
+    finished.notify_all();
+}
+
+// Returns straight away unless there are local limits to put back
+fn wait_for_stats_thread() {
+    if !ENFORCING_LOCALLY.load(Ordering::SeqCst) {
+        return;
+    }
+    let (done, finished) = &STATS_THREAD_DONE;
+    let guard = done.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = finished.wait_timeout_while(guard, RESTORE_TIMEOUT, |done| !*done);
+}
+
+fn sleep_unless_shutdown(duration: Duration) {
+    let until = Instant::now() + duration;
+    while !shutdown_requested() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(50)));
+    }
+}
+
+// A panic anywhere else in the GUI also ends the app, so have the stats
+// thread restore the firmware defaults before it goes. Panics in the stats
+// thread itself are caught there.
+pub fn install_panic_restore() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if std::thread::current().name() != Some(STATS_THREAD) {
+            request_shutdown();
+            wait_for_stats_thread();
+        }
+    }));
+}
+
+fn open_metrics_store() -> Option<MetricsStore> {
+    let result = read_app_settings().and_then(|settings| MetricsStore::open_default(&settings));
+    match result {
+        Ok(store) => Some(store),
+        Err(e) => {
+            log_warn("metrics", format!("Metrics history disabled: {}", e));
+            None
+        }
+    }
+}
+
+fn open_stats_logger() -> Option<StatsLogger> {
+    match StatsLogger::open_default() {
+        Ok(logger) => Some(logger),
+        Err(e) => {
+            log_warn("logs", format!("Stats logging disabled: {}", e));
+            None
+        }
+    }
+}
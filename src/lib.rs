@@ -0,0 +1,4 @@
+//! Everything that doesn't need a window: hardware access, battery readers, settings and
+//! profile enforcement. Shared by the `ryzone` GUI and the headless `ryzoned` daemon.
+
+pub mod utils;
@@ -4,20 +4,28 @@ use dioxus::prelude::*;
 
 use dioxus_desktop::{Config, WindowCloseBehaviour};
 
-use components::{Navbar, Battery, Dashboard, Info, Profiles, Settings};
+use components::{Navbar, Battery, Cpu, Dashboard, Info, Log, PmTableExplorer, Profiles, Settings};
 
-use std::os::unix::net::{UnixStream, UnixListener};
-use std::io::Write;
-use std::path::PathBuf;
+use std::sync::Arc;
 
 
 
 /// Define a components module that contains all shared components for our app.
+mod app_server;
 mod components;
-mod utils;
+mod hooks;
 
+// Non-GUI code lives in the library so ryzoned can share it
+use ryzone::utils;
+
+use app_server::AppHandler;
+use hooks::{install_panic_restore, use_current_stats_signal};
+use utils::capabilities::capabilities_for;
+use utils::defaults::install_shutdown_handler;
+use utils::metrics::MetricsHistory;
+use utils::ipc::{app_socket_path, bind_socket, serve, Client, Request, StatsBroadcaster};
 use utils::settings::{read_app_settings, read_profile_settings};
-use utils::stats::use_current_stats_signal;
+use utils::sysfs::SysfsRoot;
 use utils::types::{AppSettings, ProfileSettings};
 
 // We can import assets in dioxus with the `asset!` macro. This macro takes a path to an asset relative to the crate root.
@@ -26,21 +34,18 @@ const FAVICON: Asset = asset!("/assets/icon.ico");
 // The asset macro also minifies some assets like CSS and JS to make bundled smaller
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
-fn socket_path() -> PathBuf {
-    // XDG_RUNTIME_DIR is per-user, tmpfs, and cleaned on logout
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
-        .unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(runtime_dir).join("ryzone.sock")
-}
-
 fn main() {
-    let sock = socket_path();
+    let sock = app_socket_path();
 
-    if let Ok(mut stream) = UnixStream::connect(&sock) {
-        let _ = stream.write_all(b"show");
+    if let Ok(mut client) = Client::connect(&sock) {
+        let _ = client.request(Request::Show);
         std::process::exit(0);
     }
-    let _ = std::fs::remove_file(&sock);
+
+    // SIGTERM / SIGINT reset locally enforced limits before exiting (see hooks.rs)
+    install_shutdown_handler();
+    // ...and so do panics; the hook waits for the stats thread to do it
+    install_panic_restore();
 
     // Read settings ONCE here
     let app_settings = read_app_settings().expect("Failed to read app settings");
@@ -67,23 +72,9 @@ fn main() {
 /// Components should be annotated with `#[component]` to support props, better error messages, and autocomplete
 #[component]
 fn App() -> Element {
-    // Check if an instance is already running
-    // If yes, show the already running instance
-    // Runs ONCE on first render -- creates the listener thread
+    // Set by a `show` request on the socket, e.g. from a second instance
     let mut show_requested = use_signal_sync(|| false);
 
-    use_hook(move || {
-        std::thread::spawn(move || {
-            let listener = UnixListener::bind(socket_path()).expect("Failed to bind socket");
-            // Blocking .incoming() -- waits for connections, no sleep needed
-            for stream in listener.incoming() {
-                if stream.is_ok() {
-                    show_requested.set(true);
-                }
-            }
-        });
-    });
-
     // Reacts to the signal change
     use_effect(move || {
         if show_requested() {
@@ -103,15 +94,43 @@ fn App() -> Element {
     let profile_settings = use_context::<ProfileSettings>();
 
     use_context_provider(|| Signal::new(app_settings.clone()));
+    // Which limits the Profiles tab offers
+    use_context_provider(|| capabilities_for(&SysfsRoot::from_env()));
 
     // SyncSignal so the background stats thread can read profile settings
     let profile_signal: SyncSignal<ProfileSettings> = use_signal_sync(|| profile_settings);
     use_context_provider(|| profile_signal);
 
+    let broadcaster = use_hook(StatsBroadcaster::default);
     let update_frequency_ms = app_settings.app.update_frequency_ms;
-    let current_stats = use_current_stats_signal(update_frequency_ms, profile_signal);
+    // Recent samples for the charts, filled by the stats thread
+    let metrics_history: SyncSignal<MetricsHistory> = use_signal_sync(|| {
+        MetricsHistory::for_interval(update_frequency_ms.max(250) as u64)
+    });
+    use_context_provider(|| metrics_history);
+    let current_stats = use_current_stats_signal(
+        update_frequency_ms,
+        profile_signal,
+        metrics_history,
+        broadcaster.clone(),
+    );
     use_context_provider(|| current_stats);
 
+    // Runs ONCE on first render -- serves the command protocol on ryzone.sock
+    use_hook(move || {
+        let handler = Arc::new(AppHandler {
+            show_requested,
+            stats: current_stats,
+            profile: profile_signal,
+            broadcaster,
+        });
+        std::thread::spawn(move || {
+            let listener = bind_socket(&app_socket_path()).expect("Failed to bind socket");
+            // Blocking -- one thread per connection, no sleep needed
+            serve(listener, handler);
+        });
+    });
+
     // Runtime-only UI state (not persisted) stays as individual signals
     let mut active_tab = use_signal(|| "dashboard".to_string());
 
@@ -146,9 +165,12 @@ fn App() -> Element {
             main { class: "flex-1 overflow-auto",
                 match active_tab().as_str() {
                     "dashboard" => rsx! { Dashboard {} },
+                    "cpu" => rsx! { Cpu {} },
                     "battery" => rsx! { Battery {} },
                     "profiles" => rsx! { Profiles {} },
                     "settings" => rsx! { Settings {} },
+                    "log" => rsx! { Log {} },
+                    "pm_table" => rsx! { PmTableExplorer {} },
                     "info" => rsx! { Info {} },
                     _ => rsx! { Dashboard {} },
                 }
@@ -0,0 +1,239 @@
+use std::sync::Mutex;
+
+use libryzenadj::RyzenAdj;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::battery::write_charge_thresholds;
+use crate::utils::sysfs::SysfsRoot;
+
+// ─── Backend data ────────────────────────────────────────────────────────────
+// Values are kept in the units libryzenadj reports (MHz, °C, W); stats.rs does
+// the conversion into the integer units stored in CurrentStats.
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Telemetry {
+    pub core_clks_mhz: Vec<f32>, // one entry per core slot in the SMU table, 0 when parked
+    pub core_powers_w: Vec<f32>, // per core slot, empty if not reported
+    pub core_temps_c: Vec<f32>, // per core slot, empty if not reported
+    pub tctl_value_c: f32,
+    pub cclk_busy_percent: f32,
+    pub gfx_clk_mhz: f32,
+    pub gfx_temp_c: f32,
+    pub fast_value_w: f32,
+    pub slow_value_w: f32,
+    pub stapm_value_w: f32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub fast_w: f32,
+    pub slow_w: f32,
+    pub stapm_w: f32,
+    pub tctl_c: f32,
+}
+
+// ─── Backend trait ───────────────────────────────────────────────────────────
+// Everything the sampling and enforcement loop needs from the hardware.
+// `refresh` is called once per tick before any of the reads.
+pub trait PowerBackend {
+    fn refresh(&self) -> Result<(), String>;
+    fn read_telemetry(&self) -> Result<Telemetry, String>;
+    fn read_limits(&self) -> Result<Limits, String>;
+    fn set_fast_limit(&self, mw: u32) -> Result<(), String>;
+    fn set_slow_limit(&self, mw: u32) -> Result<(), String>;
+    fn set_stapm_limit(&self, mw: u32) -> Result<(), String>;
+    fn set_tctl_temp(&self, c: u32) -> Result<(), String>;
+    // Battery charge thresholds live in sysfs, but need the same privileges
+    fn set_charge_thresholds(&self, battery: &str, start_percent: Option<i32>, end_percent: i32) -> Result<(), String>;
+}
+
+// ─── libryzenadj implementation ──────────────────────────────────────────────
+impl PowerBackend for RyzenAdj {
+    fn refresh(&self) -> Result<(), String> {
+        RyzenAdj::refresh(self).map_err(|e| format!("refresh failed: {:?}", e))
+    }
+
+    fn read_telemetry(&self) -> Result<Telemetry, String> {
+        // Reads fail past the last core slot in the SMU table; parked cores
+        // read 0 (or NaN on some tables)
+        let mut core_clks_mhz = Vec::new();
+        for core in 0..32u32 {
+            match self.get_core_clk(core) {
+                Ok(clk) => core_clks_mhz.push(if clk.is_finite() { clk.max(0.0) } else { 0.0 }),
+                Err(_) => break,
+            }
+        }
+        let cores = core_clks_mhz.len() as u32;
+
+        Ok(Telemetry {
+            core_clks_mhz,
+            core_powers_w: read_per_core(cores, |core| self.get_core_power(core).ok()),
+            core_temps_c: read_per_core(cores, |core| self.get_core_temp(core).ok()),
+            tctl_value_c: self.get_tctl_temp_value().unwrap_or(0.0),
+            cclk_busy_percent: self.get_cclk_busy_value().unwrap_or(0.0),
+            gfx_clk_mhz: self.get_gfx_clk().unwrap_or(0.0),
+            gfx_temp_c: self.get_gfx_temp().unwrap_or(0.0),
+            fast_value_w: self.get_fast_value().unwrap_or(0.0),
+            slow_value_w: self.get_slow_value().unwrap_or(0.0),
+            stapm_value_w: self.get_stapm_value().unwrap_or(0.0),
+        })
+    }
+
+    fn read_limits(&self) -> Result<Limits, String> {
+        Ok(Limits {
+            fast_w: self.get_fast_limit().unwrap_or(0.0),
+            slow_w: self.get_slow_limit().unwrap_or(0.0),
+            stapm_w: self.get_stapm_limit().unwrap_or(0.0),
+            tctl_c: self.get_tctl_temp().unwrap_or(0.0),
+        })
+    }
+
+    fn set_fast_limit(&self, mw: u32) -> Result<(), String> {
+        RyzenAdj::set_fast_limit(self, mw).map_err(|e| format!("set_fast_limit: {:?}", e))
+    }
+
+    fn set_slow_limit(&self, mw: u32) -> Result<(), String> {
+        RyzenAdj::set_slow_limit(self, mw).map_err(|e| format!("set_slow_limit: {:?}", e))
+    }
+
+    fn set_stapm_limit(&self, mw: u32) -> Result<(), String> {
+        RyzenAdj::set_stapm_limit(self, mw).map_err(|e| format!("set_stapm_limit: {:?}", e))
+    }
+
+    fn set_tctl_temp(&self, c: u32) -> Result<(), String> {
+        RyzenAdj::set_tctl_temp(self, c).map_err(|e| format!("set_tctl_temp: {:?}", e))
+    }
+
+    fn set_charge_thresholds(&self, battery: &str, start_percent: Option<i32>, end_percent: i32) -> Result<(), String> {
+        write_charge_thresholds(&SysfsRoot::from_env(), battery, start_percent, end_percent)
+    }
+}
+
+// Empty if no core reports the value, e.g. on tables without per-core power
+fn read_per_core(cores: u32, read: impl Fn(u32) -> Option<f32>) -> Vec<f32> {
+    let values: Vec<Option<f32>> = (0..cores)
+        .map(|core| read(core).filter(|v| v.is_finite()))
+        .collect();
+    if values.iter().all(Option::is_none) {
+        return Vec::new();
+    }
+    values.into_iter().map(|v| v.unwrap_or(0.0)).collect()
+}
+
+// ─── In-memory fake ──────────────────────────────────────────────────────────
+// Behaves like an APU that accepts every write. Setting a limit updates what
+// `read_limits` returns, and every write is recorded so callers can assert on
+// exactly what enforcement did.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackendWrite {
+    FastLimit(u32),  // milliwatts
+    SlowLimit(u32),  // milliwatts
+    StapmLimit(u32), // milliwatts
+    TctlTemp(u32),   // celsius
+    ChargeThresholds { battery: String, start_percent: Option<i32>, end_percent: i32 },
+}
+
+#[derive(Default)]
+struct FakeState {
+    telemetry: Telemetry,
+    limits: Limits,
+    writes: Vec<BackendWrite>,
+    fail_writes: bool,
+}
+
+#[derive(Default)]
+pub struct FakeBackend {
+    state: Mutex<FakeState>,
+}
+
+impl FakeBackend {
+    pub fn new(telemetry: Telemetry, limits: Limits) -> Self {
+        Self {
+            state: Mutex::new(FakeState {
+                telemetry,
+                limits,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn set_telemetry(&self, telemetry: Telemetry) {
+        self.state.lock().unwrap().telemetry = telemetry;
+    }
+
+    pub fn set_limits(&self, limits: Limits) {
+        self.state.lock().unwrap().limits = limits;
+    }
+
+    // Makes every subsequent set_* call fail, like a locked-down firmware would.
+    pub fn set_fail_writes(&self, fail: bool) {
+        self.state.lock().unwrap().fail_writes = fail;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.state.lock().unwrap().limits.clone()
+    }
+
+    pub fn writes(&self) -> Vec<BackendWrite> {
+        self.state.lock().unwrap().writes.clone()
+    }
+
+    pub fn clear_writes(&self) {
+        self.state.lock().unwrap().writes.clear();
+    }
+
+    fn apply(&self, write: BackendWrite) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if state.fail_writes {
+            return Err(format!("fake backend rejected {:?}", write));
+        }
+        match write {
+            BackendWrite::FastLimit(mw) => state.limits.fast_w = mw as f32 / 1000.0,
+            BackendWrite::SlowLimit(mw) => state.limits.slow_w = mw as f32 / 1000.0,
+            BackendWrite::StapmLimit(mw) => state.limits.stapm_w = mw as f32 / 1000.0,
+            BackendWrite::TctlTemp(c) => state.limits.tctl_c = c as f32,
+            BackendWrite::ChargeThresholds { .. } => {}
+        }
+        state.writes.push(write);
+        Ok(())
+    }
+}
+
+impl PowerBackend for FakeBackend {
+    fn refresh(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn read_telemetry(&self) -> Result<Telemetry, String> {
+        Ok(self.state.lock().unwrap().telemetry.clone())
+    }
+
+    fn read_limits(&self) -> Result<Limits, String> {
+        Ok(self.state.lock().unwrap().limits.clone())
+    }
+
+    fn set_fast_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply(BackendWrite::FastLimit(mw))
+    }
+
+    fn set_slow_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply(BackendWrite::SlowLimit(mw))
+    }
+
+    fn set_stapm_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply(BackendWrite::StapmLimit(mw))
+    }
+
+    fn set_tctl_temp(&self, c: u32) -> Result<(), String> {
+        self.apply(BackendWrite::TctlTemp(c))
+    }
+
+    fn set_charge_thresholds(&self, battery: &str, start_percent: Option<i32>, end_percent: i32) -> Result<(), String> {
+        self.apply(BackendWrite::ChargeThresholds {
+            battery: battery.to_string(),
+            start_percent,
+            end_percent,
+        })
+    }
+}
@@ -2,6 +2,11 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use crate::utils::sysfs::SysfsRoot;
+use crate::utils::types::BatteryInfo;
+
+// All system batteries combined: capacities, energy and power are summed,
+// percentages weighted by capacity. `batteries` keeps the individual readings.
 #[derive(Clone, Debug, Default)]
 pub struct BatterySnapshot {
     pub charge_status: String,
@@ -14,13 +19,21 @@ pub struct BatterySnapshot {
     pub cycle_count_cycles: i32,
     pub temperature_c: i32,
     pub power_draw_mw: i32,
+    pub batteries: Vec<BatteryInfo>,
 }
 
-pub fn read_battery_snapshot() -> io::Result<Option<BatterySnapshot>> {
-    let Some(battery_dir) = find_battery_dir("/sys/class/power_supply")? else {
+pub fn read_battery_snapshot(sysfs: &SysfsRoot) -> io::Result<Option<BatterySnapshot>> {
+    let batteries: Vec<BatteryInfo> = find_battery_dirs(&sysfs.power_supply_dir())?
+        .iter()
+        .map(|dir| read_battery(dir))
+        .collect();
+    if batteries.is_empty() {
         return Ok(None);
-    };
+    }
+    Ok(Some(aggregate_batteries(batteries)))
+}
 
+fn read_battery(battery_dir: &Path) -> BatteryInfo {
     let status = normalize_status(read_trimmed(&battery_dir.join("status")).as_deref());
     let charge_percent = read_i32(&battery_dir.join("capacity")).unwrap_or(0).clamp(0, 100);
 
@@ -36,7 +49,7 @@ pub fn read_battery_snapshot() -> io::Result<Option<BatterySnapshot>> {
 
     // Design/full capacities should come from BMS capacity files.
     // If only charge_* is available, convert with design voltage (not voltage_now).
-    let design_capacity_mwh = read_energy_mwh(&battery_dir, "energy_full_design")
+    let design_capacity_mwh = read_energy_mwh(battery_dir, "energy_full_design")
         .or_else(|| {
             charge_to_mwh(
                 read_i64(&battery_dir.join("charge_full_design"))?,
@@ -44,7 +57,7 @@ pub fn read_battery_snapshot() -> io::Result<Option<BatterySnapshot>> {
             )
         })
         .unwrap_or(-1);
-    let full_charge_capacity_mwh = read_energy_mwh(&battery_dir, "energy_full")
+    let full_charge_capacity_mwh = read_energy_mwh(battery_dir, "energy_full")
         .or_else(|| {
             charge_to_mwh(
                 read_i64(&battery_dir.join("charge_full"))?,
@@ -52,23 +65,30 @@ pub fn read_battery_snapshot() -> io::Result<Option<BatterySnapshot>> {
             )
         })
         .unwrap_or(-1);
-    let current_capacity_mwh = read_energy_mwh(&battery_dir, "energy_now")
+    let current_capacity_mwh = read_energy_mwh(battery_dir, "energy_now")
         .or_else(|| charge_to_mwh(read_i64(&battery_dir.join("charge_now"))?, voltage_uv))
         .unwrap_or(0);
 
-    let health_percent = if design_capacity_mwh > 0 && full_charge_capacity_mwh > 0 {
-        (((full_charge_capacity_mwh as i64 * 100) / design_capacity_mwh as i64) as i32).clamp(0, 100)
-    } else {
-        -1
-    };
+    let health_percent = health_percent(design_capacity_mwh, full_charge_capacity_mwh);
 
     let cycle_count_cycles = read_i32(&battery_dir.join("cycle_count")).unwrap_or(0).max(0);
 
-    let temperature_c = read_temperature_c(&battery_dir).unwrap_or(-1);
+    let temperature_c = read_temperature_c(battery_dir).unwrap_or(-1);
+
+    let power_draw_mw = read_power_mw(battery_dir, voltage_uv).unwrap_or(0).max(0);
 
-    let power_draw_mw = read_power_mw(&battery_dir, voltage_uv).unwrap_or(0).max(0);
+    let text = |file: &str| read_trimmed(&battery_dir.join(file)).unwrap_or_default();
+    let threshold = |kind: ThresholdKind| read_i32(&threshold_file(battery_dir, kind)?);
 
-    Ok(Some(BatterySnapshot {
+    BatteryInfo {
+        name: battery_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        manufacturer: text("manufacturer"),
+        model: text("model_name"),
+        serial: text("serial_number"),
+        technology: text("technology"),
         charge_status: status,
         charge_percent,
         design_capacity_mwh,
@@ -79,21 +99,231 @@ pub fn read_battery_snapshot() -> io::Result<Option<BatterySnapshot>> {
         cycle_count_cycles,
         temperature_c,
         power_draw_mw,
-    }))
+        charge_start_threshold_percent: threshold(ThresholdKind::Start),
+        charge_end_threshold_percent: threshold(ThresholdKind::End),
+    }
+}
+
+fn health_percent(design_capacity_mwh: i32, full_charge_capacity_mwh: i32) -> i32 {
+    if design_capacity_mwh > 0 && full_charge_capacity_mwh > 0 {
+        (((full_charge_capacity_mwh as i64 * 100) / design_capacity_mwh as i64) as i32).clamp(0, 100)
+    } else {
+        -1
+    }
+}
+
+// With a single battery this returns its own readings unchanged.
+fn aggregate_batteries(batteries: Vec<BatteryInfo>) -> BatterySnapshot {
+    // Sums a capacity, or -1 when any battery doesn't report it
+    let sum_known = |field: fn(&BatteryInfo) -> i32| {
+        batteries
+            .iter()
+            .map(field)
+            .try_fold(0, |total, value| (value > 0).then_some(total + value))
+            .unwrap_or(-1)
+    };
+    let design_capacity_mwh = sum_known(|b| b.design_capacity_mwh);
+    let full_charge_capacity_mwh = sum_known(|b| b.full_charge_capacity_mwh);
+
+    // Charge % weighted by each battery's full capacity, if all of them have one
+    let charge_percent = if full_charge_capacity_mwh > 0 {
+        let weighted: i64 = batteries
+            .iter()
+            .map(|b| b.charge_percent as i64 * b.full_charge_capacity_mwh as i64)
+            .sum();
+        (weighted / full_charge_capacity_mwh as i64) as i32
+    } else {
+        batteries.iter().map(|b| b.charge_percent).sum::<i32>() / batteries.len() as i32
+    };
+
+    // Whichever battery is charging or discharging says what the system is
+    // doing; the other one is often idle
+    let statuses: Vec<&str> = batteries.iter().map(|b| b.charge_status.as_str()).collect();
+    let charge_status = ["charging", "discharging"]
+        .into_iter()
+        .find(|status| statuses.contains(status))
+        .or_else(|| statuses.iter().copied().find(|status| *status != "na"))
+        .unwrap_or("na")
+        .to_string();
+
+    let voltages: Vec<i32> = batteries
+        .iter()
+        .map(|b| b.voltage_millivolt)
+        .filter(|v| *v > 0)
+        .collect();
+    let voltage_millivolt = if voltages.is_empty() {
+        0
+    } else {
+        voltages.iter().sum::<i32>() / voltages.len() as i32
+    };
+
+    BatterySnapshot {
+        charge_status,
+        charge_percent: charge_percent.clamp(0, 100),
+        design_capacity_mwh,
+        full_charge_capacity_mwh,
+        current_capacity_mwh: batteries.iter().map(|b| b.current_capacity_mwh).sum(),
+        health_percent: health_percent(design_capacity_mwh, full_charge_capacity_mwh),
+        voltage_millivolt,
+        cycle_count_cycles: batteries.iter().map(|b| b.cycle_count_cycles).max().unwrap_or(0),
+        temperature_c: batteries.iter().map(|b| b.temperature_c).max().unwrap_or(-1),
+        power_draw_mw: batteries.iter().map(|b| b.power_draw_mw).sum(),
+        batteries,
+    }
 }
 
-fn find_battery_dir(base: &str) -> io::Result<Option<PathBuf>> {
+// System batteries, sorted by name. Peripherals report type "Battery" too, so
+// those (scope "Device") are skipped; drivers without `type` are matched by
+// the usual BAT* name.
+fn find_battery_dirs(base: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
     for entry in fs::read_dir(base)? {
-        let entry = entry?;
-        let file_name = entry.file_name();
-        let Some(name) = file_name.to_str() else {
-            continue;
+        let path = entry?.path();
+        let is_battery = match read_trimmed(&path.join("type")) {
+            Some(kind) => kind == "Battery",
+            None => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("BAT")),
         };
-        if name.starts_with("BAT") && entry.path().is_dir() {
-            return Ok(Some(entry.path()));
+        let is_device = read_trimmed(&path.join("scope")).as_deref() == Some("Device");
+        if is_battery && !is_device && path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+// ─── Charge thresholds ───────────────────────────────────────────────────────
+// charge_control_{start,end}_threshold (older ThinkPad drivers:
+// charge_{start,stop}_threshold). Many laptops only have the end threshold.
+// The files are root-only, so writes go through the backend.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThresholdKind {
+    Start,
+    End,
+}
+
+fn threshold_file(battery_dir: &Path, kind: ThresholdKind) -> Option<PathBuf> {
+    let names = match kind {
+        ThresholdKind::Start => ["charge_control_start_threshold", "charge_start_threshold"],
+        ThresholdKind::End => ["charge_control_end_threshold", "charge_stop_threshold"],
+    };
+    names
+        .iter()
+        .map(|name| battery_dir.join(name))
+        .find(|path| path.exists())
+}
+
+// Writes the thresholds of one battery and reads them back. A start threshold
+// is skipped on batteries that only have an end threshold. Firmware that only
+// takes fixed steps either rejects the value or rounds it; both are errors.
+pub fn write_charge_thresholds(
+    sysfs: &SysfsRoot,
+    battery: &str,
+    start_percent: Option<i32>,
+    end_percent: i32,
+) -> Result<(), String> {
+    let battery_dir = find_battery_dirs(&sysfs.power_supply_dir())
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|dir| dir.file_name().and_then(|name| name.to_str()) == Some(battery))
+        .ok_or_else(|| format!("no battery named '{}'", battery))?;
+    let end_file = threshold_file(&battery_dir, ThresholdKind::End)
+        .ok_or_else(|| format!("{} doesn't support charge thresholds", battery))?;
+    let start_file = threshold_file(&battery_dir, ThresholdKind::Start);
+
+    let mut writes = vec![(end_file, end_percent)];
+    if let (Some(file), Some(start)) = (start_file, start_percent) {
+        // The driver rejects start >= end at every step, so lower the start
+        // first when it is currently above the new end
+        let current_start = read_i32(&file).unwrap_or(0);
+        if current_start >= end_percent {
+            writes.insert(0, (file, start));
+        } else {
+            writes.push((file, start));
+        }
+    }
+
+    for (file, value) in writes {
+        let label = file.file_name().unwrap().to_string_lossy().into_owned();
+        fs::write(&file, value.to_string()).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidInput => format!(
+                "{}: firmware rejected {}% for {} (it may only accept fixed steps)",
+                battery, value, label
+            ),
+            _ => format!("{}: {}: {}", battery, label, e),
+        })?;
+        match read_i32(&file) {
+            Some(actual) if actual != value => {
+                return Err(format!(
+                    "{}: firmware only accepts fixed steps, {} is {}% instead of {}%",
+                    battery, label, actual, value
+                ));
+            }
+            _ => {}
         }
     }
-    Ok(None)
+    Ok(())
+}
+
+// ─── AC adapter ──────────────────────────────────────────────────────────────
+// Mains and USB (USB-C / PD) supplies under class/power_supply. The system is
+// on AC when any of them is online; the battery status alone can't tell, as
+// it reads "not charging" or "unknown" on AC with charge thresholds.
+#[derive(Clone, Debug, Default)]
+pub struct AdapterSnapshot {
+    pub online: bool,
+    pub name: String,  // the supply that is online, empty when none is
+    pub power_mw: i32, // rated or negotiated power, -1 if not reported
+}
+
+// Ok(None) when the machine exposes no Mains/USB supply at all
+pub fn read_adapter_snapshot(sysfs: &SysfsRoot) -> io::Result<Option<AdapterSnapshot>> {
+    let mut found = false;
+    for entry in fs::read_dir(sysfs.power_supply_dir())? {
+        let dir = entry?.path();
+        let kind = read_trimmed(&dir.join("type")).unwrap_or_default();
+        let is_adapter = kind == "Mains" || kind.starts_with("USB");
+        // Peripherals (mice, headsets) report scope "Device"
+        if !is_adapter || read_trimmed(&dir.join("scope")).as_deref() == Some("Device") {
+            continue;
+        }
+        found = true;
+
+        if read_i32(&dir.join("online")) == Some(1) {
+            return Ok(Some(AdapterSnapshot {
+                online: true,
+                name: dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                power_mw: read_adapter_power_mw(&dir).unwrap_or(-1),
+            }));
+        }
+    }
+
+    Ok(found.then(|| AdapterSnapshot {
+        online: false,
+        name: String::new(),
+        power_mw: -1,
+    }))
+}
+
+// power_now if the driver has it, else the negotiated USB PD contract
+// (voltage_max x current_max), else voltage_now x current_now.
+fn read_adapter_power_mw(dir: &Path) -> Option<i32> {
+    if let Some(power_uw) = read_i64(&dir.join("power_now")).filter(|v| *v > 0) {
+        return Some((power_uw / 1000) as i32);
+    }
+    let product = |voltage: &str, current: &str| {
+        let voltage_uv = read_i64(&dir.join(voltage)).filter(|v| *v > 0)?;
+        let current_ua = read_i64(&dir.join(current)).filter(|v| *v > 0)?;
+        Some(((voltage_uv as i128 * current_ua as i128) / 1_000_000_000) as i32)
+    };
+    product("voltage_max", "current_max").or_else(|| product("voltage_now", "current_now"))
 }
 
 fn read_trimmed(path: &Path) -> Option<String> {
@@ -116,7 +346,7 @@ fn read_energy_mwh(battery_dir: &Path, file_name: &str) -> Option<i32> {
     Some((value_uwh / 1000) as i32)
 }
 
-fn charge_to_mwh(charge_uah: i64, voltage_uv: i64) -> Option<i32> {
+pub fn charge_to_mwh(charge_uah: i64, voltage_uv: i64) -> Option<i32> {
     if charge_uah <= 0 || voltage_uv <= 0 {
         return None;
     }
@@ -134,7 +364,7 @@ fn read_temperature_c(battery_dir: &Path) -> Option<i32> {
     None
 }
 
-fn scale_temperature_c(raw: i64) -> i32 {
+pub fn scale_temperature_c(raw: i64) -> i32 {
     if raw > 1000 {
         (raw / 1000) as i32
     } else if raw > 200 {
@@ -157,7 +387,7 @@ fn read_power_mw(battery_dir: &Path, voltage_uv: i64) -> Option<i32> {
     Some(power_mw as i32)
 }
 
-fn normalize_status(status: Option<&str>) -> String {
+pub fn normalize_status(status: Option<&str>) -> String {
     match status.unwrap_or("").trim().to_lowercase().as_str() {
         "charging" => "charging".to_string(),
         "discharging" => "discharging".to_string(),
@@ -167,3 +397,116 @@ fn normalize_status(status: Option<&str>) -> String {
         _ => "na".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> SysfsRoot {
+        SysfsRoot::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/sysfs").join(name))
+    }
+
+    fn snapshot(name: &str) -> BatterySnapshot {
+        read_battery_snapshot(&fixture(name)).unwrap().unwrap()
+    }
+
+    // One row of the "Expected readings" table in fixtures/README.md:
+    // status, charge, (design, full, now) capacity, health, voltage, temp, power draw
+    type Readings = (&'static str, i32, (i32, i32, i32), i32, i32, i32, i32);
+
+    fn readings(s: &BatterySnapshot) -> (&str, i32, (i32, i32, i32), i32, i32, i32, i32) {
+        (
+            s.charge_status.as_str(),
+            s.charge_percent,
+            (s.design_capacity_mwh, s.full_charge_capacity_mwh, s.current_capacity_mwh),
+            s.health_percent,
+            s.voltage_millivolt,
+            s.temperature_c,
+            s.power_draw_mw,
+        )
+    }
+
+    fn assert_readings(name: &str, expected: Readings) {
+        assert_eq!(readings(&snapshot(name)), expected, "fixture {}", name);
+    }
+
+    #[test]
+    fn energy_power_now() {
+        assert_readings("energy-power-now", ("discharging", 64, (57000, 51300, 32832), 90, 11940, 31, 9450));
+    }
+
+    #[test]
+    fn charge_no_power_now() {
+        assert_readings("charge-no-power-now", ("full", 80, (73920, 70224, 57638), 95, 15800, 29, 18960));
+    }
+
+    #[test]
+    fn no_power_reading() {
+        assert_readings("no-power-reading", ("na", 100, (50000, 50000, 50000), 100, 12600, 27, 0));
+    }
+
+    #[test]
+    fn dual_battery_totals() {
+        assert_readings("dual-battery", ("discharging", 80, (96000, 88000, 70400), 91, 12000, 30, 6000));
+        assert_eq!(snapshot("dual-battery").cycle_count_cycles, 310);
+    }
+
+    #[test]
+    fn dual_battery_breakdown() {
+        let s = snapshot("dual-battery");
+        let names: Vec<&str> = s.batteries.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["BAT0", "BAT1"]);
+
+        let (bat0, bat1) = (&s.batteries[0], &s.batteries[1]);
+        assert_eq!((bat0.charge_status.as_str(), bat1.charge_status.as_str()), ("discharging", "na"));
+        assert_eq!((bat0.charge_percent, bat1.charge_percent), (50, 90));
+        assert_eq!((bat0.design_capacity_mwh, bat1.design_capacity_mwh), (24000, 72000));
+        assert_eq!((bat0.full_charge_capacity_mwh, bat1.full_charge_capacity_mwh), (22000, 66000));
+        assert_eq!((bat0.current_capacity_mwh, bat1.current_capacity_mwh), (11000, 59400));
+        assert_eq!((bat0.health_percent, bat1.health_percent), (91, 91));
+        assert_eq!((bat0.power_draw_mw, bat1.power_draw_mw), (6000, 0));
+        assert_eq!((bat0.temperature_c, bat1.temperature_c), (-1, 30));
+        assert_eq!((bat0.model.as_str(), bat1.model.as_str()), ("01AV421", "01AV490"));
+        for battery in &s.batteries {
+            assert_eq!(battery.charge_start_threshold_percent, Some(75));
+            assert_eq!(battery.charge_end_threshold_percent, Some(80));
+        }
+    }
+
+    #[test]
+    fn adapters() {
+        let adapter = |name: &str| read_adapter_snapshot(&fixture(name)).unwrap().unwrap();
+        assert!(!adapter("energy-power-now").online);
+        let usb_c = adapter("charge-no-power-now");
+        assert!(usb_c.online);
+        assert_eq!(usb_c.power_mw, 65000);
+        let adp = adapter("no-power-reading");
+        assert!(adp.online);
+        assert_eq!(adp.power_mw, -1);
+    }
+
+    #[test]
+    fn converts_charge_to_energy() {
+        // charge-no-power-now: charge_full_design x voltage_min_design
+        assert_eq!(charge_to_mwh(4_800_000, 15_400_000), Some(73920));
+        assert_eq!(charge_to_mwh(0, 15_400_000), None);
+        assert_eq!(charge_to_mwh(4_800_000, 0), None);
+    }
+
+    #[test]
+    fn scales_temperatures() {
+        assert_eq!(scale_temperature_c(310), 31); // 0.1 °C
+        assert_eq!(scale_temperature_c(29000), 29); // m°C
+        assert_eq!(scale_temperature_c(27), 27); // °C
+    }
+
+    #[test]
+    fn normalizes_status() {
+        assert_eq!(normalize_status(Some("Discharging\n")), "discharging");
+        assert_eq!(normalize_status(Some("Charging")), "charging");
+        assert_eq!(normalize_status(Some("Full")), "full");
+        assert_eq!(normalize_status(Some("Not charging")), "full");
+        assert_eq!(normalize_status(Some("Unknown")), "na");
+        assert_eq!(normalize_status(None), "na");
+    }
+}
@@ -0,0 +1,410 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::utils::processes::proc_root;
+use crate::utils::ryzen_smu::*;
+use crate::utils::sysfs::SysfsRoot;
+use crate::utils::types::{LimitError, LimitField, LimitRange, PowerLimits};
+
+// ─── Families ────────────────────────────────────────────────────────────────
+// Which limits each APU generation accepts, and in what range. A family is
+// matched by the ryzen_smu codename when the driver is loaded and readable,
+// otherwise by CPU family/model from /proc/cpuinfo. Unknown chips get every
+// limit with the generic ranges, so nothing is hidden on a guess.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mailbox {
+    Rsmu,
+    Mp1,
+}
+
+impl Mailbox {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Mailbox::Rsmu => "rsmu",
+            Mailbox::Mp1 => "mp1",
+        }
+    }
+}
+
+struct Family {
+    name: &'static str,
+    codenames: &'static [u32],
+    cpu_models: &'static [(u32, u32)], // (cpu family, model) from cpuinfo
+    mailbox: Mailbox,
+    fast_mw: Option<LimitRange>,
+    slow_mw: Option<LimitRange>,
+    stapm_mw: Option<LimitRange>,
+    temp_c: Option<LimitRange>,
+}
+
+const fn power(min_w: i32, max_w: i32) -> Option<LimitRange> {
+    Some(LimitRange { min: min_w * 1000, max: max_w * 1000 })
+}
+
+const APU_TEMP: Option<LimitRange> = Some(LimitRange { min: 40, max: 100 });
+
+const FAMILIES: &[Family] = &[
+    Family {
+        name: "Raven Ridge / Picasso / Dali",
+        codenames: &[CODENAME_RAVEN_RIDGE, CODENAME_RAVEN_RIDGE_2, CODENAME_PICASSO, CODENAME_DALI],
+        cpu_models: &[(0x17, 0x11), (0x17, 0x18), (0x17, 0x20)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: power(4, 65),
+        slow_mw: power(4, 65),
+        stapm_mw: power(4, 65),
+        temp_c: APU_TEMP,
+    },
+    Family {
+        name: "Renoir / Lucienne",
+        codenames: &[CODENAME_RENOIR, CODENAME_LUCIENNE],
+        cpu_models: &[(0x17, 0x60), (0x17, 0x68)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: power(4, 90),
+        slow_mw: power(4, 90),
+        stapm_mw: power(4, 90),
+        temp_c: APU_TEMP,
+    },
+    // Only the MP1 mailbox exists, and the firmware keeps Tctl to itself
+    Family {
+        name: "Van Gogh",
+        codenames: &[CODENAME_VANGOGH],
+        cpu_models: &[(0x17, 0x90)],
+        mailbox: Mailbox::Mp1,
+        fast_mw: power(3, 30),
+        slow_mw: power(3, 30),
+        stapm_mw: power(3, 30),
+        temp_c: None,
+    },
+    Family {
+        name: "Cezanne",
+        codenames: &[CODENAME_CEZANNE],
+        cpu_models: &[(0x19, 0x50)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: power(4, 90),
+        slow_mw: power(4, 90),
+        stapm_mw: power(4, 90),
+        temp_c: APU_TEMP,
+    },
+    Family {
+        name: "Rembrandt",
+        codenames: &[CODENAME_REMBRANDT],
+        cpu_models: &[(0x19, 0x40), (0x19, 0x44)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: power(4, 120),
+        slow_mw: power(4, 120),
+        stapm_mw: power(4, 120),
+        temp_c: APU_TEMP,
+    },
+    Family {
+        name: "Phoenix / Hawk Point",
+        codenames: &[CODENAME_PHOENIX],
+        cpu_models: &[(0x19, 0x74), (0x19, 0x75), (0x19, 0x78), (0x19, 0x7C)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: power(4, 120),
+        slow_mw: power(4, 120),
+        stapm_mw: power(4, 120),
+        temp_c: APU_TEMP,
+    },
+    // Desktop parts have no STAPM or fast/slow PPT split for RyzenAdj to set
+    Family {
+        name: "Matisse / Vermeer",
+        codenames: &[CODENAME_MATISSE, CODENAME_VERMEER],
+        cpu_models: &[(0x17, 0x71), (0x19, 0x21)],
+        mailbox: Mailbox::Rsmu,
+        fast_mw: None,
+        slow_mw: None,
+        stapm_mw: None,
+        temp_c: None,
+    },
+];
+
+// ─── Capabilities ────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+    pub family: &'static str,
+    pub codename: Option<u32>,
+    pub mailbox: Mailbox,
+    pub fast_mw: Option<LimitRange>,
+    pub slow_mw: Option<LimitRange>,
+    pub stapm_mw: Option<LimitRange>,
+    pub temp_c: Option<LimitRange>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            family: "Unknown",
+            codename: None,
+            mailbox: Mailbox::Rsmu,
+            fast_mw: Some(LimitRange::POWER),
+            slow_mw: Some(LimitRange::POWER),
+            stapm_mw: Some(LimitRange::POWER),
+            temp_c: Some(LimitRange::TEMP),
+        }
+    }
+}
+
+impl Capabilities {
+    fn from_family(family: &Family, codename: Option<u32>) -> Self {
+        Self {
+            family: family.name,
+            codename,
+            mailbox: family.mailbox,
+            fast_mw: family.fast_mw,
+            slow_mw: family.slow_mw,
+            stapm_mw: family.stapm_mw,
+            temp_c: family.temp_c,
+        }
+    }
+
+    // None when the limit can't be set on this chip
+    pub fn range(&self, field: LimitField) -> Option<LimitRange> {
+        match field {
+            LimitField::Fast => self.fast_mw,
+            LimitField::Slow => self.slow_mw,
+            LimitField::Stapm => self.stapm_mw,
+            LimitField::Temp => self.temp_c,
+        }
+    }
+
+    pub fn supports(&self, field: LimitField) -> bool {
+        self.range(field).is_some()
+    }
+
+    // `limits` with the unsupported and out-of-range ones unset, for enforcement
+    pub fn supported_limits(&self, limits: &PowerLimits) -> PowerLimits {
+        let keep = |field: LimitField, value: Option<i32>| {
+            value.filter(|v| self.range(field).is_some_and(|range| range.contains(*v)))
+        };
+        PowerLimits {
+            fast_mw: keep(LimitField::Fast, limits.fast_mw),
+            slow_mw: keep(LimitField::Slow, limits.slow_mw),
+            stapm_mw: keep(LimitField::Stapm, limits.stapm_mw),
+            temp_c: keep(LimitField::Temp, limits.temp_c),
+        }
+    }
+
+    // Set, supported limits that fall outside this family's range
+    pub fn check(&self, limits: &PowerLimits) -> Vec<LimitError> {
+        [
+            (LimitField::Fast, limits.fast_mw),
+            (LimitField::Slow, limits.slow_mw),
+            (LimitField::Stapm, limits.stapm_mw),
+            (LimitField::Temp, limits.temp_c),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| {
+            let (value, range) = (value?, self.range(field)?);
+            (!range.contains(value)).then_some(LimitError::OutsideFamilyRange {
+                field,
+                value,
+                range,
+                family: self.family,
+            })
+        })
+        .collect()
+    }
+}
+
+// ─── Detection ───────────────────────────────────────────────────────────────
+
+pub fn detect_capabilities(sysfs: &SysfsRoot, proc: &Path) -> Capabilities {
+    let smu_dir = sysfs.ryzen_smu_dir();
+    // Reading the codename needs root on most setups
+    let codename = fs::read_to_string(smu_dir.join("codename"))
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok());
+    let cpu_model = read_cpu_model(proc);
+
+    let family = FAMILIES
+        .iter()
+        .find(|family| codename.is_some_and(|c| family.codenames.contains(&c)))
+        .or_else(|| {
+            let model = cpu_model?;
+            FAMILIES.iter().find(|family| family.cpu_models.contains(&model))
+        });
+    let mut capabilities = family
+        .map(|family| Capabilities::from_family(family, codename))
+        .unwrap_or_else(|| Capabilities { codename, ..Default::default() });
+
+    // The driver only creates the mailboxes the chip has
+    if smu_dir.join("rsmu_cmd").exists() {
+        capabilities.mailbox = Mailbox::Rsmu;
+    } else if smu_dir.join("mp1_smu_cmd").exists() {
+        capabilities.mailbox = Mailbox::Mp1;
+    }
+    capabilities
+}
+
+// (cpu family, model) of the first AMD CPU in cpuinfo
+fn read_cpu_model(proc: &Path) -> Option<(u32, u32)> {
+    let cpuinfo = fs::read_to_string(proc.join("cpuinfo")).ok()?;
+    let first = cpuinfo.split("\n\n").next()?;
+    let field = |name: &str| {
+        first.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    if field("vendor_id")? != "AuthenticAMD" {
+        return None;
+    }
+    Some((field("cpu family")?.parse().ok()?, field("model")?.parse().ok()?))
+}
+
+// Detected once per sysfs root; the chip doesn't change while running.
+static CAPABILITIES: Mutex<Option<(SysfsRoot, Capabilities)>> = Mutex::new(None);
+
+pub fn capabilities_for(sysfs: &SysfsRoot) -> Capabilities {
+    let mut cached = CAPABILITIES.lock().unwrap();
+    match cached.as_ref() {
+        Some((root, capabilities)) if root == sysfs => capabilities.clone(),
+        _ => {
+            let capabilities = detect_capabilities(sysfs, &proc_root());
+            *cached = Some((sysfs.clone(), capabilities.clone()));
+            capabilities
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn proc_fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/proc").join(name)
+    }
+
+    // A sysfs root with only ryzen_smu_drv, holding `codename` if given and
+    // the named mailbox files
+    fn smu_sysfs(name: &str, codename: Option<&str>, mailboxes: &[&str]) -> SysfsRoot {
+        let root = std::env::temp_dir().join(format!("ryzone-capabilities-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let sysfs = SysfsRoot::new(&root);
+        fs::create_dir_all(sysfs.ryzen_smu_dir()).unwrap();
+        if let Some(codename) = codename {
+            fs::write(sysfs.ryzen_smu_dir().join("codename"), codename).unwrap();
+        }
+        for mailbox in mailboxes {
+            fs::write(sysfs.ryzen_smu_dir().join(mailbox), "").unwrap();
+        }
+        sysfs
+    }
+
+    fn family(name: &str) -> Capabilities {
+        let family = FAMILIES.iter().find(|family| family.name == name).unwrap();
+        Capabilities::from_family(family, None)
+    }
+
+    #[test]
+    fn cpu_model_from_cpuinfo() {
+        for (fixture, expected) in [
+            ("phoenix", Some((0x19, 0x74))),
+            ("renoir", Some((0x17, 0x60))),
+            ("unknown-amd", Some((0x1A, 0x24))),
+            ("intel", None),
+            ("missing", None),
+        ] {
+            assert_eq!(read_cpu_model(&proc_fixture(fixture)), expected, "fixture {}", fixture);
+        }
+    }
+
+    #[test]
+    fn family_lookup() {
+        // (codename file, cpuinfo fixture, expected family, expected codename)
+        for (name, codename, proc, expected, expected_codename) in [
+            ("phoenix", Some("21"), "intel", "Phoenix / Hawk Point", Some(CODENAME_PHOENIX)),
+            ("codename-wins", Some("13"), "phoenix", "Van Gogh", Some(CODENAME_VANGOGH)),
+            ("unknown-codename", Some("999"), "renoir", "Renoir / Lucienne", Some(999)),
+            ("unreadable", Some("n/a"), "phoenix", "Phoenix / Hawk Point", None),
+            ("no-driver", None, "renoir", "Renoir / Lucienne", None),
+            ("unknown-chip", None, "unknown-amd", "Unknown", None),
+            ("not-amd", None, "intel", "Unknown", None),
+        ] {
+            let sysfs = smu_sysfs(name, codename, &[]);
+            let capabilities = detect_capabilities(&sysfs, &proc_fixture(proc));
+            assert_eq!(capabilities.family, expected, "case {}", name);
+            assert_eq!(capabilities.codename, expected_codename, "case {}", name);
+            let _ = fs::remove_dir_all(sysfs.path());
+        }
+    }
+
+    #[test]
+    fn unknown_chip_gets_generic_ranges() {
+        let sysfs = smu_sysfs("generic", None, &[]);
+        let capabilities = detect_capabilities(&sysfs, &proc_fixture("unknown-amd"));
+        assert_eq!(capabilities, Capabilities::default());
+        let _ = fs::remove_dir_all(sysfs.path());
+    }
+
+    #[test]
+    fn mailbox_follows_driver_files() {
+        for (name, codename, mailboxes, expected) in [
+            ("family-default", "13", &[][..], Mailbox::Mp1),
+            ("rsmu-present", "13", &["rsmu_cmd", "mp1_smu_cmd"][..], Mailbox::Rsmu),
+            ("mp1-only", "21", &["mp1_smu_cmd"][..], Mailbox::Mp1),
+        ] {
+            let sysfs = smu_sysfs(name, Some(codename), mailboxes);
+            assert_eq!(detect_capabilities(&sysfs, &proc_fixture("missing")).mailbox, expected, "case {}", name);
+            let _ = fs::remove_dir_all(sysfs.path());
+        }
+    }
+
+    #[test]
+    fn supported_limits_drops_unsupported_and_out_of_range() {
+        let limits = PowerLimits {
+            fast_mw: Some(25_000),
+            slow_mw: Some(35_000),
+            stapm_mw: Some(2_000),
+            temp_c: Some(95),
+        };
+        for (name, expected) in [
+            // Tctl isn't settable, slow is over 30 W, STAPM under 3 W
+            ("Van Gogh", PowerLimits { fast_mw: Some(25_000), ..Default::default() }),
+            // Everything but the 2 W STAPM
+            ("Phoenix / Hawk Point", PowerLimits { stapm_mw: None, ..limits.clone() }),
+            // Nothing is settable
+            ("Matisse / Vermeer", PowerLimits::default()),
+        ] {
+            assert_eq!(family(name).supported_limits(&limits), expected, "family {}", name);
+        }
+        assert_eq!(family("Van Gogh").supported_limits(&PowerLimits::default()), PowerLimits::default());
+    }
+
+    #[test]
+    fn check_reports_values_outside_family_range() {
+        let limits = PowerLimits {
+            fast_mw: Some(100_000),
+            slow_mw: Some(30_000),
+            stapm_mw: None,
+            temp_c: Some(35),
+        };
+        let outside = |capabilities: &Capabilities| {
+            capabilities
+                .check(&limits)
+                .into_iter()
+                .map(|error| match error {
+                    LimitError::OutsideFamilyRange { field, value, .. } => (field, value),
+                    other => panic!("unexpected {:?}", other),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // 100 W is over Renoir's 90 W, 35 °C under the 40 °C APU floor
+        assert_eq!(
+            outside(&family("Renoir / Lucienne")),
+            vec![(LimitField::Fast, 100_000), (LimitField::Temp, 35)]
+        );
+        // Rembrandt takes 100 W
+        assert_eq!(outside(&family("Rembrandt")), vec![(LimitField::Temp, 35)]);
+        // Limits the chip doesn't have aren't range errors
+        assert_eq!(outside(&family("Van Gogh")), vec![(LimitField::Fast, 100_000)]);
+        assert_eq!(outside(&family("Matisse / Vermeer")), vec![]);
+        // The generic ranges allow both
+        assert_eq!(outside(&Capabilities::default()), vec![]);
+    }
+}
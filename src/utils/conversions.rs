@@ -51,6 +51,28 @@ pub fn temp_unit_label(unit: &str) -> &'static str {
     }
 }
 
+// Milliwatts in the user's power unit, e.g. "15.3 W" or "15300 mW"
+pub fn format_power(value_mw: i32, unit: &str) -> String {
+    match unit {
+        "milliwatt" => format!("{} mW", value_mw),
+        _ => format!("{:.1} W", value_mw as f32 / 1000.0),
+    }
+}
+
+// Celsius in the user's temperature unit, e.g. "62 °C" or "144 °F"
+pub fn format_temp(value_c: i32, unit: &str) -> String {
+    let value = temp_conversion(value_c as f32, "celsius", unit);
+    format!("{:.0} {}", value, temp_unit_label(unit))
+}
+
+pub fn format_frequency_mhz(value_mhz: i32) -> String {
+    if value_mhz >= 1000 {
+        format!("{:.1} GHz", value_mhz as f32 / 1000.0)
+    } else {
+        format!("{} MHz", value_mhz)
+    }
+}
+
 pub fn minutes_to_hm_text(minutes: i32) -> String {
     if minutes <= 0 {
         return "N/A".to_string();
@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::utils::backend::{Limits, PowerBackend};
+use crate::utils::logs::log_warn;
+use crate::utils::settings::{read_profile_settings, settings_dir, write_profile_settings};
+use crate::utils::types::{LimitField, LimitRange, PowerLimits, Profile, ProfileMode, ProfileSettings};
+
+// ─── Firmware defaults ───────────────────────────────────────────────────────
+// The limits the firmware had before Ryzone first wrote to them. Captured once,
+// the first time a backend comes up, and kept in ~/.ryzone so that any
+// experiment can be undone without a reboot.
+
+pub const FIRMWARE_DEFAULT_PROFILE: &str = "firmware default";
+
+pub fn firmware_defaults_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("firmware_defaults.toml"))
+}
+
+pub fn read_firmware_defaults() -> Option<PowerLimits> {
+    let contents = fs::read_to_string(firmware_defaults_path().ok()?).ok()?;
+    toml::from_str(&contents).ok()
+}
+
+// Limits the firmware doesn't report (read as 0) stay unset
+fn limits_from_backend(limits: &Limits) -> PowerLimits {
+    let mw = |watts: f32| Some((watts * 1000.0).round() as i32).filter(|v| *v > 0);
+    PowerLimits {
+        fast_mw: mw(limits.fast_w),
+        slow_mw: mw(limits.slow_w),
+        stapm_mw: mw(limits.stapm_w),
+        temp_c: Some(limits.tctl_c.round() as i32).filter(|v| *v > 0),
+    }
+}
+
+// Called right after the backend is created, before anything is enforced.
+// Does nothing once a snapshot exists, so later runs never overwrite it with
+// limits Ryzone itself has set.
+pub fn capture_firmware_defaults(backend: &dyn PowerBackend) -> Result<(), String> {
+    let path = firmware_defaults_path().map_err(|e| e.to_string())?;
+    if path.exists() {
+        return Ok(());
+    }
+
+    backend.refresh()?;
+    let defaults = limits_from_backend(&backend.read_limits()?);
+    if defaults.is_empty() {
+        return Err("firmware reported no limits".to_string());
+    }
+
+    let contents = toml::to_string_pretty(&defaults).map_err(|e| e.to_string())?;
+    fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    fs::write(
+        &path,
+        format!("# Limits read from the firmware before Ryzone changed them\n{}", contents),
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Offer the snapshot as a profile straight away
+    let mut settings = read_profile_settings().map_err(|e| e.to_string())?;
+    if add_firmware_default_profile(&mut settings, &defaults) {
+        write_profile_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Adds the "firmware default" profile unless it's already there.
+// Returns whether the settings changed.
+pub fn add_firmware_default_profile(settings: &mut ProfileSettings, defaults: &PowerLimits) -> bool {
+    if settings.has_profile(FIRMWARE_DEFAULT_PROFILE) {
+        return false;
+    }
+    let mut profile = Profile::new(
+        FIRMWARE_DEFAULT_PROFILE,
+        "The limits your firmware had before Ryzone changed them",
+        ProfileMode::Fixed,
+    );
+    profile.limits.insert("fixed".to_string(), within_bounds(defaults));
+    settings.profiles.push(profile);
+    true
+}
+
+// "Restore defaults": makes the firmware default profile the active one, so
+// enforcement puts the snapshot back and keeps it there.
+pub fn activate_firmware_defaults(settings: &mut ProfileSettings) -> Result<(), String> {
+    let defaults = read_firmware_defaults()
+        .ok_or("no firmware defaults captured yet; start Ryzone with a working backend first")?;
+    add_firmware_default_profile(settings, &defaults);
+    settings.active_profile = FIRMWARE_DEFAULT_PROFILE.to_string();
+    Ok(())
+}
+
+// Writes the whole snapshot back at once. Used on shutdown and after a crash
+// in the enforcement loop.
+pub fn restore_firmware_defaults(backend: &dyn PowerBackend) -> Result<(), String> {
+    let defaults = within_bounds(&read_firmware_defaults().ok_or("no firmware defaults captured")?);
+    if let Some(mw) = defaults.fast_mw {
+        backend.set_fast_limit(mw as u32)?;
+    }
+    if let Some(mw) = defaults.slow_mw {
+        backend.set_slow_limit(mw as u32)?;
+    }
+    if let Some(mw) = defaults.stapm_mw {
+        backend.set_stapm_limit(mw as u32)?;
+    }
+    if let Some(c) = defaults.temp_c {
+        backend.set_tctl_temp(c as u32)?;
+    }
+    Ok(())
+}
+
+// The firmware may run limits outside what Ryzone (and ryzone-helper) accept,
+// e.g. a 105 °C Tctl. Rather than failing the whole restore, those are
+// clamped to the nearest allowed value and logged.
+fn within_bounds(defaults: &PowerLimits) -> PowerLimits {
+    let clamp = |field: LimitField, value: Option<i32>| {
+        let value = value?;
+        let range = if field == LimitField::Temp { LimitRange::TEMP } else { LimitRange::POWER };
+        let clamped = value.clamp(range.min, range.max);
+        if clamped != value {
+            log_warn(
+                "defaults",
+                format!("firmware {} of {} is outside {}..={}, using {}", field.label(), value, range.min, range.max, clamped),
+            );
+        }
+        Some(clamped)
+    };
+    PowerLimits {
+        fast_mw: clamp(LimitField::Fast, defaults.fast_mw),
+        slow_mw: clamp(LimitField::Slow, defaults.slow_mw),
+        stapm_mw: clamp(LimitField::Stapm, defaults.stapm_mw),
+        temp_c: clamp(LimitField::Temp, defaults.temp_c),
+    }
+}
+
+// ─── Shutdown ────────────────────────────────────────────────────────────────
+// SIGTERM / SIGINT only set a flag; the enforcement loop notices it, restores
+// the defaults with the backend it owns, and exits.
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_shutdown_signal(_signal: libc::c_int) {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub fn install_shutdown_handler() {
+    let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+pub fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,200 @@
+use std::io::{self, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::backend::{Limits, PowerBackend, Telemetry};
+use crate::utils::ipc::{helper_socket_path, read_message, write_json_line, Message};
+use crate::utils::pm_table::PmTable;
+use crate::utils::types::{LimitField, PowerLimits};
+
+// ─── Helper protocol ─────────────────────────────────────────────────────────
+// ryzone-helper runs as root through pkexec and only does the RyzenAdj /
+// RyzenSmu calls. Same framing as the command socket: one versioned JSON
+// message per line.
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum HelperRequest {
+    ReadTelemetry,
+    ApplyLimits { limits: PowerLimits }, // unset limits are left untouched
+    SetChargeThresholds { battery: String, start_percent: Option<i32>, end_percent: i32 },
+    ReadPmTable,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HelperResponse {
+    Ok,
+    Telemetry { telemetry: Telemetry, limits: Limits },
+    PmTable {
+        codename: Option<u32>,
+        #[serde(default)]
+        version: Option<u32>,
+        values: Vec<f32>,
+    },
+    Error { message: String },
+}
+
+// Same bounds as PowerLimits::validate. Writes are partial, so the
+// fast/slow/STAPM ordering isn't checked here.
+pub fn check_limits(limits: &PowerLimits) -> Result<(), String> {
+    let power = [
+        (LimitField::Fast, limits.fast_mw),
+        (LimitField::Slow, limits.slow_mw),
+        (LimitField::Stapm, limits.stapm_mw),
+    ];
+    for (field, mw) in power {
+        if let Some(mw) = mw {
+            PowerLimits::check_power(field, mw).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(temp_c) = limits.temp_c {
+        PowerLimits::check_temp(temp_c).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+pub fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+// ─── Client / PowerBackend ───────────────────────────────────────────────────
+// Used by the unprivileged GUI, ryzoned and ryzonectl. `refresh` fetches
+// telemetry and limits in one round trip; the reads return that snapshot.
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+pub struct HelperBackend {
+    connection: Mutex<Connection>,
+    snapshot: Mutex<(Telemetry, Limits)>,
+}
+
+impl HelperBackend {
+    pub fn connect() -> io::Result<Self> {
+        let writer = UnixStream::connect(helper_socket_path(current_uid())?)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self {
+            connection: Mutex::new(Connection { reader, writer }),
+            snapshot: Mutex::new(Default::default()),
+        })
+    }
+
+    // Connects to a running helper, or starts one through pkexec and waits for
+    // its socket. pkexec shows the polkit authentication dialog if needed.
+    pub fn spawn() -> Result<Self, String> {
+        if let Ok(backend) = Self::connect() {
+            return Ok(backend);
+        }
+        // No point asking for a password if the helper can't bind its socket
+        helper_socket_path(current_uid()).map_err(|e| e.to_string())?;
+
+        let mut child = Command::new("pkexec")
+            .arg(helper_binary_path())
+            .stdin(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("failed to run pkexec: {}", e))?;
+
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(120) {
+            if let Ok(backend) = Self::connect() {
+                // Reap the helper when it exits on its own
+                std::thread::spawn(move || child.wait());
+                return Ok(backend);
+            }
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!("ryzone-helper exited ({}); authorization denied?", status));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        Err("timed out waiting for ryzone-helper".to_string())
+    }
+
+    pub fn request(&self, request: HelperRequest) -> Result<HelperResponse, String> {
+        let mut connection = self.connection.lock().unwrap();
+        write_json_line(&mut connection.writer, &Message::new(request))
+            .map_err(|e| format!("helper: {}", e))?;
+        match read_message(&mut connection.reader).map_err(|e| format!("helper: {}", e))? {
+            HelperResponse::Error { message } => Err(message),
+            response => Ok(response),
+        }
+    }
+
+    pub fn apply_limits(&self, limits: PowerLimits) -> Result<(), String> {
+        self.request(HelperRequest::ApplyLimits { limits }).map(|_| ())
+    }
+
+    pub fn read_pm_table(&self) -> Result<PmTable, String> {
+        match self.request(HelperRequest::ReadPmTable)? {
+            HelperResponse::PmTable { codename, version, values } => {
+                Ok(PmTable { codename, version, values })
+            }
+            _ => Err("unexpected helper response".to_string()),
+        }
+    }
+}
+
+// Installed next to the other binaries; fall back to the usual prefix.
+fn helper_binary_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("ryzone-helper")))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from("/usr/bin/ryzone-helper"))
+}
+
+impl PowerBackend for HelperBackend {
+    fn refresh(&self) -> Result<(), String> {
+        match self.request(HelperRequest::ReadTelemetry)? {
+            HelperResponse::Telemetry { telemetry, limits } => {
+                *self.snapshot.lock().unwrap() = (telemetry, limits);
+                Ok(())
+            }
+            _ => Err("unexpected helper response".to_string()),
+        }
+    }
+
+    fn read_telemetry(&self) -> Result<Telemetry, String> {
+        Ok(self.snapshot.lock().unwrap().0.clone())
+    }
+
+    fn read_limits(&self) -> Result<Limits, String> {
+        Ok(self.snapshot.lock().unwrap().1.clone())
+    }
+
+    fn set_fast_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply_limits(PowerLimits { fast_mw: Some(mw as i32), ..Default::default() })
+    }
+
+    fn set_slow_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply_limits(PowerLimits { slow_mw: Some(mw as i32), ..Default::default() })
+    }
+
+    fn set_stapm_limit(&self, mw: u32) -> Result<(), String> {
+        self.apply_limits(PowerLimits { stapm_mw: Some(mw as i32), ..Default::default() })
+    }
+
+    fn set_tctl_temp(&self, c: u32) -> Result<(), String> {
+        self.apply_limits(PowerLimits { temp_c: Some(c as i32), ..Default::default() })
+    }
+
+    fn set_charge_thresholds(&self, battery: &str, start_percent: Option<i32>, end_percent: i32) -> Result<(), String> {
+        self.request(HelperRequest::SetChargeThresholds {
+            battery: battery.to_string(),
+            start_percent,
+            end_percent,
+        })
+        .map(|_| ())
+    }
+}
@@ -0,0 +1,292 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::types::{CurrentStats, ProfileSettings};
+
+// ─── Protocol ────────────────────────────────────────────────────────────────
+// Line-delimited JSON over a Unix socket. Every message carries the protocol
+// version next to its body, e.g.
+//   -> {"v":1,"cmd":"set_active_profile","profile":"turbo"}
+//   <- {"v":1,"type":"ok"}
+// A `subscribe` request turns the connection into a stream of `stats` messages
+// until the client disconnects.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    Show,
+    GetStats,
+    GetProfile,
+    SetActiveProfile { profile: String },
+    ReloadConfig,
+    RestoreDefaults,
+    Subscribe,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    Stats { stats: Box<CurrentStats> }, // boxed, it dwarfs the other variants
+    Profile { profile: ProfileSettings },
+    Error { message: String },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Message<T> {
+    pub v: u32,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> Message<T> {
+    pub fn new(body: T) -> Self {
+        Self {
+            v: PROTOCOL_VERSION,
+            body,
+        }
+    }
+}
+
+impl Response {
+    pub fn error(message: impl Into<String>) -> Self {
+        Response::Error {
+            message: message.into(),
+        }
+    }
+}
+
+// ─── Socket paths ────────────────────────────────────────────────────────────
+
+fn runtime_dir() -> PathBuf {
+    // XDG_RUNTIME_DIR is per-user, tmpfs, and cleaned on logout
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+        .unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(runtime_dir)
+}
+
+// Single-instance socket owned by the GUI.
+pub fn app_socket_path() -> PathBuf {
+    runtime_dir().join("ryzone.sock")
+}
+
+// Socket owned by ryzoned. Serves the same protocol as the GUI socket.
+pub fn daemon_socket_path() -> PathBuf {
+    runtime_dir().join("ryzoned.sock")
+}
+
+// Socket of the privileged helper serving user `uid`. pkexec clears the
+// environment, so both sides derive it from the uid rather than XDG_RUNTIME_DIR.
+// Only a private /run/user/<uid> is accepted: anywhere world-writable another
+// user could bind the path first and pose as the helper.
+pub fn helper_socket_path(uid: u32) -> io::Result<PathBuf> {
+    let user_runtime_dir = PathBuf::from(format!("/run/user/{}", uid));
+    let meta = std::fs::metadata(&user_runtime_dir).map_err(|e| {
+        io::Error::new(e.kind(), format!("{}: {} (no login session?)", user_runtime_dir.display(), e))
+    })?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a private directory owned by uid {}", user_runtime_dir.display(), uid),
+        ));
+    }
+    Ok(user_runtime_dir.join("ryzone-helper.sock"))
+}
+
+// ─── Listener setup ──────────────────────────────────────────────────────────
+// Binds a socket, replacing a stale file left behind by a crashed process.
+// When started through `sudo -E` the socket is handed back to the invoking
+// user so their unprivileged tools can connect to it.
+pub fn bind_socket(path: &Path) -> io::Result<UnixListener> {
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)?;
+
+    let sudo_uid = std::env::var("SUDO_UID").ok().and_then(|v| v.parse::<u32>().ok());
+    let sudo_gid = std::env::var("SUDO_GID").ok().and_then(|v| v.parse::<u32>().ok());
+    if sudo_uid.is_some() {
+        std::os::unix::fs::chown(path, sudo_uid, sudo_gid)?;
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    Ok(listener)
+}
+
+// ─── Framing ─────────────────────────────────────────────────────────────────
+// Messages are JSON, one per line.
+pub fn write_json_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+// Reads one message and checks its protocol version.
+pub fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed",
+        ));
+    }
+    let message: Message<T> = serde_json::from_str(&line)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if message.v != PROTOCOL_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported protocol version {}", message.v),
+        ));
+    }
+    Ok(message.body)
+}
+
+// ─── Server ──────────────────────────────────────────────────────────────────
+// Implemented by whichever process owns the socket (the GUI or ryzoned).
+pub trait CommandHandler: Send + Sync + 'static {
+    fn show(&self) -> Result<(), String>;
+    fn current_stats(&self) -> Option<CurrentStats>;
+    fn profile_settings(&self) -> ProfileSettings;
+    fn set_active_profile(&self, profile: &str) -> Result<(), String>;
+    fn reload_config(&self) -> Result<(), String>;
+    fn restore_defaults(&self) -> Result<(), String>;
+    fn subscribe(&self) -> Receiver<CurrentStats>;
+}
+
+// Fans every new sample out to the connections that sent `subscribe`.
+#[derive(Clone, Default)]
+pub struct StatsBroadcaster {
+    subscribers: Arc<Mutex<Vec<Sender<CurrentStats>>>>,
+}
+
+impl StatsBroadcaster {
+    pub fn subscribe(&self) -> Receiver<CurrentStats> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    // Disconnected subscribers are dropped on the next publish.
+    pub fn publish(&self, stats: &CurrentStats) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(stats.clone()).is_ok());
+    }
+}
+
+// Blocks on the listener, serving each connection on its own thread.
+pub fn serve<H: CommandHandler>(listener: UnixListener, handler: Arc<H>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let handler = handler.clone();
+        std::thread::spawn(move || {
+            let _ = serve_connection(stream, handler.as_ref());
+        });
+    }
+}
+
+fn serve_connection<H: CommandHandler>(stream: UnixStream, handler: &H) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = match serde_json::from_str::<Message<Request>>(&line) {
+            Ok(message) if message.v == PROTOCOL_VERSION => message.body,
+            Ok(message) => {
+                let response = Response::error(format!(
+                    "unsupported protocol version {} (expected {})",
+                    message.v, PROTOCOL_VERSION
+                ));
+                write_json_line(&mut writer, &Message::new(response))?;
+                continue;
+            }
+            Err(e) => {
+                let response = Response::error(format!("invalid request: {}", e));
+                write_json_line(&mut writer, &Message::new(response))?;
+                continue;
+            }
+        };
+
+        if let Request::Subscribe = request {
+            // Streams until the client goes away, then the connection closes
+            for stats in handler.subscribe() {
+                write_json_line(&mut writer, &Message::new(Response::Stats { stats: Box::new(stats) }))?;
+            }
+            return Ok(());
+        }
+
+        let response = handle_request(handler, request);
+        write_json_line(&mut writer, &Message::new(response))?;
+    }
+
+    Ok(())
+}
+
+fn handle_request<H: CommandHandler>(handler: &H, request: Request) -> Response {
+    let result = match request {
+        Request::Show => handler.show(),
+        Request::GetStats => {
+            return match handler.current_stats() {
+                Some(stats) => Response::Stats { stats: Box::new(stats) },
+                None => Response::error("no stats sampled yet"),
+            };
+        }
+        Request::GetProfile => {
+            return Response::Profile {
+                profile: handler.profile_settings(),
+            };
+        }
+        Request::SetActiveProfile { profile } => handler.set_active_profile(&profile),
+        Request::ReloadConfig => handler.reload_config(),
+        Request::RestoreDefaults => handler.restore_defaults(),
+        Request::Subscribe => unreachable!("handled by serve_connection"),
+    };
+
+    match result {
+        Ok(()) => Response::Ok,
+        Err(message) => Response::error(message),
+    }
+}
+
+// ─── Client ──────────────────────────────────────────────────────────────────
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub fn connect(path: &Path) -> io::Result<Self> {
+        let writer = UnixStream::connect(path)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer })
+    }
+
+    pub fn send(&mut self, request: Request) -> io::Result<()> {
+        write_json_line(&mut self.writer, &Message::new(request))
+    }
+
+    // Sends a request and waits for its response.
+    pub fn request(&mut self, request: Request) -> io::Result<Response> {
+        self.send(request)?;
+        self.read_response()
+    }
+
+    pub fn read_response(&mut self) -> io::Result<Response> {
+        read_message(&mut self.reader)
+    }
+}
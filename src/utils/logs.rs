@@ -1,14 +1,471 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::utils::metrics::unix_time_ms;
+use crate::utils::settings::{app_settings_modified, read_app_settings, settings_dir};
+use crate::utils::types::{App, CurrentStats, LogFormat};
+
 /* Statistics Logging */
-// Capture stats at the preset update interval and then write to a txt or csv file at the preset logging interval
+// While enable_logging is on, every logging_frequency_ms one row of the full
+// CurrentStats goes to ~/.ryzone/logs/stats.csv (or stats.jsonl). The first
+// column is the local time in RFC 3339. Nested fields are flattened the same
+// way ryzonectl prints them, e.g. "batteries.0.charge_percent".
+// Once a file reaches MAX_LOG_BYTES it is renamed to stats.1.csv, stats.1.csv
+// to stats.2.csv and so on, keeping ROTATED_LOGS old files. A CSV file keeps
+// its columns: rows missing some (e.g. a battery was removed) leave them
+// empty, and new ones are added to the end of the header without rotating.
+
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+const ROTATED_LOGS: u32 = 4;
+
+pub fn logs_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("logs"))
+}
+
+pub struct StatsLogger {
+    dir: PathBuf,
+    enabled: bool,
+    interval_ms: i64,
+    format: LogFormat,
+    settings_modified: Option<SystemTime>,
+    last_row_ms: Option<i64>,
+}
+
+impl StatsLogger {
+    pub fn new(dir: PathBuf, settings: &App) -> Self {
+        let mut logger = Self {
+            dir,
+            enabled: false,
+            interval_ms: 0,
+            format: LogFormat::default(),
+            settings_modified: app_settings_modified(),
+            last_row_ms: None,
+        };
+        logger.apply_settings(settings);
+        logger
+    }
+
+    // ~/.ryzone/logs, set up from app_settings.toml
+    pub fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
+        let settings = read_app_settings()?;
+        Ok(Self::new(logs_dir()?, &settings.app))
+    }
+
+    pub fn apply_settings(&mut self, settings: &App) {
+        self.enabled = settings.enable_logging;
+        self.interval_ms = settings.logging_frequency_ms.max(1) as i64;
+        self.format = settings.log_format;
+    }
+
+    // The Settings tab saves straight to app_settings.toml; re-read it when it changes
+    pub fn reload_settings(&mut self) {
+        let modified = app_settings_modified();
+        if modified != self.settings_modified {
+            if let Ok(settings) = read_app_settings() {
+                self.apply_settings(&settings.app);
+            }
+            self.settings_modified = modified;
+        }
+    }
+
+    // Writes a row if logging is on and the interval has passed
+    pub fn log(&mut self, stats: &CurrentStats, timestamp_ms: i64) -> io::Result<()> {
+        if !self.enabled
+            || self
+                .last_row_ms
+                .is_some_and(|last| timestamp_ms - last < self.interval_ms)
+        {
+            return Ok(());
+        }
+        self.last_row_ms = Some(timestamp_ms);
+
+        let value = serde_json::to_value(stats).map_err(io::Error::other)?;
+        let mut fields = vec![(
+            "timestamp".to_string(),
+            serde_json::Value::String(format_local_timestamp(timestamp_ms)),
+        )];
+        if let Some(object) = value.as_object() {
+            for (key, value) in object {
+                flatten_fields(key, value, &mut fields);
+            }
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        match self.format {
+            LogFormat::Csv => self.write_csv_row(&fields),
+            LogFormat::Jsonl => self.write_jsonl_row(fields),
+        }
+    }
+
+    fn write_csv_row(&self, fields: &[(String, serde_json::Value)]) -> io::Result<()> {
+        let path = self.dir.join("stats.csv");
+        if file_full(&path) {
+            rotate(&path)?;
+        }
+
+        let mut contents = String::new();
+        let mut columns = read_csv_header(&path);
+        let added: Vec<String> = fields
+            .iter()
+            .map(|(key, _)| key.clone())
+            .filter(|key| !columns.contains(key))
+            .collect();
+        if columns.is_empty() {
+            columns = added;
+            contents.push_str(&csv_line(columns.iter().cloned()));
+            contents.push('\n');
+        } else if !added.is_empty() {
+            // Earlier rows just end before the new columns
+            columns.extend(added);
+            replace_csv_header(&path, &csv_line(columns.iter().cloned()))?;
+        }
+
+        let values: HashMap<&str, &serde_json::Value> =
+            fields.iter().map(|(key, value)| (key.as_str(), value)).collect();
+        contents.push_str(&csv_line(
+            columns
+                .iter()
+                .map(|column| values.get(column.as_str()).map_or_else(String::new, |value| csv_value(value))),
+        ));
+        contents.push('\n');
+        append(&path, &contents)
+    }
+
+    fn write_jsonl_row(&self, fields: Vec<(String, serde_json::Value)>) -> io::Result<()> {
+        let path = self.dir.join("stats.jsonl");
+        if file_full(&path) {
+            rotate(&path)?;
+        }
+        let row: serde_json::Map<String, serde_json::Value> = fields.into_iter().collect();
+        let mut line = serde_json::to_string(&row).map_err(io::Error::other)?;
+        line.push('\n');
+        append(&path, &line)
+    }
+}
+
+// Nested lists and objects (e.g. the per-battery breakdown) become
+// "batteries.0.name" keys
+pub fn flatten_fields(key: &str, value: &serde_json::Value, fields: &mut Vec<(String, serde_json::Value)>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten_fields(&format!("{}.{}", key, i), item, fields);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            for (field, item) in object {
+                flatten_fields(&format!("{}.{}", key, field), item, fields);
+            }
+        }
+        other => fields.push((key.to_string(), other.clone())),
+    }
+}
+
+fn csv_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_line(cells: impl Iterator<Item = String>) -> String {
+    cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn read_csv_header(path: &Path) -> Vec<String> {
+    File::open(path)
+        .ok()
+        .and_then(|file| BufReader::new(file).lines().next())
+        .and_then(Result::ok)
+        .filter(|header| !header.is_empty())
+        .map(|header| parse_csv_line(&header))
+        .unwrap_or_default()
+}
+
+fn replace_csv_header(path: &Path, header: &str) -> io::Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let rows = contents.split_once('\n').map_or("", |(_, rows)| rows);
+    let tmp = path.with_extension("csv.tmp");
+    fs::write(&tmp, format!("{}\n{}", header, rows))?;
+    fs::rename(&tmp, path)
+}
+
+fn file_full(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.len() >= MAX_LOG_BYTES)
+}
+
+fn append(path: &Path, contents: &str) -> io::Result<()> {
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(contents.as_bytes())
+}
+
+// stats.csv -> stats.1.csv -> ... -> stats.<ROTATED_LOGS>.csv, dropping the oldest
+fn rotate(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let rotated = |n: u32| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = path.extension().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}.{}.{}", stem, n, ext))
+    };
+    for n in (1..ROTATED_LOGS).rev() {
+        let from = rotated(n);
+        if from.exists() {
+            fs::rename(&from, rotated(n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+// e.g. 2025-03-14T09:26:53.589+01:00
+pub fn format_local_timestamp(timestamp_ms: i64) -> String {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let t = timestamp_ms.div_euclid(1000) as libc::time_t;
+    unsafe {
+        libc::localtime_r(&t, &mut tm);
+    }
+    let offset_min = tm.tm_gmtoff / 60;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}{}{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        timestamp_ms.rem_euclid(1000),
+        if offset_min < 0 { '-' } else { '+' },
+        offset_min.abs() / 60,
+        offset_min.abs() % 60,
+    )
+}
 
 
 /* Status and Error Logging */
-// Capture any errors, messages, etc from the app into a log file in the .ryzone folder.
-// File should be either logs.txt or logs.csv
-// Each entry should be timestamped and appended to the bottom of the file and we should store a maximum of 1000 entries.
-// If the file is too large, we should delete the oldest entries.
-// We should also log the following information:
-// - Timestamp
-// - Error message
-// - Message type (error, warning, info, debug)
-// - Additional context (if available)
\ No newline at end of file
+// Errors, warnings and notable events from the app, ryzoned and the shared
+// stats code go to ~/.ryzone/logs.csv as timestamp,level,context,message rows,
+// and are echoed to stderr as "[context] message". The file keeps the newest
+// MAX_LOG_ENTRIES rows. A message repeated for the same context within
+// REPEAT_WINDOW_MS (e.g. a failing read on every tick) is only written once.
+
+const MAX_LOG_ENTRIES: usize = 1000;
+const REPEAT_WINDOW_MS: i64 = 60 * 1000;
+const EVENT_LOG_HEADER: &str = "timestamp,level,context,message";
+
+static LAST_EVENTS: Mutex<Vec<(String, String, i64)>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+
+    pub fn from_name(level: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.as_str() == level)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: String, // RFC 3339, local time
+    pub level: LogLevel,
+    pub context: String, // where it came from, e.g. "ryzenadj"
+    pub message: String,
+}
+
+pub fn event_log_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("logs.csv"))
+}
+
+pub fn log_error(context: &str, message: impl Display) {
+    log_event(LogLevel::Error, context, message);
+}
+
+pub fn log_warn(context: &str, message: impl Display) {
+    log_event(LogLevel::Warn, context, message);
+}
+
+pub fn log_info(context: &str, message: impl Display) {
+    log_event(LogLevel::Info, context, message);
+}
+
+pub fn log_debug(context: &str, message: impl Display) {
+    log_event(LogLevel::Debug, context, message);
+}
+
+pub fn log_event(level: LogLevel, context: &str, message: impl Display) {
+    // One row per event
+    let message = message.to_string().replace(['\n', '\r'], " ");
+    let now = unix_time_ms();
+    {
+        let mut last = LAST_EVENTS.lock().unwrap();
+        match last.iter_mut().find(|(c, _, _)| c == context) {
+            Some((_, m, at)) if *m == message && now - *at < REPEAT_WINDOW_MS => return,
+            Some(entry) => *entry = (context.to_string(), message.clone(), now),
+            None => last.push((context.to_string(), message.clone(), now)),
+        }
+    }
+
+    eprintln!("[{}] {}", context, message);
+    let entry = LogEntry {
+        timestamp: format_local_timestamp(now),
+        level,
+        context: context.to_string(),
+        message,
+    };
+    // Nowhere left to report a failure to but stderr
+    let result = event_log_path().and_then(|path| Ok(append_event(&path, &entry)?));
+    if let Err(e) = result {
+        eprintln!("[logs] Failed to write event log: {}", e);
+    }
+}
+
+fn append_event(path: &Path, entry: &LogEntry) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let row = csv_line(
+        [
+            entry.timestamp.clone(),
+            entry.level.as_str().to_string(),
+            entry.context.clone(),
+            entry.message.clone(),
+        ]
+        .into_iter(),
+    );
+
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let mut rows: Vec<&str> = contents.lines().skip(1).collect();
+    if rows.len() < MAX_LOG_ENTRIES {
+        let header = if contents.is_empty() { format!("{}\n", EVENT_LOG_HEADER) } else { String::new() };
+        return append(path, &format!("{}{}\n", header, row));
+    }
+
+    // Full: drop the oldest rows
+    rows.push(&row);
+    let keep = &rows[rows.len() - MAX_LOG_ENTRIES..];
+    let tmp = path.with_extension("csv.tmp");
+    fs::write(&tmp, format!("{}\n{}\n", EVENT_LOG_HEADER, keep.join("\n")))?;
+    fs::rename(&tmp, path)
+}
+
+// Oldest first. Rows that don't parse are skipped.
+pub fn read_event_log() -> Result<Vec<LogEntry>, Box<dyn std::error::Error>> {
+    let contents = match fs::read_to_string(event_log_path()?) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cells = parse_csv_line(line);
+            let [timestamp, level, context, message] = <[String; 4]>::try_from(cells).ok()?;
+            Some(LogEntry {
+                timestamp,
+                level: LogLevel::from_name(&level)?,
+                context,
+                message,
+            })
+        })
+        .collect())
+}
+
+// Inverse of csv_line, for a single line
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger(name: &str) -> StatsLogger {
+        let dir = std::env::temp_dir().join(format!("ryzone-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let settings = App {
+            start_on_login: false,
+            minimize_to_tray: false,
+            enable_logging: true,
+            update_frequency_ms: 1000,
+            logging_frequency_ms: 1,
+            log_format: LogFormat::Csv,
+        };
+        StatsLogger::new(dir, &settings)
+    }
+
+    fn stats_with_cores(clocks: &[i32]) -> CurrentStats {
+        CurrentStats { core_clocks_mhz: clocks.to_vec(), ..Default::default() }
+    }
+
+    #[test]
+    fn csv_keeps_columns_when_fields_change() {
+        let mut logger = logger("csv-columns");
+        logger.log(&stats_with_cores(&[3000, 2800]), 1_000).unwrap();
+        logger.log(&stats_with_cores(&[3000]), 2_000).unwrap();
+        logger.log(&stats_with_cores(&[3000, 2800, 2600]), 3_000).unwrap();
+
+        // Never rotated, one header for every row
+        assert!(!logger.dir.join("stats.1.csv").exists());
+        let contents = fs::read_to_string(logger.dir.join("stats.csv")).unwrap();
+        let lines: Vec<Vec<String>> = contents.lines().map(parse_csv_line).collect();
+        assert_eq!(lines.len(), 4);
+        let header = &lines[0];
+        let column = |name: &str| header.iter().position(|c| c == name).unwrap();
+        assert_eq!(column("core_clocks_mhz.2"), header.len() - 1);
+
+        let second = column("core_clocks_mhz.1");
+        assert_eq!(lines[1][second], "2800");
+        assert_eq!(lines[2][second], "");
+        assert_eq!(lines[3][second], "2800");
+        assert_eq!(lines[3][column("core_clocks_mhz.2")], "2600");
+
+        fs::remove_dir_all(&logger.dir).unwrap();
+    }
+}
@@ -0,0 +1,662 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::settings::settings_dir;
+use crate::utils::types::{AppSettings, CurrentStats, MetricsRetention};
+
+// ─── Metrics history ─────────────────────────────────────────────────────────
+// The stats thread records every CurrentStats sample here. The buffer is
+// bounded: once full, the oldest sample is dropped for each new one. Charts
+// read from it through context.
+
+pub const HISTORY_DURATION_MS: i64 = 60 * 60 * 1000; // one hour
+
+// The subset of CurrentStats worth charting, with the time it was taken
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MetricSample {
+    pub timestamp_ms: i64, // unix epoch milliseconds
+    pub charge_percent: i32, // percentage
+    pub runtime_min: i32, // minutes, to empty or to full
+    pub power_draw_mw: i32, // milliwatts
+    pub cpu_temperature_c: i32, // celsius
+    pub cpu_load_percent: i32, // percentage
+    pub gpu_temperature_c: i32, // celsius
+    pub gpu_load_percent: i32, // percentage
+    pub fast_value_mw: i32, // milliwatts
+    pub slow_value_mw: i32, // milliwatts
+    pub stapm_value_mw: i32, // milliwatts
+    pub tctl_value_c: i32, // celsius
+}
+
+impl MetricSample {
+    pub fn from_stats(stats: &CurrentStats, timestamp_ms: i64) -> Self {
+        Self {
+            timestamp_ms,
+            charge_percent: stats.batt_charge_percent,
+            runtime_min: stats.current_load_min,
+            power_draw_mw: stats.power_draw_mw,
+            cpu_temperature_c: stats.cpu_temperature_c,
+            cpu_load_percent: stats.cpu_load_percent,
+            gpu_temperature_c: stats.gpu_temperature_c,
+            gpu_load_percent: stats.gpu_load_percent,
+            fast_value_mw: stats.curr_fast_value_mw,
+            slow_value_mw: stats.curr_slow_value_mw,
+            stapm_value_mw: stats.curr_stapm_value_mw,
+            tctl_value_c: stats.curr_tctl_value_c,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    ChargePercent,
+    Runtime,
+    PowerDraw,
+    CpuTemperature,
+    CpuLoad,
+    GpuTemperature,
+    GpuLoad,
+    FastValue,
+    SlowValue,
+    StapmValue,
+    TctlValue,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 11] = [
+        Metric::ChargePercent,
+        Metric::Runtime,
+        Metric::PowerDraw,
+        Metric::CpuTemperature,
+        Metric::CpuLoad,
+        Metric::GpuTemperature,
+        Metric::GpuLoad,
+        Metric::FastValue,
+        Metric::SlowValue,
+        Metric::StapmValue,
+        Metric::TctlValue,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::ChargePercent => "Battery Charge",
+            Metric::Runtime => "Runtime",
+            Metric::PowerDraw => "Power Draw",
+            Metric::CpuTemperature => "CPU Temperature",
+            Metric::CpuLoad => "CPU Load",
+            Metric::GpuTemperature => "GPU Temperature",
+            Metric::GpuLoad => "GPU Load",
+            Metric::FastValue => "Fast Limit Value",
+            Metric::SlowValue => "Slow Limit Value",
+            Metric::StapmValue => "STAPM Limit Value",
+            Metric::TctlValue => "TCTL Value",
+        }
+    }
+
+    // Unit of `value`
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::ChargePercent | Metric::CpuLoad | Metric::GpuLoad => "%",
+            Metric::Runtime => "min",
+            Metric::PowerDraw | Metric::FastValue | Metric::SlowValue | Metric::StapmValue => "mW",
+            Metric::CpuTemperature | Metric::GpuTemperature | Metric::TctlValue => "°C",
+        }
+    }
+
+    pub fn value(self, sample: &MetricSample) -> i32 {
+        match self {
+            Metric::ChargePercent => sample.charge_percent,
+            Metric::Runtime => sample.runtime_min,
+            Metric::PowerDraw => sample.power_draw_mw,
+            Metric::CpuTemperature => sample.cpu_temperature_c,
+            Metric::CpuLoad => sample.cpu_load_percent,
+            Metric::GpuTemperature => sample.gpu_temperature_c,
+            Metric::GpuLoad => sample.gpu_load_percent,
+            Metric::FastValue => sample.fast_value_mw,
+            Metric::SlowValue => sample.slow_value_mw,
+            Metric::StapmValue => sample.stapm_value_mw,
+            Metric::TctlValue => sample.tctl_value_c,
+        }
+    }
+
+    fn set(self, sample: &mut MetricSample, value: i32) {
+        let field = match self {
+            Metric::ChargePercent => &mut sample.charge_percent,
+            Metric::Runtime => &mut sample.runtime_min,
+            Metric::PowerDraw => &mut sample.power_draw_mw,
+            Metric::CpuTemperature => &mut sample.cpu_temperature_c,
+            Metric::CpuLoad => &mut sample.cpu_load_percent,
+            Metric::GpuTemperature => &mut sample.gpu_temperature_c,
+            Metric::GpuLoad => &mut sample.gpu_load_percent,
+            Metric::FastValue => &mut sample.fast_value_mw,
+            Metric::SlowValue => &mut sample.slow_value_mw,
+            Metric::StapmValue => &mut sample.stapm_value_mw,
+            Metric::TctlValue => &mut sample.tctl_value_c,
+        };
+        *field = value;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MetricsHistory {
+    samples: VecDeque<MetricSample>,
+    capacity: usize,
+}
+
+impl MetricsHistory {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    // Enough room for HISTORY_DURATION_MS at the given sampling interval
+    pub fn for_interval(interval_ms: u64) -> Self {
+        Self::new((HISTORY_DURATION_MS as u64 / interval_ms.max(1)) as usize)
+    }
+
+    pub fn push(&mut self, sample: MetricSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    // Records a sample taken now
+    pub fn record(&mut self, stats: &CurrentStats) {
+        self.push(MetricSample::from_stats(stats, unix_time_ms()));
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    // Oldest first
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &MetricSample> {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<&MetricSample> {
+        self.samples.back()
+    }
+
+    // (timestamp_ms, value) pairs of one metric, oldest first, limited to
+    // samples taken at or after `since_ms`
+    pub fn series(&self, metric: Metric, since_ms: i64) -> Vec<(i64, i32)> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.timestamp_ms >= since_ms)
+            .map(|sample| (sample.timestamp_ms, metric.value(sample)))
+            .collect()
+    }
+}
+
+impl Default for MetricsHistory {
+    fn default() -> Self {
+        Self::for_interval(1000)
+    }
+}
+
+pub fn unix_time_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+// ─── Persistent store ────────────────────────────────────────────────────────
+// ~/.ryzone/metrics keeps history across restarts as three append-only JSON
+// Lines files:
+//   raw.jsonl     one MetricSample per logging_frequency_ms
+//   minute.jsonl  one Aggregate per minute
+//   hour.jsonl    one Aggregate per hour
+// Aggregates are built from every sample recorded, not just the logged ones.
+// Entries older than the retention for their file are pruned on open and then
+// once an hour. Whoever samples the hardware records: ryzoned when it runs,
+// otherwise the GUI.
+
+pub const MINUTE_MS: i64 = 60 * 1000;
+pub const HOUR_MS: i64 = 60 * MINUTE_MS;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Minute,
+    Hour,
+}
+
+impl Resolution {
+    pub fn bucket_ms(self) -> i64 {
+        match self {
+            Resolution::Minute => MINUTE_MS,
+            Resolution::Hour => HOUR_MS,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Resolution::Minute => "minute.jsonl",
+            Resolution::Hour => "hour.jsonl",
+        }
+    }
+}
+
+const RAW_FILE: &str = "raw.jsonl";
+
+// min / avg / max of every metric over one bucket. The timestamps inside the
+// three samples are set to `start_ms`.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Aggregate {
+    pub start_ms: i64, // unix epoch milliseconds, start of the bucket
+    pub count: u32, // samples in the bucket
+    pub min: MetricSample,
+    pub avg: MetricSample,
+    pub max: MetricSample,
+}
+
+impl Aggregate {
+    fn from_sample(sample: &MetricSample) -> Self {
+        Self {
+            start_ms: sample.timestamp_ms,
+            count: 1,
+            min: *sample,
+            avg: *sample,
+            max: *sample,
+        }
+    }
+}
+
+// Running min / sum / max for the bucket being filled
+#[derive(Clone, Debug)]
+struct Accumulator {
+    start_ms: i64,
+    count: u32,
+    min: MetricSample,
+    max: MetricSample,
+    sums: [i64; Metric::ALL.len()],
+}
+
+impl Accumulator {
+    fn new(start_ms: i64, first: &Aggregate) -> Self {
+        let mut acc = Self {
+            start_ms,
+            count: 0,
+            min: first.min,
+            max: first.max,
+            sums: [0; Metric::ALL.len()],
+        };
+        acc.add(first);
+        acc
+    }
+
+    fn add(&mut self, agg: &Aggregate) {
+        for (i, metric) in Metric::ALL.into_iter().enumerate() {
+            let min = metric.value(&self.min).min(metric.value(&agg.min));
+            let max = metric.value(&self.max).max(metric.value(&agg.max));
+            metric.set(&mut self.min, min);
+            metric.set(&mut self.max, max);
+            self.sums[i] += metric.value(&agg.avg) as i64 * agg.count as i64;
+        }
+        self.count += agg.count;
+    }
+
+    fn finish(&self) -> Aggregate {
+        let mut avg = MetricSample::default();
+        for (i, metric) in Metric::ALL.into_iter().enumerate() {
+            metric.set(&mut avg, (self.sums[i] / self.count.max(1) as i64) as i32);
+        }
+        let (mut min, mut max) = (self.min, self.max);
+        avg.timestamp_ms = self.start_ms;
+        min.timestamp_ms = self.start_ms;
+        max.timestamp_ms = self.start_ms;
+        Aggregate { start_ms: self.start_ms, count: self.count, min, avg, max }
+    }
+}
+
+fn bucket_start(timestamp_ms: i64, bucket_ms: i64) -> i64 {
+    timestamp_ms - timestamp_ms.rem_euclid(bucket_ms)
+}
+
+pub fn metrics_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("metrics"))
+}
+
+pub struct MetricsStore {
+    dir: PathBuf,
+    retention: MetricsRetention,
+    logging_interval_ms: i64,
+    last_raw_ms: Option<i64>,
+    minute: Option<Accumulator>,
+    hour: Option<Accumulator>,
+    last_prune_ms: i64,
+}
+
+impl MetricsStore {
+    // Picks up the buckets a previous run left unfinished, so a restart in the
+    // middle of an hour doesn't split it
+    pub fn open(dir: PathBuf, retention: MetricsRetention, logging_interval_ms: i64) -> io::Result<Self> {
+        Self::open_at(dir, retention, logging_interval_ms, unix_time_ms())
+    }
+
+    fn open_at(dir: PathBuf, retention: MetricsRetention, logging_interval_ms: i64, now: i64) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut store = Self {
+            dir,
+            retention,
+            logging_interval_ms: logging_interval_ms.max(1),
+            last_raw_ms: None,
+            minute: None,
+            hour: None,
+            last_prune_ms: now,
+        };
+        store.prune(now)?;
+
+        let minute_start = bucket_start(now, MINUTE_MS);
+        let hour_start = bucket_start(now, HOUR_MS);
+        for sample in read_samples(&store.dir, minute_start)? {
+            store.last_raw_ms = Some(sample.timestamp_ms);
+            add_to(&mut store.minute, minute_start, &Aggregate::from_sample(&sample));
+        }
+        for agg in read_aggregates(&store.dir, Resolution::Minute, hour_start)? {
+            add_to(&mut store.hour, hour_start, &agg);
+        }
+        Ok(store)
+    }
+
+    // ~/.ryzone/metrics, with the retention and interval from app_settings.toml
+    pub fn open_default(settings: &AppSettings) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::open(
+            metrics_dir()?,
+            settings.metrics.clone(),
+            settings.app.logging_frequency_ms as i64,
+        )?)
+    }
+
+    pub fn record(&mut self, sample: MetricSample) -> io::Result<()> {
+        let now = sample.timestamp_ms;
+
+        if self.retention.raw_retention_hours > 0
+            && self.last_raw_ms.is_none_or(|last| now - last >= self.logging_interval_ms)
+        {
+            append_line(&self.dir.join(RAW_FILE), &sample)?;
+            self.last_raw_ms = Some(now);
+        }
+
+        let minute_start = bucket_start(now, MINUTE_MS);
+        if let Some(done) = take_finished(&mut self.minute, minute_start) {
+            self.finish_minute(done)?;
+        }
+        add_to(&mut self.minute, minute_start, &Aggregate::from_sample(&sample));
+
+        if now - self.last_prune_ms >= HOUR_MS {
+            self.prune(now)?;
+            self.last_prune_ms = now;
+        }
+        Ok(())
+    }
+
+    fn finish_minute(&mut self, minute: Aggregate) -> io::Result<()> {
+        if self.retention.minute_retention_days > 0 {
+            append_line(&self.dir.join(Resolution::Minute.file_name()), &minute)?;
+        }
+
+        let hour_start = bucket_start(minute.start_ms, HOUR_MS);
+        if let Some(done) = take_finished(&mut self.hour, hour_start) {
+            if self.retention.hour_retention_days > 0 {
+                append_line(&self.dir.join(Resolution::Hour.file_name()), &done)?;
+            }
+        }
+        add_to(&mut self.hour, hour_start, &minute);
+        Ok(())
+    }
+
+    fn prune(&self, now: i64) -> io::Result<()> {
+        let raw_cutoff = now - self.retention.raw_retention_hours as i64 * HOUR_MS;
+        prune_file::<MetricSample>(&self.dir.join(RAW_FILE), |s| s.timestamp_ms >= raw_cutoff)?;
+        for (resolution, days) in [
+            (Resolution::Minute, self.retention.minute_retention_days),
+            (Resolution::Hour, self.retention.hour_retention_days),
+        ] {
+            let cutoff = now - days as i64 * DAY_MS;
+            prune_file::<Aggregate>(&self.dir.join(resolution.file_name()), |a| a.start_ms >= cutoff)?;
+        }
+        Ok(())
+    }
+}
+
+fn add_to(acc: &mut Option<Accumulator>, start_ms: i64, agg: &Aggregate) {
+    match acc {
+        Some(acc) => acc.add(agg),
+        None => *acc = Some(Accumulator::new(start_ms, agg)),
+    }
+}
+
+// The finished aggregate, if `acc` holds a bucket other than `start_ms`
+fn take_finished(acc: &mut Option<Accumulator>, start_ms: i64) -> Option<Aggregate> {
+    match acc {
+        Some(current) if current.start_ms != start_ms => {
+            let done = current.finish();
+            *acc = None;
+            Some(done)
+        }
+        _ => None,
+    }
+}
+
+fn append_line<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())
+}
+
+// Entries of a store file, oldest first. Lines that don't parse (e.g. one cut
+// short by a crash) are skipped.
+fn read_lines<T: DeserializeOwned>(path: &Path) -> io::Result<Vec<T>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// Rewrites the file without the entries `keep` rejects
+fn prune_file<T: DeserializeOwned + Serialize>(path: &Path, keep: impl Fn(&T) -> bool) -> io::Result<()> {
+    let entries: Vec<T> = read_lines(path)?;
+    let kept: Vec<&T> = entries.iter().filter(|entry| keep(entry)).collect();
+    if kept.len() == entries.len() {
+        return Ok(());
+    }
+    let mut contents = String::new();
+    for entry in kept {
+        contents.push_str(&serde_json::to_string(entry).map_err(io::Error::other)?);
+        contents.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+// Raw samples taken at or after `since_ms`
+pub fn read_samples(dir: &Path, since_ms: i64) -> io::Result<Vec<MetricSample>> {
+    let mut samples: Vec<MetricSample> = read_lines(&dir.join(RAW_FILE))?;
+    samples.retain(|sample| sample.timestamp_ms >= since_ms);
+    Ok(samples)
+}
+
+// Aggregates of buckets starting at or after `since_ms`
+pub fn read_aggregates(dir: &Path, resolution: Resolution, since_ms: i64) -> io::Result<Vec<Aggregate>> {
+    let mut aggregates: Vec<Aggregate> = read_lines(&dir.join(resolution.file_name()))?;
+    aggregates.retain(|agg| agg.start_ms >= since_ms);
+    Ok(aggregates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_699_999_200_000; // on the hour
+    const SECOND_MS: i64 = 1000;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ryzone-metrics-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn sample(timestamp_ms: i64, power_draw_mw: i32) -> MetricSample {
+        MetricSample { timestamp_ms, power_draw_mw, ..Default::default() }
+    }
+
+    fn open_at(dir: &Path, retention: &MetricsRetention, now: i64) -> MetricsStore {
+        MetricsStore::open_at(dir.to_path_buf(), retention.clone(), 5 * SECOND_MS, now).unwrap()
+    }
+
+    fn powers(aggregate: &Aggregate) -> (u32, i32, i32, i32) {
+        let power = |s: &MetricSample| s.power_draw_mw;
+        (aggregate.count, power(&aggregate.min), power(&aggregate.avg), power(&aggregate.max))
+    }
+
+    #[test]
+    fn raw_samples_follow_logging_interval() {
+        let dir = temp_dir("raw");
+        let mut store = open_at(&dir, &MetricsRetention::default(), T0);
+        for second in 0..12 {
+            store.record(sample(T0 + second * SECOND_MS, 1000)).unwrap();
+        }
+        let times: Vec<i64> = read_samples(&dir, 0).unwrap().iter().map(|s| s.timestamp_ms - T0).collect();
+        assert_eq!(times, [0, 5000, 10000]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn minutes_roll_up_into_hours() {
+        let dir = temp_dir("rollup");
+        let mut store = open_at(&dir, &MetricsRetention::default(), T0);
+        for (offset, power) in [(0, 10), (20, 20), (40, 60)] {
+            store.record(sample(T0 + offset * SECOND_MS, power)).unwrap();
+        }
+        store.record(sample(T0 + MINUTE_MS, 40)).unwrap();
+        store.record(sample(T0 + HOUR_MS - MINUTE_MS, 100)).unwrap();
+        // An hour is written once the first minute of the next one is
+        store.record(sample(T0 + HOUR_MS, 0)).unwrap();
+        store.record(sample(T0 + HOUR_MS + MINUTE_MS, 0)).unwrap();
+
+        let minutes = read_aggregates(&dir, Resolution::Minute, 0).unwrap();
+        let summary: Vec<_> = minutes.iter().map(|m| (m.start_ms - T0, powers(m))).collect();
+        assert_eq!(
+            summary,
+            [
+                (0, (3, 10, 30, 60)),
+                (MINUTE_MS, (1, 40, 40, 40)),
+                (HOUR_MS - MINUTE_MS, (1, 100, 100, 100)),
+                (HOUR_MS, (1, 0, 0, 0)),
+            ]
+        );
+
+        // Averaged over samples, not over minutes: (10 + 20 + 60 + 40 + 100) / 5
+        let hours = read_aggregates(&dir, Resolution::Hour, 0).unwrap();
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].start_ms, T0);
+        assert_eq!(powers(&hours[0]), (5, 10, 46, 100));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reopening_keeps_buckets_whole() {
+        let dir = temp_dir("reopen");
+        let retention = MetricsRetention::default();
+
+        let mut store = open_at(&dir, &retention, T0);
+        store.record(sample(T0, 10)).unwrap();
+        store.record(sample(T0 + MINUTE_MS, 20)).unwrap();
+        store.record(sample(T0 + MINUTE_MS + 10 * SECOND_MS, 30)).unwrap();
+        drop(store);
+
+        // Restarted in the middle of minute 1 (and of the hour)
+        let mut store = open_at(&dir, &retention, T0 + MINUTE_MS + 30 * SECOND_MS);
+        store.record(sample(T0 + MINUTE_MS + 40 * SECOND_MS, 40)).unwrap();
+        store.record(sample(T0 + HOUR_MS, 0)).unwrap();
+        store.record(sample(T0 + HOUR_MS + MINUTE_MS, 0)).unwrap();
+
+        let minutes = read_aggregates(&dir, Resolution::Minute, 0).unwrap();
+        let summary: Vec<_> = minutes.iter().map(|m| (m.start_ms - T0, powers(m))).collect();
+        assert_eq!(summary, [(0, (1, 10, 10, 10)), (MINUTE_MS, (3, 20, 30, 40)), (HOUR_MS, (1, 0, 0, 0))]);
+
+        let hours = read_aggregates(&dir, Resolution::Hour, 0).unwrap();
+        assert_eq!(hours.iter().map(powers).collect::<Vec<_>>(), [(4, 10, 25, 40)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn old_entries_are_pruned() {
+        let dir = temp_dir("prune");
+        let retention = MetricsRetention { raw_retention_hours: 1, minute_retention_days: 1, hour_retention_days: 2 };
+
+        let mut store = open_at(&dir, &retention, T0);
+        store.record(sample(T0, 10)).unwrap();
+        store.record(sample(T0 + HOUR_MS, 20)).unwrap();
+        store.record(sample(T0 + 2 * HOUR_MS, 30)).unwrap();
+        drop(store);
+
+        // Raw keeps the last hour, minutes the last day, hours the last two days
+        open_at(&dir, &retention, T0 + 2 * HOUR_MS);
+        let raw: Vec<i64> = read_samples(&dir, 0).unwrap().iter().map(|s| s.timestamp_ms - T0).collect();
+        assert_eq!(raw, [HOUR_MS, 2 * HOUR_MS]);
+
+        open_at(&dir, &retention, T0 + DAY_MS + 30 * MINUTE_MS);
+        assert!(read_samples(&dir, 0).unwrap().is_empty());
+        let minutes: Vec<i64> = read_aggregates(&dir, Resolution::Minute, 0)
+            .unwrap()
+            .iter()
+            .map(|m| m.start_ms - T0)
+            .collect();
+        assert_eq!(minutes, [HOUR_MS]);
+        assert_eq!(read_aggregates(&dir, Resolution::Hour, 0).unwrap().len(), 1);
+
+        open_at(&dir, &retention, T0 + 3 * DAY_MS);
+        assert!(read_aggregates(&dir, Resolution::Hour, 0).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn zero_retention_disables_file() {
+        let dir = temp_dir("disabled");
+        let retention = MetricsRetention { raw_retention_hours: 0, minute_retention_days: 0, hour_retention_days: 90 };
+
+        let mut store = open_at(&dir, &retention, T0);
+        store.record(sample(T0, 10)).unwrap();
+        store.record(sample(T0 + MINUTE_MS, 20)).unwrap();
+        store.record(sample(T0 + HOUR_MS, 30)).unwrap();
+        store.record(sample(T0 + HOUR_MS + MINUTE_MS, 40)).unwrap();
+
+        assert!(!dir.join(RAW_FILE).exists());
+        assert!(!dir.join(Resolution::Minute.file_name()).exists());
+        // The hour rollup still sees every minute
+        let hours = read_aggregates(&dir, Resolution::Hour, 0).unwrap();
+        assert_eq!(hours.iter().map(powers).collect::<Vec<_>>(), [(2, 10, 15, 20)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,17 @@
+pub mod backend;
+pub mod defaults;
+pub mod helper;
 pub mod settings;
 pub mod stats;
 pub mod logs;
 pub mod types;
 pub mod conversions;
 pub mod battery;
-pub mod ryzen_smu;
\ No newline at end of file
+pub mod capabilities;
+pub mod ryzen_smu;
+pub mod ipc;
+pub mod sysfs;
+pub mod processes;
+pub mod schedule;
+pub mod metrics;
+pub mod pm_table;
\ No newline at end of file
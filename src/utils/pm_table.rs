@@ -0,0 +1,392 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::helper::{is_root, HelperBackend};
+use crate::utils::ryzen_smu::*;
+
+// ─── PM table snapshot ───────────────────────────────────────────────────────
+// The SMU's power-management table as exposed by ryzen_smu: a flat array of
+// little-endian f32s whose layout depends on the chip and firmware. The
+// version is what really identifies the layout; the codename is a fallback
+// for drivers/helpers that don't report it.
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PmTable {
+    pub codename: Option<u32>,
+    pub version: Option<u32>,
+    pub values: Vec<f32>,
+}
+
+impl PmTable {
+    pub fn read_smu(smu: &RyzenSmu) -> Result<Self, String> {
+        let values = smu
+            .read_pm_table()
+            .map_err(|e| format!("read_pm_table: {:?}", e))?;
+        Ok(Self {
+            codename: smu.get_codename().ok(),
+            version: smu.get_pm_table_version().ok(),
+            values,
+        })
+    }
+
+    pub fn layout(&self) -> Option<&'static PmLayout> {
+        find_layout(self.codename, self.version)
+    }
+
+    pub fn value_at(&self, offset: usize) -> Option<f32> {
+        if !offset.is_multiple_of(4) {
+            return None;
+        }
+        self.values.get(offset / 4).copied()
+    }
+}
+
+// Reads the table directly as root, otherwise over one ryzone-helper
+// connection kept between reads. It only connects to a helper that's already
+// running (the stats thread starts it), so polling never runs pkexec.
+#[derive(Default)]
+pub struct PmTableReader {
+    helper: Option<HelperBackend>,
+}
+
+impl PmTableReader {
+    pub fn read(&mut self) -> Result<PmTable, String> {
+        if is_root() {
+            let smu = RyzenSmu::new().map_err(|e| format!("ryzen_smu: {:?}", e))?;
+            return PmTable::read_smu(&smu);
+        }
+        let helper = match self.helper.take() {
+            Some(helper) => helper,
+            None => HelperBackend::connect().map_err(|e| format!("ryzone-helper is not running ({})", e))?,
+        };
+        let table = helper.read_pm_table()?;
+        // Dropped on error, so the next read reconnects
+        self.helper = Some(helper);
+        Ok(table)
+    }
+}
+
+// ─── Layouts ─────────────────────────────────────────────────────────────────
+// Byte offsets follow ryzen_smu's monitor_cpu (Matisse/Vermeer) and RyzenAdj
+// (Renoir, and the package limit header every APU table starts with). Only
+// entries known for a table version are listed; everything else stays in the
+// raw view. No APU layout has a source yet for the SoC temperature, the STAPM
+// time constants, rail voltages or per-core voltages, so those are only
+// decoded on Matisse/Vermeer where monitor_cpu lists them. Offsets carried over from a related table without a source for
+// this one go in `unverified`, which the explorer hides unless asked. Add a
+// layout here when a new version turns up, and move fields out of
+// `unverified` once they're checked against a real table.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PmGroup {
+    Power,
+    Current,
+    Temperature,
+    Clock,
+    Voltage,
+}
+
+impl PmGroup {
+    pub fn label(self) -> &'static str {
+        match self {
+            PmGroup::Power => "Power",
+            PmGroup::Current => "Current",
+            PmGroup::Temperature => "Temperature",
+            PmGroup::Clock => "Clocks",
+            PmGroup::Voltage => "Voltage",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PmField {
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub group: PmGroup,
+    pub offset: usize,
+    pub count: usize, // > 1 for per-core arrays, one f32 per core
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PmLayout {
+    pub name: &'static str,
+    pub codenames: &'static [u32],
+    pub versions: &'static [u32],
+    pub fields: &'static [PmField],
+    pub unverified: &'static [PmField], // guessed from a related table
+}
+
+const fn field(name: &'static str, unit: &'static str, group: PmGroup, offset: usize) -> PmField {
+    PmField { name, unit, group, offset, count: 1 }
+}
+
+const fn per_core(name: &'static str, unit: &'static str, group: PmGroup, offset: usize, count: usize) -> PmField {
+    PmField { name, unit, group, offset, count }
+}
+
+// Shared by every APU table: the three package power limits and their values
+const APU_LIMITS: [PmField; 6] = [
+    field("STAPM limit", "W", PmGroup::Power, 0x00),
+    field("STAPM value", "W", PmGroup::Power, 0x04),
+    field("PPT fast limit", "W", PmGroup::Power, 0x08),
+    field("PPT fast value", "W", PmGroup::Power, 0x0C),
+    field("PPT slow limit", "W", PmGroup::Power, 0x10),
+    field("PPT slow value", "W", PmGroup::Power, 0x14),
+];
+
+// What follows the header on Renoir
+const RENOIR_EXTRA: [PmField; 16] = [
+    field("APU slow limit", "W", PmGroup::Power, 0x18),
+    field("APU slow value", "W", PmGroup::Power, 0x1C),
+    field("VDDCR_VDD TDC limit", "A", PmGroup::Current, 0x20),
+    field("VDDCR_VDD TDC value", "A", PmGroup::Current, 0x24),
+    field("VDDCR_SOC TDC limit", "A", PmGroup::Current, 0x28),
+    field("VDDCR_SOC TDC value", "A", PmGroup::Current, 0x2C),
+    field("VDDCR_VDD EDC limit", "A", PmGroup::Current, 0x30),
+    field("VDDCR_VDD EDC value", "A", PmGroup::Current, 0x34),
+    field("VDDCR_SOC EDC limit", "A", PmGroup::Current, 0x38),
+    field("VDDCR_SOC EDC value", "A", PmGroup::Current, 0x3C),
+    field("Tctl limit", "°C", PmGroup::Temperature, 0x40),
+    field("Tctl", "°C", PmGroup::Temperature, 0x44),
+    field("APU skin temp limit", "°C", PmGroup::Temperature, 0x48),
+    field("APU skin temp", "°C", PmGroup::Temperature, 0x4C),
+    field("dGPU skin temp limit", "°C", PmGroup::Temperature, 0x50),
+    field("dGPU skin temp", "°C", PmGroup::Temperature, 0x54),
+];
+
+const RENOIR_FIELDS: [PmField; 22] = [
+    APU_LIMITS[0], APU_LIMITS[1], APU_LIMITS[2], APU_LIMITS[3], APU_LIMITS[4], APU_LIMITS[5],
+    RENOIR_EXTRA[0], RENOIR_EXTRA[1], RENOIR_EXTRA[2], RENOIR_EXTRA[3],
+    RENOIR_EXTRA[4], RENOIR_EXTRA[5], RENOIR_EXTRA[6], RENOIR_EXTRA[7],
+    RENOIR_EXTRA[8], RENOIR_EXTRA[9], RENOIR_EXTRA[10], RENOIR_EXTRA[11],
+    RENOIR_EXTRA[12], RENOIR_EXTRA[13], RENOIR_EXTRA[14], RENOIR_EXTRA[15],
+];
+
+const MATISSE_FIELDS: [PmField; 21] = [
+    field("PPT limit", "W", PmGroup::Power, 0x000),
+    field("PPT value", "W", PmGroup::Power, 0x004),
+    field("TDC limit", "A", PmGroup::Current, 0x008),
+    field("TDC value", "A", PmGroup::Current, 0x00C),
+    field("Tctl limit", "°C", PmGroup::Temperature, 0x010),
+    field("Tctl", "°C", PmGroup::Temperature, 0x014),
+    field("EDC limit", "A", PmGroup::Current, 0x020),
+    field("EDC value", "A", PmGroup::Current, 0x024),
+    field("VDDCR_CPU power", "W", PmGroup::Power, 0x060),
+    field("VDDCR_SOC power", "W", PmGroup::Power, 0x064),
+    field("VDDIO_MEM power", "W", PmGroup::Power, 0x068),
+    field("Socket power", "W", PmGroup::Power, 0x070),
+    field("VDDCR_SOC", "V", PmGroup::Voltage, 0x0B4),
+    field("FCLK", "MHz", PmGroup::Clock, 0x0C0),
+    field("FCLK effective", "MHz", PmGroup::Clock, 0x0C4),
+    field("UCLK", "MHz", PmGroup::Clock, 0x0C8),
+    field("MCLK", "MHz", PmGroup::Clock, 0x0CC),
+    per_core("Core power", "W", PmGroup::Power, 0x24C, 8),
+    per_core("Core voltage", "V", PmGroup::Voltage, 0x26C, 8),
+    per_core("Core temp", "°C", PmGroup::Temperature, 0x28C, 8),
+    per_core("Core clock", "MHz", PmGroup::Clock, 0x2EC, 8),
+];
+
+pub const LAYOUTS: &[PmLayout] = &[
+    PmLayout {
+        name: "Raven Ridge / Picasso / Dali",
+        codenames: &[CODENAME_RAVEN_RIDGE, CODENAME_RAVEN_RIDGE_2, CODENAME_PICASSO, CODENAME_DALI],
+        versions: &[0x1E0001, 0x1E0002, 0x1E0003, 0x1E0004, 0x1E0005, 0x1E000A, 0x1E0101],
+        fields: &APU_LIMITS,
+        unverified: &[],
+    },
+    PmLayout {
+        name: "Renoir / Lucienne",
+        codenames: &[CODENAME_RENOIR, CODENAME_LUCIENNE],
+        versions: &[0x370000, 0x370001, 0x370002, 0x370003, 0x370004, 0x370005],
+        fields: &RENOIR_FIELDS,
+        unverified: &[],
+    },
+    PmLayout {
+        name: "Cezanne",
+        codenames: &[CODENAME_CEZANNE],
+        versions: &[0x400001, 0x400002, 0x400003, 0x400004, 0x400005],
+        fields: &APU_LIMITS,
+        unverified: &RENOIR_EXTRA,
+    },
+    PmLayout {
+        name: "Rembrandt",
+        codenames: &[CODENAME_REMBRANDT],
+        versions: &[0x450004, 0x450005],
+        fields: &APU_LIMITS,
+        unverified: &RENOIR_EXTRA,
+    },
+    PmLayout {
+        name: "Phoenix",
+        codenames: &[CODENAME_PHOENIX],
+        versions: &[0x4C0003, 0x4C0004, 0x4C0005, 0x4C0006, 0x4C0007, 0x4C0008, 0x4C0009],
+        fields: &APU_LIMITS,
+        unverified: &RENOIR_EXTRA,
+    },
+    PmLayout {
+        name: "Van Gogh",
+        codenames: &[CODENAME_VANGOGH],
+        versions: &[0x3F0000],
+        fields: &APU_LIMITS,
+        unverified: &[],
+    },
+    PmLayout {
+        name: "Matisse / Vermeer",
+        codenames: &[CODENAME_MATISSE, CODENAME_VERMEER],
+        versions: &[0x240902, 0x240903, 0x380804, 0x380805],
+        fields: &MATISSE_FIELDS,
+        unverified: &[],
+    },
+];
+
+// A known version picks its layout. The codename is only trusted when there
+// is no version, since one chip goes through several incompatible tables.
+pub fn find_layout(codename: Option<u32>, version: Option<u32>) -> Option<&'static PmLayout> {
+    match version {
+        Some(version) => LAYOUTS.iter().find(|layout| layout.versions.contains(&version)),
+        None => {
+            let codename = codename?;
+            LAYOUTS.iter().find(|layout| layout.codenames.contains(&codename))
+        }
+    }
+}
+
+// ─── Decoding ────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PmValue {
+    pub name: String,
+    pub unit: &'static str,
+    pub group: PmGroup,
+    pub offset: usize,
+    pub value: f32,
+    pub verified: bool,
+}
+
+// Named values for the table's layout, in layout order, followed by the
+// unverified ones when asked for. Empty when the layout is unknown; offsets
+// past the end of the table are skipped.
+pub fn decode(table: &PmTable, include_unverified: bool) -> Vec<PmValue> {
+    let Some(layout) = table.layout() else {
+        return Vec::new();
+    };
+
+    let verified = layout.fields.iter().map(|field| (field, true));
+    let unverified = layout.unverified.iter().map(|field| (field, false));
+    let mut decoded = Vec::new();
+    for (field, verified) in verified.chain(unverified.filter(|_| include_unverified)) {
+        for i in 0..field.count {
+            let offset = field.offset + i * 4;
+            let Some(value) = table.value_at(offset) else {
+                continue;
+            };
+            let name = if field.count > 1 {
+                format!("{} {}", field.name, i)
+            } else {
+                field.name.to_string()
+            };
+            decoded.push(PmValue { name, unit: field.unit, group: field.group, offset, value, verified });
+        }
+    }
+    decoded
+}
+
+// Raw view: (index, byte offset, value) for every entry
+pub fn raw_values(table: &PmTable) -> Vec<(usize, usize, f32)> {
+    table
+        .values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (index, index * 4, *value))
+        .collect()
+}
+
+pub fn format_pm_version(version: Option<u32>) -> String {
+    version.map_or_else(|| "unknown".to_string(), |v| format!("0x{:06X}", v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every entry holds its own index, so a decoded value gives away its offset
+    fn synthetic(codename: Option<u32>, version: Option<u32>, len: usize) -> PmTable {
+        PmTable { codename, version, values: (0..len).map(|i| i as f32).collect() }
+    }
+
+    #[test]
+    fn version_picks_layout() {
+        for layout in LAYOUTS {
+            for version in layout.versions {
+                assert_eq!(find_layout(None, Some(*version)).map(|l| l.name), Some(layout.name));
+            }
+        }
+        // The version wins over a codename that disagrees
+        let renoir = find_layout(Some(CODENAME_CEZANNE), Some(0x370003));
+        assert_eq!(renoir.map(|l| l.name), Some("Renoir / Lucienne"));
+    }
+
+    #[test]
+    fn codename_only_without_version() {
+        for layout in LAYOUTS {
+            for codename in layout.codenames {
+                assert_eq!(find_layout(Some(*codename), None).map(|l| l.name), Some(layout.name));
+            }
+        }
+        // A version nobody knows isn't rescued by the codename
+        assert!(find_layout(Some(CODENAME_RENOIR), Some(0x999999)).is_none());
+        assert!(find_layout(None, None).is_none());
+    }
+
+    #[test]
+    fn decodes_every_layout() {
+        for layout in LAYOUTS {
+            let table = synthetic(None, Some(layout.versions[0]), 0x400);
+            let count = |fields: &[PmField]| fields.iter().map(|f| f.count).sum::<usize>();
+
+            let decoded = decode(&table, false);
+            assert_eq!(decoded.len(), count(layout.fields), "{}", layout.name);
+            assert!(decoded.iter().all(|v| v.verified));
+            for value in &decoded {
+                assert_eq!(value.value, (value.offset / 4) as f32, "{} {}", layout.name, value.name);
+            }
+
+            let all = decode(&table, true);
+            assert_eq!(all.len(), count(layout.fields) + count(layout.unverified), "{}", layout.name);
+            assert_eq!(all.iter().filter(|v| !v.verified).count(), count(layout.unverified));
+        }
+    }
+
+    #[test]
+    fn per_core_fields_are_numbered() {
+        let table = synthetic(None, Some(0x380805), 0x400);
+        let decoded = decode(&table, false);
+        let core = decoded.iter().find(|v| v.name == "Core clock 3").unwrap();
+        assert_eq!(core.offset, 0x2EC + 3 * 4);
+        assert_eq!(core.value, ((0x2EC + 3 * 4) / 4) as f32);
+    }
+
+    #[test]
+    fn short_table_skips_missing_offsets() {
+        // Only the first four limit entries fit
+        let table = synthetic(None, Some(0x1E0004), 4);
+        let names: Vec<_> = decode(&table, false).into_iter().map(|v| v.name).collect();
+        assert_eq!(names, ["STAPM limit", "STAPM value", "PPT fast limit", "PPT fast value"]);
+    }
+
+    #[test]
+    fn unknown_version_is_raw_only() {
+        let table = synthetic(Some(CODENAME_PHOENIX), Some(0x123456), 8);
+        assert!(table.layout().is_none());
+        assert!(decode(&table, true).is_empty());
+        let raw = raw_values(&table);
+        assert_eq!(raw.len(), 8);
+        assert_eq!(raw[5], (5, 0x14, 5.0));
+    }
+
+    #[test]
+    fn misaligned_offset_has_no_value() {
+        let table = synthetic(None, None, 4);
+        assert_eq!(table.value_at(8), Some(2.0));
+        assert_eq!(table.value_at(6), None);
+        assert_eq!(table.value_at(16), None);
+    }
+}
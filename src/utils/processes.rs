@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::types::{AppRule, ProfileSettings};
+
+// Like RYZONE_SYSFS_ROOT: point the scanner at a copy of /proc instead.
+const PROC_ROOT_ENV: &str = "RYZONE_PROC_ROOT";
+const DEFAULT_PROC_ROOT: &str = "/proc";
+
+pub fn proc_root() -> PathBuf {
+    match std::env::var(PROC_ROOT_ENV) {
+        Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => PathBuf::from(DEFAULT_PROC_ROOT),
+    }
+}
+
+// ─── Process scan ────────────────────────────────────────────────────────────
+// One entry per running process. Processes that exit mid-scan, and kernel
+// threads (empty cmdline), are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub comm: String,     // /proc/<pid>/comm, truncated to 15 chars by the kernel
+    pub exe_name: String, // file name of argv[0]
+    pub cmdline: String,  // arguments joined with spaces
+}
+
+pub fn scan_processes(proc_dir: &Path) -> Vec<ProcessInfo> {
+    let Ok(entries) = fs::read_dir(proc_dir) else {
+        return Vec::new();
+    };
+
+    let mut processes: Vec<ProcessInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            read_process(&entry.path(), pid)
+        })
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+    processes
+}
+
+fn read_process(dir: &Path, pid: u32) -> Option<ProcessInfo> {
+    let raw_cmdline = fs::read(dir.join("cmdline")).ok()?;
+    let args: Vec<String> = raw_cmdline
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    let argv0 = args.first()?;
+
+    let comm = fs::read_to_string(dir.join("comm"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    let exe_name = Path::new(argv0)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    Some(ProcessInfo {
+        pid,
+        comm,
+        exe_name,
+        cmdline: args.join(" "),
+    })
+}
+
+// ─── Rule matching ───────────────────────────────────────────────────────────
+impl AppRule {
+    // `exe` matches the executable name exactly (also against the truncated
+    // comm); `cmdline` is a substring of the full command line. A rule with
+    // both set needs both to match.
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        let exe_ok = match self.exe.as_deref() {
+            Some(exe) => {
+                process.exe_name == exe
+                    || process.comm == exe
+                    || (process.comm.len() == 15 && exe.starts_with(process.comm.as_str()))
+            }
+            None => true,
+        };
+        let cmdline_ok = match self.cmdline.as_deref() {
+            Some(pattern) => process.cmdline.contains(pattern),
+            None => true,
+        };
+        (self.exe.is_some() || self.cmdline.is_some()) && exe_ok && cmdline_ok
+    }
+}
+
+// First rule (in config order) with a running match wins. Rules pointing at
+// a profile that doesn't exist are skipped.
+pub fn find_matching_rule<'a>(
+    settings: &'a ProfileSettings,
+    processes: &[ProcessInfo],
+) -> Option<&'a AppRule> {
+    settings
+        .app_rules
+        .iter()
+        .filter(|rule| settings.has_profile(&rule.profile))
+        .find(|rule| processes.iter().any(|process| rule.matches(process)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A /proc with a few processes: (pid, comm, argv)
+    const PROCESSES: &[(u32, &str, &[&str])] = &[
+        (100, "gnome-shell", &["/usr/bin/gnome-shell"]),
+        // The kernel keeps the first 15 bytes of the name in comm
+        (200, "DaVinci-Resolve", &["/opt/resolve/bin/DaVinci-Resolve-Studio", "--nogui"]),
+        (300, "python3", &["/usr/bin/python3", "train.py", "--epochs", "10"]),
+        // Kernel thread
+        (400, "kworker/0:1", &[]),
+    ];
+
+    fn proc_fixture(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ryzone-processes-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (pid, comm, argv) in PROCESSES {
+            let dir = root.join(pid.to_string());
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+            let cmdline: Vec<u8> = argv.iter().flat_map(|arg| arg.bytes().chain([0])).collect();
+            fs::write(dir.join("cmdline"), cmdline).unwrap();
+        }
+        fs::create_dir_all(root.join("self")).unwrap();
+        root
+    }
+
+    fn scan(name: &str) -> Vec<ProcessInfo> {
+        let root = proc_fixture(name);
+        let processes = scan_processes(&root);
+        let _ = fs::remove_dir_all(&root);
+        processes
+    }
+
+    fn rule(name: &str, profile: &str, exe: Option<&str>, cmdline: Option<&str>) -> AppRule {
+        AppRule {
+            name: name.to_string(),
+            profile: profile.to_string(),
+            exe: exe.map(str::to_string),
+            cmdline: cmdline.map(str::to_string),
+        }
+    }
+
+    fn matching(rule: &AppRule, processes: &[ProcessInfo]) -> Vec<u32> {
+        processes.iter().filter(|p| rule.matches(p)).map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn scan_reads_processes() {
+        let processes = scan("scan");
+        assert_eq!(
+            processes[1],
+            ProcessInfo {
+                pid: 200,
+                comm: "DaVinci-Resolve".to_string(),
+                exe_name: "DaVinci-Resolve-Studio".to_string(),
+                cmdline: "/opt/resolve/bin/DaVinci-Resolve-Studio --nogui".to_string(),
+            }
+        );
+        // Kernel threads and non-pid entries are skipped
+        assert_eq!(processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![100, 200, 300]);
+    }
+
+    #[test]
+    fn exe_matches_name_or_truncated_comm() {
+        let processes = scan("exe");
+        // argv[0] differs from the full name; only the 15-char comm is left
+        let mut truncated = processes[1].clone();
+        truncated.exe_name = "resolve".to_string();
+
+        for (exe, expected) in [
+            ("gnome-shell", vec![100]),
+            ("DaVinci-Resolve-Studio", vec![200]),
+            ("DaVinci-Resolve", vec![200]),
+            // Exact names only, no prefixes
+            ("gnome", vec![]),
+            ("DaVinci", vec![]),
+            ("/usr/bin/python3", vec![]),
+        ] {
+            assert_eq!(matching(&rule("r", "turbo", Some(exe), None), &processes), expected, "exe {}", exe);
+        }
+        assert!(rule("r", "turbo", Some("DaVinci-Resolve-Studio"), None).matches(&truncated));
+        // A comm shorter than 15 chars wasn't truncated, so it isn't a prefix match
+        assert!(!rule("r", "turbo", Some("python3.12"), None).matches(&processes[2]));
+    }
+
+    #[test]
+    fn cmdline_is_a_substring() {
+        let processes = scan("cmdline");
+        for (pattern, expected) in [
+            ("train.py", vec![300]),
+            ("train.py --epochs 10", vec![300]),
+            ("--nogui", vec![200]),
+            ("/usr/bin/", vec![100, 300]),
+            ("serve.py", vec![]),
+        ] {
+            assert_eq!(matching(&rule("r", "turbo", None, Some(pattern)), &processes), expected, "cmdline {}", pattern);
+        }
+    }
+
+    #[test]
+    fn exe_and_cmdline_both_have_to_match() {
+        let processes = scan("both");
+        assert_eq!(matching(&rule("r", "turbo", Some("python3"), Some("train.py")), &processes), vec![300]);
+        assert!(matching(&rule("r", "turbo", Some("python3"), Some("serve.py")), &processes).is_empty());
+        assert!(matching(&rule("r", "turbo", Some("gnome-shell"), Some("train.py")), &processes).is_empty());
+        // A rule with neither matches nothing
+        assert!(matching(&rule("r", "turbo", None, None), &processes).is_empty());
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let processes = scan("order");
+        let mut settings: ProfileSettings =
+            toml::from_str(crate::utils::settings::PROFILE_SETTINGS_TEMPLATE).unwrap();
+        settings.app_rules = vec![
+            rule("not running", "fixed", Some("blender"), None),
+            rule("missing profile", "gaming", Some("gnome-shell"), None),
+            rule("training", "turbo", None, Some("train.py")),
+            rule("desktop", "custom", Some("gnome-shell"), None),
+        ];
+        let name = |processes: &[ProcessInfo]| find_matching_rule(&settings, processes).map(|r| r.name.clone());
+
+        assert_eq!(name(&processes), Some("training".to_string()));
+        // Without python, the next rule in order
+        assert_eq!(name(&processes[..2]), Some("desktop".to_string()));
+        assert_eq!(name(&[]), None);
+    }
+}
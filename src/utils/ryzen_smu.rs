@@ -2,7 +2,7 @@ use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
-const SMU_DRIVER_PATH: &str = "/sys/kernel/ryzen_smu_drv";
+use crate::utils::sysfs::SysfsRoot;
 
 #[derive(Debug)]
 pub enum SmuError {
@@ -19,22 +19,41 @@ impl From<io::Error> for SmuError {
     }
 }
 
+// Values of the driver's `codename` file (enum smu_processor_codename)
+pub const CODENAME_RENOIR: u32 = 2;
+pub const CODENAME_PICASSO: u32 = 3;
+pub const CODENAME_MATISSE: u32 = 4;
+pub const CODENAME_RAVEN_RIDGE: u32 = 7;
+pub const CODENAME_RAVEN_RIDGE_2: u32 = 8;
+pub const CODENAME_REMBRANDT: u32 = 11;
+pub const CODENAME_VERMEER: u32 = 12;
+pub const CODENAME_VANGOGH: u32 = 13;
+pub const CODENAME_CEZANNE: u32 = 14;
+pub const CODENAME_DALI: u32 = 16;
+pub const CODENAME_LUCIENNE: u32 = 17;
+pub const CODENAME_PHOENIX: u32 = 21;
+
 pub struct RyzenSmu {
     path: String,
 }
 
 impl RyzenSmu {
     pub fn new() -> Result<Self, SmuError> {
-        if !Path::new(SMU_DRIVER_PATH).exists() {
+        Self::with_root(&SysfsRoot::from_env())
+    }
+
+    pub fn with_root(sysfs: &SysfsRoot) -> Result<Self, SmuError> {
+        let driver_dir = sysfs.ryzen_smu_dir();
+        if !driver_dir.exists() {
             return Err(SmuError::DriverNotLoaded);
         }
         Ok(Self {
-            path: SMU_DRIVER_PATH.to_string(),
+            path: driver_dir.to_string_lossy().to_string(),
         })
     }
 
     pub fn is_supported() -> bool {
-        Path::new(SMU_DRIVER_PATH).exists()
+        SysfsRoot::from_env().ryzen_smu_dir().exists()
     }
 
     pub fn get_driver_version(&self) -> Result<String, SmuError> {
@@ -55,6 +74,14 @@ impl RyzenSmu {
         })
     }
 
+    // `pm_table_version` is a binary 32-bit little-endian value
+    pub fn get_pm_table_version(&self) -> Result<u32, SmuError> {
+        let path = format!("{}/pm_table_version", self.path);
+        let buf = fs::read(path)?;
+        let bytes: [u8; 4] = buf.get(..4).ok_or(SmuError::InvalidSize)?.try_into().unwrap();
+        Ok(u32::from_le_bytes(bytes))
+    }
+
     fn read_string_file(&self, filename: &str) -> Result<String, SmuError> {
         let path = format!("{}/{}", self.path, filename);
         let content = fs::read_to_string(path)?;
@@ -0,0 +1,305 @@
+use std::time::Instant;
+
+use crate::utils::types::{ProfileSettings, ScheduleEntry, Weekday};
+
+// ─── Clock ───────────────────────────────────────────────────────────────────
+// Profile resolution reads the time from whatever Clock is passed in, so
+// schedules can be checked at any time of the week without waiting for it.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalTime {
+    pub weekday: Weekday,
+    pub minute: u16, // minutes since local midnight, 0..1440
+}
+
+pub trait Clock {
+    fn now(&self) -> LocalTime;
+    fn instant(&self) -> Instant; // for measuring durations, e.g. dwell times
+}
+
+// The machine's local time zone, through localtime_r
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> LocalTime {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        unsafe {
+            let t = libc::time(std::ptr::null_mut());
+            libc::localtime_r(&t, &mut tm);
+        }
+        LocalTime {
+            // tm_wday counts from Sunday
+            weekday: Weekday::ALL[((tm.tm_wday + 6) % 7) as usize],
+            minute: (tm.tm_hour * 60 + tm.tm_min) as u16,
+        }
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// ─── Times ───────────────────────────────────────────────────────────────────
+pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+// "HH:MM" -> minutes since midnight. "24:00" is accepted as an end time.
+pub fn parse_time(value: &str) -> Result<u16, String> {
+    let invalid = || format!("invalid time '{}', expected HH:MM", value);
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 || hours > 24 || (hours == 24 && minutes > 0) {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+pub fn format_time(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+impl ScheduleEntry {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.days.is_empty() {
+            return Err("no days selected".to_string());
+        }
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        Ok(())
+    }
+
+    // Whether `now` falls inside this entry. Entries whose end is at or before
+    // their start continue into the next day, so equal times cover 24 hours.
+    pub fn covers(&self, now: &LocalTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let start = start % MINUTES_PER_DAY;
+        if end > start {
+            self.days.contains(&now.weekday) && now.minute >= start && now.minute < end
+        } else {
+            (self.days.contains(&now.weekday) && now.minute >= start)
+                || (self.days.contains(&now.weekday.previous()) && now.minute < end)
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}-{}", self.start, self.end)
+    }
+}
+
+// First valid entry (in config order) covering the current time. Entries for
+// a profile that doesn't exist are skipped.
+pub fn active_schedule_entry<'a>(
+    settings: &'a ProfileSettings,
+    clock: &dyn Clock,
+) -> Option<&'a ScheduleEntry> {
+    let now = clock.now();
+    settings
+        .schedule
+        .iter()
+        .filter(|entry| settings.has_profile(&entry.profile))
+        .find(|entry| entry.covers(&now))
+}
+
+// ─── Weekly grid ─────────────────────────────────────────────────────────────
+// The Profiles tab edits the schedule as a 7 x 24 grid of hours. Each cell
+// holds the profile scheduled at the start of that hour, so entries that
+// don't start and end on the hour can't be saved back from it.
+
+pub type ScheduleGrid = [[Option<String>; 24]; 7];
+
+pub fn schedule_grid(settings: &ProfileSettings) -> ScheduleGrid {
+    let mut grid: ScheduleGrid = Default::default();
+    for weekday in Weekday::ALL {
+        for hour in 0..24 {
+            let now = LocalTime { weekday, minute: hour * 60 };
+            grid[weekday.index()][hour as usize] = settings
+                .schedule
+                .iter()
+                .find(|entry| entry.covers(&now))
+                .map(|entry| entry.profile.clone());
+        }
+    }
+    grid
+}
+
+// Entries the grid would round: not on the hour, or unreadable
+pub fn off_grid_entries(schedule: &[ScheduleEntry]) -> Vec<&ScheduleEntry> {
+    let on_the_hour = |time: &str| parse_time(time).is_ok_and(|minute| minute % 60 == 0);
+    schedule
+        .iter()
+        .filter(|entry| !on_the_hour(&entry.start) || !on_the_hour(&entry.end))
+        .collect()
+}
+
+// Turns the grid back into entries: one per run of equal hours in a day,
+// with runs that repeat on several days merged into a single entry.
+pub fn schedule_from_grid(grid: &ScheduleGrid) -> Vec<ScheduleEntry> {
+    let mut entries: Vec<ScheduleEntry> = Vec::new();
+    for weekday in Weekday::ALL {
+        let hours = &grid[weekday.index()];
+        let mut hour = 0;
+        while hour < 24 {
+            let Some(profile) = &hours[hour] else {
+                hour += 1;
+                continue;
+            };
+            let start = hour;
+            while hour < 24 && hours[hour].as_ref() == Some(profile) {
+                hour += 1;
+            }
+            let (start, end) = (format_time(start as u16 * 60), format_time(hour as u16 * 60));
+
+            let same_run = entries
+                .iter_mut()
+                .find(|e| e.profile == *profile && e.start == start && e.end == end);
+            match same_run {
+                Some(entry) => entry.days.push(weekday),
+                None => entries.push(ScheduleEntry {
+                    days: vec![weekday],
+                    start,
+                    end,
+                    profile: profile.clone(),
+                }),
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedClock(LocalTime);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> LocalTime {
+            self.0
+        }
+
+        fn instant(&self) -> Instant {
+            Instant::now()
+        }
+    }
+
+    fn at(weekday: Weekday, time: &str) -> FixedClock {
+        FixedClock(LocalTime { weekday, minute: parse_time(time).unwrap() })
+    }
+
+    fn entry(days: &[Weekday], start: &str, end: &str, profile: &str) -> ScheduleEntry {
+        ScheduleEntry {
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+            profile: profile.to_string(),
+        }
+    }
+
+    fn settings(schedule: Vec<ScheduleEntry>) -> ProfileSettings {
+        let mut settings: ProfileSettings =
+            toml::from_str(crate::utils::settings::PROFILE_SETTINGS_TEMPLATE).unwrap();
+        settings.schedule = schedule;
+        settings
+    }
+
+    fn scheduled(settings: &ProfileSettings, clock: &FixedClock) -> Option<String> {
+        active_schedule_entry(settings, clock).map(|e| e.profile.clone())
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("00:00"), Ok(0));
+        assert_eq!(parse_time("09:30"), Ok(570));
+        assert_eq!(parse_time("24:00"), Ok(1440));
+        assert!(parse_time("24:01").is_err());
+        assert!(parse_time("12:60").is_err());
+        assert!(parse_time("noon").is_err());
+    }
+
+    #[test]
+    fn working_hours() {
+        let weekdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+        let s = settings(vec![entry(&weekdays, "09:00", "18:00", "turbo")]);
+
+        assert_eq!(scheduled(&s, &at(Weekday::Mon, "09:00")), Some("turbo".to_string()));
+        assert_eq!(scheduled(&s, &at(Weekday::Fri, "17:59")), Some("turbo".to_string()));
+        assert_eq!(scheduled(&s, &at(Weekday::Fri, "18:00")), None);
+        assert_eq!(scheduled(&s, &at(Weekday::Wed, "08:59")), None);
+        assert_eq!(scheduled(&s, &at(Weekday::Sat, "12:00")), None);
+    }
+
+    #[test]
+    fn overnight_entry_continues_into_next_day() {
+        let s = settings(vec![entry(&[Weekday::Sun], "22:00", "07:00", "fixed")]);
+
+        assert_eq!(scheduled(&s, &at(Weekday::Sun, "23:30")), Some("fixed".to_string()));
+        assert_eq!(scheduled(&s, &at(Weekday::Mon, "06:59")), Some("fixed".to_string()));
+        assert_eq!(scheduled(&s, &at(Weekday::Mon, "07:00")), None);
+        assert_eq!(scheduled(&s, &at(Weekday::Sun, "06:00")), None);
+    }
+
+    #[test]
+    fn first_entry_wins_and_unknown_profiles_are_skipped() {
+        let s = settings(vec![
+            entry(&[Weekday::Tue], "00:00", "24:00", "deleted"),
+            entry(&[Weekday::Tue], "10:00", "12:00", "turbo"),
+            entry(&[Weekday::Tue], "00:00", "00:00", "custom"),
+        ]);
+
+        assert_eq!(scheduled(&s, &at(Weekday::Tue, "11:00")), Some("turbo".to_string()));
+        assert_eq!(scheduled(&s, &at(Weekday::Tue, "13:00")), Some("custom".to_string()));
+    }
+
+    #[test]
+    fn grid_round_trip() {
+        let s = settings(vec![
+            entry(&[Weekday::Mon, Weekday::Tue], "09:00", "18:00", "turbo"),
+            entry(&[Weekday::Sat], "22:00", "07:00", "fixed"),
+        ]);
+        let grid = schedule_grid(&s);
+        assert_eq!(grid[Weekday::Mon.index()][9].as_deref(), Some("turbo"));
+        assert_eq!(grid[Weekday::Sun.index()][6].as_deref(), Some("fixed"));
+        assert_eq!(grid[Weekday::Sun.index()][7], None);
+
+        let entries = schedule_from_grid(&grid);
+        assert_eq!(
+            entries,
+            vec![
+                entry(&[Weekday::Mon, Weekday::Tue], "09:00", "18:00", "turbo"),
+                entry(&[Weekday::Sat], "22:00", "24:00", "fixed"),
+                entry(&[Weekday::Sun], "00:00", "07:00", "fixed"),
+            ]
+        );
+        assert_eq!(schedule_grid(&settings(entries)), grid);
+    }
+
+    #[test]
+    fn painted_grid_round_trip() {
+        let mut grid: ScheduleGrid = Default::default();
+        for day in [Weekday::Wed, Weekday::Thu] {
+            grid[day.index()][8..12].fill(Some("turbo".to_string()));
+        }
+        grid[Weekday::Wed.index()][12] = Some("fixed".to_string());
+        grid[Weekday::Sun.index()][23] = Some("fixed".to_string());
+        grid[Weekday::Mon.index()][0] = Some("fixed".to_string());
+
+        let entries = schedule_from_grid(&grid);
+        assert!(off_grid_entries(&entries).is_empty());
+        assert_eq!(schedule_grid(&settings(entries)), grid);
+    }
+
+    #[test]
+    fn entries_off_the_hour_are_flagged() {
+        let schedule = vec![
+            entry(&[Weekday::Mon], "09:00", "17:00", "turbo"),
+            entry(&[Weekday::Mon], "09:30", "17:00", "turbo"),
+            entry(&[Weekday::Tue], "22:00", "06:45", "fixed"),
+            entry(&[Weekday::Wed], "00:00", "24:00", "fixed"),
+            entry(&[Weekday::Thu], "noon", "13:00", "fixed"),
+        ];
+        let flagged: Vec<_> = off_grid_entries(&schedule).into_iter().map(ScheduleEntry::label).collect();
+        assert_eq!(flagged, ["09:30-17:00", "22:00-06:45", "noon-13:00"]);
+    }
+}
@@ -1,8 +1,13 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use serde::{Deserialize, Serialize};
 
-use crate::utils::types::{AppSettings, ProfileSettings};
+use crate::utils::logs::{log_error, log_warn};
+use crate::utils::types::{
+    format_limit_errors, AppSettings, PowerLimits, Profile, ProfileMode, ProfileSettings,
+};
 
 pub static APP_SETTINGS_TEMPLATE: &str = 
 r#"[units]
@@ -20,68 +25,109 @@ minimize_to_tray = true             # true | false
 enable_logging = true               # true | false
 update_frequency_ms = 1000        # 1000 | 500 | 100
 logging_frequency_ms = 10000      # 10000 | 5000 | 1000
+log_format = "csv"                  # csv | jsonl
+
+# Stats history in ~/.ryzone/metrics, for the Battery tab charts. 0 turns one off.
+[metrics]
+raw_retention_hours = 24            # samples at logging_frequency_ms
+minute_retention_days = 7           # 1-minute min / avg / max
+hour_retention_days = 90            # 1-hour min / avg / max
 "#;
 
 pub static PROFILE_SETTINGS_TEMPLATE: &str = 
-r#"active_profile = "system"                  # system | custom | turbo | fixed
-low_batt_threshold_percent = 20
+r#"active_profile = "system"                  # name of any profile below
 
-[system.performance]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+# mode: platform_profile | power_source | fixed
+# Limits: fast_mw, slow_mw, stapm_mw (milliwatts) and temp_c (celsius).
+# Leave a limit out to keep whatever the firmware has set.
+[[profiles]]
+name = "system"
+description = "Follows the power profile selected in your operating system"
+mode = "platform_profile"
 
-[system.balanced]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.balanced]
 
-[system.power_saver]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.performance]
 
-[custom.ac]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.power_saver]
 
-[custom.batt]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[[profiles]]
+name = "custom"
+description = "Separate limits for AC, battery and low battery"
+mode = "power_source"
+low_batt_threshold_percent = 20            # enter low_batt below this
+low_batt_hysteresis_percent = 5            # leave it above threshold + this
+min_dwell_s = 30                           # a new sub-profile must hold this long
 
-[custom.low_batt]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.ac]
 
-[turbo.turbo]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.batt]
 
-[fixed.fixed]
-fast_mw = 0
-slow_mw = 0
-stapm_mw = 0
-temp_c = 0
+[profiles.limits.low_batt]
+
+[[profiles]]
+name = "turbo"
+description = "Maximum performance mode with highest power limits"
+mode = "fixed"
+
+[profiles.limits.fixed]
+
+[[profiles]]
+name = "fixed"
+description = "The same limits regardless of power source"
+mode = "fixed"
+
+[profiles.limits.fixed]
+
+# Switch profile while a program runs, e.g. turbo while Blender is open.
+# Rules are checked in order and override active_profile; the first one with a
+# running match wins. exe is the executable name, cmdline any part of the
+# command line; a rule with both needs both to match.
+# [[app_rules]]
+# name = "blender"
+# profile = "turbo"
+# exe = "blender"
+#
+# [[app_rules]]
+# name = "steam games"
+# profile = "turbo"
+# cmdline = "steamapps/common"
+
+# Use a profile at set times, in local time. Checked after app rules and in
+# order; an end at or before the start runs past midnight.
+# days: mon tue wed thu fri sat sun
+# [[schedule]]
+# days = ["mon", "tue", "wed", "thu", "fri"]
+# start = "09:00"
+# end = "18:00"
+# profile = "turbo"
+
+# Battery charge thresholds (percent), for batteries that support them.
+# Leave them out to keep whatever the firmware has set.
+# [charge_thresholds]
+# start_percent = 60                       # start charging below this
+# end_percent = 80                         # stop charging here
 "#;
 
-pub fn read_app_settings() -> Result<AppSettings, Box<dyn std::error::Error>> {
+// ~/.ryzone holds every file Ryzone persists. The GUI and ryzoned both read from here.
+pub fn settings_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let home_dir = std::env::var("HOME")?;
-    let settings_file = format!("{}/.ryzone/app_settings.toml", home_dir);
+    Ok(PathBuf::from(home_dir).join(".ryzone"))
+}
+
+pub fn app_settings_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("app_settings.toml"))
+}
+
+pub fn profile_settings_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(settings_dir()?.join("profile_settings.toml"))
+}
 
-    if !Path::new(&settings_file).exists() {
-        let dir = format!("{}/.ryzone", home_dir);
-        fs::create_dir_all(&dir)?;
+pub fn read_app_settings() -> Result<AppSettings, Box<dyn std::error::Error>> {
+    let settings_file = app_settings_path()?;
+
+    if !settings_file.exists() {
+        fs::create_dir_all(settings_dir()?)?;
         fs::write(&settings_file, APP_SETTINGS_TEMPLATE)?;
     }
 
@@ -91,33 +137,335 @@ pub fn read_app_settings() -> Result<AppSettings, Box<dyn std::error::Error>> {
 }
 
 pub fn read_profile_settings() -> Result<ProfileSettings, Box<dyn std::error::Error>> {
-    let home_dir = std::env::var("HOME")?;
-    let settings_file = format!("{}/.ryzone/profile_settings.toml", home_dir);
+    read_profile_settings_at(&profile_settings_path()?)
+}
 
-    if !Path::new(&settings_file).exists() {
-        let dir = format!("{}/.ryzone", home_dir);
-        fs::create_dir_all(&dir)?;
-        fs::write(&settings_file, PROFILE_SETTINGS_TEMPLATE)?;
+fn read_profile_settings_at(settings_file: &Path) -> Result<ProfileSettings, Box<dyn std::error::Error>> {
+    if !settings_file.exists() {
+        if let Some(dir) = settings_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(settings_file, PROFILE_SETTINGS_TEMPLATE)?;
     }
 
-    let contents = fs::read_to_string(&settings_file)?;
-    let settings = toml::from_str(&contents)?;
+    let contents = fs::read_to_string(settings_file)?;
+    let table: toml::Table = toml::from_str(&contents)?;
+    let settings = if table.contains_key("profiles") {
+        table.try_into()?
+    } else {
+        // Pre-named-profiles file: convert it, keeping the original next to it
+        let legacy: LegacyProfileSettings = table.try_into()?;
+        let settings = legacy.migrate();
+        fs::write(settings_file.with_extension("toml.bak"), &contents)?;
+        write_profile_settings_to(Ok(settings_file.to_path_buf()), &settings)?;
+        settings
+    };
+
+    warn_invalid_settings(&settings);
     Ok(settings)
 }
 
+// Hand-edited files aren't rejected outright; invalid sub-profiles and rules
+// are reported here and enforcement skips them.
+fn warn_invalid_settings(settings: &ProfileSettings) {
+    for profile in &settings.profiles {
+        for (sub, errors) in profile.limit_errors() {
+            log_warn(
+                "settings",
+                format!(
+                    "{}.{} will not be applied: {}",
+                    profile.name,
+                    sub,
+                    format_limit_errors(&errors)
+                ),
+            );
+        }
+    }
+    for rule in &settings.app_rules {
+        if !settings.has_profile(&rule.profile) {
+            log_warn(
+                "settings",
+                format!("rule '{}' ignored: unknown profile '{}'", rule.name, rule.profile),
+            );
+        } else if rule.exe.is_none() && rule.cmdline.is_none() {
+            log_warn("settings", format!("rule '{}' ignored: needs exe or cmdline", rule.name));
+        }
+    }
+    if let Err(e) = settings.charge_thresholds.validate() {
+        log_warn("settings", format!("charge thresholds will not be applied: {}", e));
+    }
+    for entry in &settings.schedule {
+        let problem = if settings.has_profile(&entry.profile) {
+            entry.validate().err()
+        } else {
+            Some(format!("unknown profile '{}'", entry.profile))
+        };
+        if let Some(problem) = problem {
+            log_warn("settings", format!("schedule entry {} ignored: {}", entry.label(), problem));
+        }
+    }
+}
+
+// Failures are also logged, as several callers have nowhere to show them
 pub fn write_app_settings(settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
-    let home_dir = std::env::var("HOME")?;
-    let settings_file = format!("{}/.ryzone/app_settings.toml", home_dir);
-    let contents = toml::to_string_pretty(settings)?;
-    fs::write(&settings_file, contents)?;
-    Ok(())
+    write_toml(app_settings_path(), settings)
+        .inspect_err(|e| log_error("settings", format!("Failed to save app settings: {}", e)))
 }
 
 pub fn write_profile_settings(settings: &ProfileSettings) -> Result<(), Box<dyn std::error::Error>> {
-    let home_dir = std::env::var("HOME")?;
-    let settings_file = format!("{}/.ryzone/profile_settings.toml", home_dir);
-    let contents = toml::to_string_pretty(settings)?;
-    fs::write(&settings_file, contents)?;
+    write_profile_settings_to(profile_settings_path(), settings)
+}
+
+fn write_profile_settings_to(
+    path: Result<PathBuf, Box<dyn std::error::Error>>,
+    settings: &ProfileSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_toml(path, settings)
+        .inspect_err(|e| log_error("settings", format!("Failed to save profile settings: {}", e)))
+}
+
+fn write_toml<T: Serialize>(
+    path: Result<PathBuf, Box<dyn std::error::Error>>,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = toml::to_string_pretty(value)?;
+    // ryzoned and the GUI re-read the file when its mtime changes, so it is
+    // replaced in one rename rather than rewritten in place
+    let path = path?;
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, &path)?;
     Ok(())
 }
 
+// Ends a "charge to 100% once" override after the battery reached full
+pub fn finish_full_charge() -> Result<(), Box<dyn std::error::Error>> {
+    let mut settings = read_profile_settings()?;
+    if settings.charge_thresholds.full_charge_once {
+        settings.charge_thresholds.full_charge_once = false;
+        write_profile_settings(&settings)?;
+    }
+    Ok(())
+}
+
+// Last modification time of app_settings.toml, used to pick up logging changes
+// saved from the GUI.
+pub fn app_settings_modified() -> Option<SystemTime> {
+    let path = app_settings_path().ok()?;
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+// Last modification time of profile_settings.toml, used by ryzoned to pick up
+// changes saved from the GUI.
+pub fn profile_settings_modified() -> Option<SystemTime> {
+    let path = profile_settings_path().ok()?;
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+// ─── Legacy profile settings ─────────────────────────────────────────────────
+// Layout used before profiles could be named: four fixed groups and a global
+// low battery threshold. Only read, to migrate existing files.
+
+#[derive(Deserialize)]
+struct LegacyProfileSettings {
+    active_profile: String,
+    low_batt_threshold_percent: i32,
+    system: LegacySystemProfiles,
+    custom: LegacyCustomProfiles,
+    turbo: LegacyTurboProfile,
+    fixed: LegacyFixedProfile,
+}
+
+#[derive(Deserialize)]
+struct LegacySystemProfiles {
+    performance: PowerLimits,
+    balanced: PowerLimits,
+    power_saver: PowerLimits,
+}
+
+#[derive(Deserialize)]
+struct LegacyCustomProfiles {
+    ac: PowerLimits,
+    batt: PowerLimits,
+    low_batt: PowerLimits,
+}
+
+#[derive(Deserialize)]
+struct LegacyTurboProfile {
+    turbo: PowerLimits,
+}
+
+#[derive(Deserialize)]
+struct LegacyFixedProfile {
+    fixed: PowerLimits,
+}
+
+impl LegacyProfileSettings {
+    fn migrate(self) -> ProfileSettings {
+        let mut system = Profile::new(
+            "system",
+            "Follows the power profile selected in your operating system",
+            ProfileMode::PlatformProfile,
+        );
+        system.limits.insert("performance".to_string(), self.system.performance);
+        system.limits.insert("balanced".to_string(), self.system.balanced);
+        system.limits.insert("power_saver".to_string(), self.system.power_saver);
+
+        let mut custom = Profile::new(
+            "custom",
+            "Separate limits for AC, battery and low battery",
+            ProfileMode::PowerSource,
+        );
+        custom.low_batt_threshold_percent = self.low_batt_threshold_percent;
+        custom.limits.insert("ac".to_string(), self.custom.ac);
+        custom.limits.insert("batt".to_string(), self.custom.batt);
+        custom.limits.insert("low_batt".to_string(), self.custom.low_batt);
+
+        let mut turbo = Profile::new(
+            "turbo",
+            "Maximum performance mode with highest power limits",
+            ProfileMode::Fixed,
+        );
+        turbo.limits.insert("fixed".to_string(), self.turbo.turbo);
+
+        let mut fixed = Profile::new(
+            "fixed",
+            "The same limits regardless of power source",
+            ProfileMode::Fixed,
+        );
+        fixed.limits.insert("fixed".to_string(), self.fixed.fixed);
+
+        ProfileSettings {
+            active_profile: self.active_profile,
+            profiles: vec![system, custom, turbo, fixed],
+            app_rules: Vec::new(),
+            schedule: Vec::new(),
+            charge_thresholds: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // profile_settings.toml as written before profiles could be named
+    const LEGACY: &str = r#"
+active_profile = "custom"
+low_batt_threshold_percent = 15
+
+[system.performance]
+fast_mw = 45000
+slow_mw = 40000
+stapm_mw = 35000
+temp_c = 95
+
+[system.balanced]
+fast_mw = 30000
+slow_mw = 25000
+stapm_mw = 20000
+temp_c = 90
+
+[system.power_saver]
+fast_mw = 15000
+slow_mw = 12000
+stapm_mw = 10000
+temp_c = 85
+
+[custom.ac]
+fast_mw = 40000
+slow_mw = 35000
+stapm_mw = 30000
+temp_c = 95
+
+[custom.batt]
+fast_mw = 20000
+slow_mw = 18000
+stapm_mw = 15000
+temp_c = 0
+
+[custom.low_batt]
+fast_mw = 0
+slow_mw = 0
+stapm_mw = 0
+temp_c = 0
+
+[turbo.turbo]
+fast_mw = 54000
+slow_mw = 54000
+stapm_mw = 54000
+temp_c = 100
+
+[fixed.fixed]
+fast_mw = 25000
+slow_mw = 25000
+stapm_mw = 25000
+temp_c = 90
+"#;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ryzone-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn limits(fast_mw: i32, slow_mw: i32, stapm_mw: i32, temp_c: Option<i32>) -> PowerLimits {
+        PowerLimits { fast_mw: Some(fast_mw), slow_mw: Some(slow_mw), stapm_mw: Some(stapm_mw), temp_c }
+    }
+
+    #[test]
+    fn legacy_file_is_migrated() {
+        let dir = temp_dir("legacy");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("profile_settings.toml");
+        fs::write(&file, LEGACY).unwrap();
+
+        let settings = read_profile_settings_at(&file).unwrap();
+        assert_eq!(settings.active_profile, "custom");
+        assert_eq!(settings.profile_names(), vec!["system", "custom", "turbo", "fixed"]);
+        assert!(settings.app_rules.is_empty() && settings.schedule.is_empty());
+
+        let system = settings.profile("system").unwrap();
+        assert_eq!(system.mode, ProfileMode::PlatformProfile);
+        assert_eq!(system.limits("balanced"), Some(&limits(30000, 25000, 20000, Some(90))));
+
+        let custom = settings.profile("custom").unwrap();
+        assert_eq!(custom.mode, ProfileMode::PowerSource);
+        // The global threshold moves onto the profile that used it
+        assert_eq!(custom.low_batt_threshold_percent, 15);
+        assert_eq!(custom.limits("ac"), Some(&limits(40000, 35000, 30000, Some(95))));
+        // 0 meant "not set"
+        assert_eq!(custom.limits("batt"), Some(&limits(20000, 18000, 15000, None)));
+        assert_eq!(custom.limits("low_batt"), Some(&PowerLimits::default()));
+
+        assert_eq!(settings.limits("turbo", "fixed"), Some(&limits(54000, 54000, 54000, Some(100))));
+        assert_eq!(settings.limits("fixed", "fixed"), Some(&limits(25000, 25000, 25000, Some(90))));
+
+        // The original is kept, and the file is rewritten in the new layout
+        assert_eq!(fs::read_to_string(dir.join("profile_settings.toml.bak")).unwrap(), LEGACY);
+        let rewritten = fs::read_to_string(&file).unwrap();
+        assert!(rewritten.contains("[[profiles]]"));
+
+        // Reading it again doesn't migrate a second time
+        fs::remove_file(dir.join("profile_settings.toml.bak")).unwrap();
+        let reread = read_profile_settings_at(&file).unwrap();
+        assert_eq!(reread.profile_names(), settings.profile_names());
+        assert_eq!(reread.profiles, settings.profiles);
+        assert!(!dir.join("profile_settings.toml.bak").exists());
+        assert_eq!(fs::read_to_string(&file).unwrap(), rewritten);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_file_gets_template() {
+        let dir = temp_dir("template");
+        let file = dir.join("profile_settings.toml");
+
+        let settings = read_profile_settings_at(&file).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), PROFILE_SETTINGS_TEMPLATE);
+        assert!(settings.has_profile(&settings.active_profile));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,95 +1,169 @@
 use std::fs;
 use std::path::Path;
 use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use dioxus::prelude::*;
 use libryzenadj::RyzenAdj;
 
-use crate::utils::battery::read_battery_snapshot;
-use crate::utils::types::{CurrentStats, PowerLimits, ProfileSettings};
-
-// ─── Dioxus hook: entry point ────────────────────────────────────────────────
-// Spawns the background polling thread. Creates RyzenAdj once, then loops.
-pub fn use_current_stats_signal(
-    update_frequency_ms: i32,
-    profile_settings: SyncSignal<ProfileSettings>,
-) -> SyncSignal<CurrentStats> {
-    let stats_signal = use_signal_sync(CurrentStats::default);
-    let mut worker_signal = stats_signal;
-    let interval_ms = update_frequency_ms.max(250) as u64;
-
-    use_hook(move || {
-        std::thread::spawn(move || {
-            // Create RyzenAdj once at thread startup
-            let adj = match RyzenAdj::new() {
-                Ok(adj) => Some(adj),
-                Err(e) => {
-                    eprintln!(
-                        "[ryzenadj] Failed to initialize: {:?}. CPU/power stats unavailable.",
-                        e
-                    );
-                    None
-                }
-            };
+use crate::utils::backend::PowerBackend;
+use crate::utils::battery::{read_adapter_snapshot, read_battery_snapshot};
+use crate::utils::capabilities::capabilities_for;
+use crate::utils::defaults::capture_firmware_defaults;
+use crate::utils::helper::{is_root, HelperBackend};
+use crate::utils::logs::{log_error, log_info, log_warn};
+use crate::utils::processes::{find_matching_rule, proc_root, scan_processes, ProcessInfo};
+use crate::utils::schedule::{active_schedule_entry, Clock, SystemClock};
+use crate::utils::settings::finish_full_charge;
+use crate::utils::sysfs::SysfsRoot;
+use crate::utils::types::{
+    format_limit_errors, ChargeThresholds, CurrentStats, LimitError, PowerLimits, Profile, ProfileMode,
+    ProfileSettings,
+};
+
+// ─── Backend setup ───────────────────────────────────────────────────────────
+// Created once per process. As root RyzenAdj is used directly; otherwise the
+// hardware calls go through ryzone-helper, started via pkexec.
+// The firmware's own limits are snapshotted before anything is enforced.
+// Without a backend, stats still carry battery data.
+pub fn init_backend() -> Option<Box<dyn PowerBackend>> {
+    let backend = init_restore_backend()?;
+    if let Err(e) = capture_firmware_defaults(backend.as_ref()) {
+        log_error("defaults", format!("Failed to capture firmware limits: {}", e));
+    }
+    Some(backend)
+}
 
-            loop {
-                let ps = profile_settings.peek().clone();
-                if let Ok(next_stats) = read_current_stats(adj.as_ref(), &ps) {
-                    worker_signal.set(next_stats);
-                }
-                std::thread::sleep(Duration::from_millis(interval_ms));
-            }
-        });
-    });
+// Same, minus the snapshot: for writing an existing snapshot back
+// (`ryzonectl restore-defaults`), where the limits in effect may already be
+// Ryzone's own.
+pub fn init_restore_backend() -> Option<Box<dyn PowerBackend>> {
+    if is_root() {
+        return init_ryzenadj().map(|adj| Box::new(adj) as Box<dyn PowerBackend>);
+    }
+    match HelperBackend::spawn() {
+        Ok(helper) => Some(Box::new(helper)),
+        Err(e) => {
+            log_error("helper", format!("{}. CPU/power stats unavailable.", e));
+            None
+        }
+    }
+}
 
-    stats_signal
+// For read-only callers (`ryzonectl stats`): never starts pkexec and never
+// snapshots firmware defaults. Uses RyzenAdj as root, otherwise only a helper
+// that is already running.
+pub fn init_readonly_backend() -> Option<Box<dyn PowerBackend>> {
+    if is_root() {
+        return init_ryzenadj().map(|adj| Box::new(adj) as Box<dyn PowerBackend>);
+    }
+    HelperBackend::connect()
+        .ok()
+        .map(|helper| Box::new(helper) as Box<dyn PowerBackend>)
+}
+
+pub fn init_ryzenadj() -> Option<RyzenAdj> {
+    match RyzenAdj::new() {
+        Ok(adj) => Some(adj),
+        Err(e) => {
+            log_error(
+                "ryzenadj",
+                format!("Failed to initialize: {:?}. CPU/power stats unavailable.", e),
+            );
+            None
+        }
+    }
 }
 
 // ─── Per-cycle orchestrator ──────────────────────────────────────────────────
 // Called once per tick. Fills all CurrentStats fields, resolves profile, enforces limits.
-fn read_current_stats(
-    adj: Option<&RyzenAdj>,
+// Generic over the backend so the whole cycle can run against FakeBackend.
+// `resolver` carries the sub-profile and refused charge thresholds from one
+// tick to the next.
+pub fn read_current_stats<B: PowerBackend + ?Sized>(
+    backend: Option<&B>,
+    sysfs: &SysfsRoot,
     profile_settings: &ProfileSettings,
+    resolver: &mut ProfileResolver,
 ) -> Result<CurrentStats, String> {
+    let (mut stats, target_limits) = sample_current_stats(backend, sysfs, profile_settings, resolver)?;
+    // Limits this chip doesn't have, or outside its range, are left alone
+    let capabilities = capabilities_for(sysfs);
+    let out_of_range = capabilities.check(&target_limits);
+    if out_of_range != resolver.out_of_range_limits {
+        if !out_of_range.is_empty() {
+            log_warn("profile", format!("skipping limits: {}", format_limit_errors(&out_of_range)));
+        }
+        resolver.out_of_range_limits = out_of_range;
+    }
+    let target_limits = capabilities.supported_limits(&target_limits);
+
+    // Enforce profile limits if they differ from target
+    if let Some(backend) = backend {
+        if let Err(e) = enforce_profile_limits(backend, &stats, &target_limits) {
+            log_error("ryzenadj", format!("enforce limits error: {}", e));
+        }
+        let refused = &mut resolver.refused_charge_targets;
+        if let Err(e) = enforce_charge_thresholds(backend, sysfs, profile_settings, &stats, refused) {
+            stats.charge_threshold_error = e;
+        }
+    }
+
+    Ok(stats)
+}
+
+// Read-only half of the cycle: fills CurrentStats and resolves the target
+// limits without touching the hardware limits.
+pub fn sample_current_stats<B: PowerBackend + ?Sized>(
+    backend: Option<&B>,
+    sysfs: &SysfsRoot,
+    profile_settings: &ProfileSettings,
+    resolver: &mut ProfileResolver,
+) -> Result<(CurrentStats, PowerLimits), String> {
     let mut stats = CurrentStats::default();
 
     // 1. Fill battery stats
-    fill_battery_stats(&mut stats)?;
+    fill_battery_stats(sysfs, &mut stats)?;
 
-    // 2. Fill ryzenadj stats (CPU, GPU, power limits)
-    if let Some(adj) = adj {
-        if let Err(e) = fill_ryzenadj_stats(adj, &mut stats) {
-            eprintln!("[ryzenadj] stats read error: {}", e);
+    // 2. Fill AC adapter state
+    let on_ac = fill_adapter_stats(sysfs, &mut stats);
+
+    // 3. Fill ryzenadj stats (CPU, GPU, power limits)
+    if let Some(backend) = backend {
+        match fill_ryzenadj_stats(backend, sysfs, &mut stats) {
+            Ok(()) => stats.ryzenadj_available = true,
+            Err(e) => log_error("ryzenadj", format!("stats read error: {}", e)),
         }
     }
 
-    // 3. Fill runtime estimates
+    // 4. Fill runtime estimates
     fill_runtime_estimates(&mut stats);
 
-    // 4. Resolve target profile
-    let (target_limits, profile_name, sub_profile_name) = resolve_target_profile(
+    // 5. Scan running programs, only when there are rules to match
+    let processes = if profile_settings.app_rules.is_empty() {
+        Vec::new()
+    } else {
+        scan_processes(&proc_root())
+    };
+
+    // 6. Resolve target profile
+    let (target_limits, profile_name, sub_profile_name) = resolver.resolve_target_profile(
         profile_settings,
-        &stats.batt_charge_status,
+        sysfs,
+        on_ac,
         stats.batt_charge_percent,
+        &processes,
+        &SystemClock,
     );
     stats.profile = profile_name;
     stats.sub_profile = sub_profile_name;
 
-    // 5. Enforce profile limits if they differ from target
-    if let Some(adj) = adj {
-        if let Err(e) = enforce_profile_limits(adj, &stats, &target_limits) {
-            eprintln!("[ryzenadj] enforce limits error: {}", e);
-        }
-    }
-
-    Ok(stats)
+    Ok((stats, target_limits))
 }
 
 // ─── Battery ─────────────────────────────────────────────────────────────────
 // Reads battery snapshot and fills battery + power_draw fields in stats.
-fn fill_battery_stats(stats: &mut CurrentStats) -> Result<(), String> {
-    let snapshot = read_battery_snapshot().map_err(|e| e.to_string())?;
+fn fill_battery_stats(sysfs: &SysfsRoot, stats: &mut CurrentStats) -> Result<(), String> {
+    let snapshot = read_battery_snapshot(sysfs).map_err(|e| e.to_string())?;
     if let Some(battery) = snapshot {
         stats.batt_charge_status = battery.charge_status;
         stats.batt_charge_percent = battery.charge_percent;
@@ -101,12 +175,31 @@ fn fill_battery_stats(stats: &mut CurrentStats) -> Result<(), String> {
         stats.batt_cycle_count_cycles = battery.cycle_count_cycles;
         stats.batt_temperature_c = battery.temperature_c;
         stats.power_draw_mw = battery.power_draw_mw;
+        stats.batteries = battery.batteries;
     } else {
         stats.batt_charge_status = "na".to_string();
     }
     Ok(())
 }
 
+// Fills the adapter fields and returns whether the system is on AC. Without a
+// Mains/USB supply this falls back to the battery status; None if that is
+// inconclusive too ("na").
+fn fill_adapter_stats(sysfs: &SysfsRoot, stats: &mut CurrentStats) -> Option<bool> {
+    let adapter = read_adapter_snapshot(sysfs).ok().flatten();
+    let on_ac = match &adapter {
+        Some(adapter) => Some(adapter.online),
+        None => match stats.batt_charge_status.as_str() {
+            "discharging" | "empty" => Some(false),
+            "charging" | "full" => Some(true),
+            _ => None,
+        },
+    };
+    stats.on_ac = on_ac.unwrap_or(true);
+    stats.ac_adapter_power_mw = adapter.map(|a| a.power_mw).unwrap_or(-1);
+    on_ac
+}
+
 // Computes runtime estimates from battery fields already in stats.
 fn fill_runtime_estimates(stats: &mut CurrentStats) {
     let current_capacity_mwh = stats.batt_current_capacity_mwh.max(0);
@@ -135,41 +228,61 @@ fn fill_runtime_estimates(stats: &mut CurrentStats) {
 
 // ─── RyzenAdj data ──────────────────────────────────────────────────────────
 // Refreshes SMU table and fills CPU, GPU, power, and limit fields.
-fn fill_ryzenadj_stats(adj: &RyzenAdj, stats: &mut CurrentStats) -> Result<(), String> {
-    adj.refresh()
-        .map_err(|e| format!("refresh failed: {:?}", e))?;
+fn fill_ryzenadj_stats<B: PowerBackend + ?Sized>(
+    backend: &B,
+    sysfs: &SysfsRoot,
+    stats: &mut CurrentStats,
+) -> Result<(), String> {
+    backend.refresh()?;
+    let telemetry = backend.read_telemetry()?;
+    let limits = backend.read_limits()?;
 
     // CPU frequency: max across all cores (MHz)
     // ryzenadj returns MHz as float
-    let mut max_core_clk: f32 = 0.0;
-    for core in 0..32u32 {
-        match adj.get_core_clk(core) {
-            Ok(clk) if clk > 0.0 => max_core_clk = max_core_clk.max(clk),
-            _ => break,
-        }
-    }
+    let max_core_clk = telemetry
+        .core_clks_mhz
+        .iter()
+        .fold(0.0f32, |max, clk| max.max(*clk));
     stats.cpu_frequency_mhz = max_core_clk.round() as i32;
 
+    // Per core. The SMU table can have more slots than the CPU has cores, so
+    // trim to the physical cores sysfs lists. Without a topology every slot
+    // that read is kept: parked cores read 0, and the count must not change
+    // from tick to tick.
+    let ccx = read_core_ccx(sysfs);
+    let core_count = if ccx.is_empty() {
+        telemetry.core_clks_mhz.len()
+    } else {
+        ccx.len().min(telemetry.core_clks_mhz.len())
+    };
+    let per_core = |values: &[f32], scale: f32| -> Vec<i32> {
+        values.iter().take(core_count).map(|v| (v * scale).round() as i32).collect()
+    };
+    stats.core_clocks_mhz = per_core(&telemetry.core_clks_mhz, 1.0);
+    stats.core_powers_mw = per_core(&telemetry.core_powers_w, 1000.0);
+    stats.core_temperatures_c = per_core(&telemetry.core_temps_c, 1.0);
+    stats.core_ccx = ccx.into_iter().take(core_count).collect();
+
     // CPU temperature (tctl value, celsius)
-    stats.cpu_temperature_c = adj.get_tctl_temp_value().unwrap_or(0.0).round() as i32;
+    stats.cpu_temperature_c = telemetry.tctl_value_c.round() as i32;
 
     // CPU load (cclk busy percentage)
-    stats.cpu_load_percent = adj.get_cclk_busy_value().unwrap_or(0.0).round() as i32;
+    stats.cpu_load_percent = telemetry.cclk_busy_percent.round() as i32;
 
     // GPU
-    stats.gpu_frequency_mhz = adj.get_gfx_clk().unwrap_or(0.0).round() as i32;
-    stats.gpu_temperature_c = adj.get_gfx_temp().unwrap_or(0.0).round() as i32;
-    stats.gpu_load_percent = read_gpu_load_percent();
+    stats.gpu_frequency_mhz = telemetry.gfx_clk_mhz.round() as i32;
+    stats.gpu_temperature_c = telemetry.gfx_temp_c.round() as i32;
+    stats.gpu_load_percent = read_gpu_load_percent(sysfs);
 
     // Power limits - ryzenadj returns watts (float), we store milliwatts (i32)
-    stats.curr_fast_limit_mw = watts_to_mw(adj.get_fast_limit().unwrap_or(0.0));
-    stats.curr_fast_value_mw = watts_to_mw(adj.get_fast_value().unwrap_or(0.0));
-    stats.curr_slow_limit_mw = watts_to_mw(adj.get_slow_limit().unwrap_or(0.0));
-    stats.curr_slow_value_mw = watts_to_mw(adj.get_slow_value().unwrap_or(0.0));
-    stats.curr_stapm_limit_mw = watts_to_mw(adj.get_stapm_limit().unwrap_or(0.0));
-    stats.curr_stapm_value_mw = watts_to_mw(adj.get_stapm_value().unwrap_or(0.0));
-    stats.curr_tctl_limit_c = adj.get_tctl_temp().unwrap_or(0.0).round() as i32;
-    stats.curr_tctl_value_c = adj.get_tctl_temp_value().unwrap_or(0.0).round() as i32;
+    stats.curr_fast_limit_mw = watts_to_mw(limits.fast_w);
+    stats.curr_fast_value_mw = watts_to_mw(telemetry.fast_value_w);
+    stats.curr_slow_limit_mw = watts_to_mw(limits.slow_w);
+    stats.curr_slow_value_mw = watts_to_mw(telemetry.slow_value_w);
+    stats.curr_stapm_limit_mw = watts_to_mw(limits.stapm_w);
+    stats.curr_stapm_value_mw = watts_to_mw(telemetry.stapm_value_w);
+    stats.curr_tctl_limit_c = limits.tctl_c.round() as i32;
+    stats.curr_tctl_value_c = telemetry.tctl_value_c.round() as i32;
 
     // Percentages (value / limit * 100), clamped 0..100
     stats.curr_fast_percent = safe_percent(stats.curr_fast_value_mw, stats.curr_fast_limit_mw);
@@ -180,10 +293,42 @@ fn fill_ryzenadj_stats(adj: &RyzenAdj, stats: &mut CurrentStats) -> Result<(), S
     Ok(())
 }
 
+// L3 cache id of each physical core, ordered by core id like the SMU table.
+// Empty if the topology isn't readable.
+pub fn read_core_ccx(sysfs: &SysfsRoot) -> Vec<i32> {
+    let Ok(entries) = fs::read_dir(sysfs.cpu_dir()) else {
+        return Vec::new();
+    };
+    let read = |path: std::path::PathBuf| -> Option<i32> { fs::read_to_string(path).ok()?.trim().parse().ok() };
+
+    // (package, core id) -> L3 id; SMT siblings share a core id
+    let mut cores: Vec<((i32, i32), i32)> = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let is_cpu = name.strip_prefix("cpu").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if !is_cpu {
+            continue;
+        }
+        let dir = entry.path();
+        let (Some(package), Some(core)) = (
+            read(dir.join("topology/physical_package_id")),
+            read(dir.join("topology/core_id")),
+        ) else {
+            continue;
+        };
+        let l3 = read(dir.join("cache/index3/id")).unwrap_or(-1);
+        if !cores.iter().any(|(key, _)| *key == (package, core)) {
+            cores.push(((package, core), l3));
+        }
+    }
+    cores.sort();
+    cores.into_iter().map(|(_, l3)| l3).collect()
+}
+
 // Reads iGPU load from sysfs (not available via ryzenadj).
-fn read_gpu_load_percent() -> i32 {
+pub fn read_gpu_load_percent(sysfs: &SysfsRoot) -> i32 {
     for card in &["card0", "card1", "card2"] {
-        let path = format!("/sys/class/drm/{}/device/gpu_busy_percent", card);
+        let path = sysfs.drm_dir().join(card).join("device/gpu_busy_percent");
         if let Ok(content) = fs::read_to_string(&path) {
             if let Ok(val) = content.trim().parse::<i32>() {
                 return val.clamp(0, 100);
@@ -194,72 +339,145 @@ fn read_gpu_load_percent() -> i32 {
 }
 
 // ─── Profile resolution ─────────────────────────────────────────────────────
-// Determines which PowerLimits should be active based on profile settings,
-// battery status, and (for "system" mode) the OS power profile.
-// Returns (target_limits, profile_name, sub_profile_name).
-fn resolve_target_profile(
-    profile_settings: &ProfileSettings,
-    battery_status: &str,
-    battery_percent: i32,
-) -> (PowerLimits, String, String) {
-    match profile_settings.active_profile.as_str() {
-        "system" => {
-            let os_profile = read_system_power_profile();
-            let (limits, sub) = match os_profile.as_str() {
-                "performance" => (profile_settings.system.performance.clone(), "performance"),
-                "power_saver" => (profile_settings.system.power_saver.clone(), "power_saver"),
-                _ => (profile_settings.system.balanced.clone(), "balanced"),
-            };
-            (limits, "system".to_string(), sub.to_string())
+// Determines which PowerLimits should be active. The profile comes from the
+// first matching app rule, then the schedule, then active_profile; its mode
+// then picks the sub-profile: AC adapter and battery level for power_source,
+// the OS power profile for platform_profile.
+// Battery-driven sub-profiles go through a small state machine kept across
+// ticks, so a charger hiccup or a battery hovering at the threshold doesn't
+// make the limits thrash:
+// - AC vs battery comes from the adapter's online flag; when that can't be
+//   determined the last known power source is kept
+// - low_batt is entered below the threshold and left above threshold +
+//   low_batt_hysteresis_percent
+// - a new sub-profile has to hold for min_dwell_s before it's applied
+// It also remembers which charge thresholds the firmware refused, and which
+// limits were skipped for being outside the chip's range.
+#[derive(Default)]
+pub struct ProfileResolver {
+    on_battery: bool,
+    low_battery: bool,
+    current: Option<(String, String)>, // (profile, sub-profile) in effect
+    pending: Option<(String, Instant)>, // sub-profile waiting out the dwell time
+    refused_charge_targets: RefusedChargeTargets,
+    out_of_range_limits: Vec<LimitError>, // last ones logged as skipped
+}
+
+impl ProfileResolver {
+    // Returns (target_limits, profile_name, sub_profile_name); the sub-profile
+    // name says why the profile was picked, e.g. "fixed (rule: blender)".
+    pub fn resolve_target_profile(
+        &mut self,
+        profile_settings: &ProfileSettings,
+        sysfs: &SysfsRoot,
+        on_ac: Option<bool>,
+        battery_percent: i32,
+        processes: &[ProcessInfo],
+        clock: &dyn Clock,
+    ) -> (PowerLimits, String, String) {
+        let rule = find_matching_rule(profile_settings, processes);
+        let scheduled = active_schedule_entry(profile_settings, clock);
+
+        let (profile, reason) = match (rule, scheduled) {
+            (Some(rule), _) => (
+                profile_settings.profile(&rule.profile),
+                Some(format!("rule: {}", rule.name)),
+            ),
+            (None, Some(entry)) => (
+                profile_settings.profile(&entry.profile),
+                Some(format!("schedule: {}", entry.label())),
+            ),
+            (None, None) => (profile_settings.active(), None),
+        };
+        let Some(profile) = profile else {
+            return (PowerLimits::default(), String::new(), String::new());
+        };
+
+        let sub = match profile.mode {
+            ProfileMode::PlatformProfile => read_system_power_profile(sysfs),
+            ProfileMode::PowerSource => {
+                let wanted = self.power_source_sub_profile(profile, on_ac, battery_percent);
+                self.settle(profile, wanted, clock.instant())
+            }
+            ProfileMode::Fixed => "fixed".to_string(),
+        };
+        let limits = profile.limits(&sub).cloned().unwrap_or_default();
+        let next = (profile.name.clone(), sub.clone());
+        let switched = self.current.as_ref().is_some_and(|current| *current != next);
+        self.current = Some(next);
+
+        let sub = match reason {
+            Some(reason) => format!("{} ({})", sub, reason),
+            None => sub,
+        };
+        if switched {
+            log_info("profile", format!("Switched to {} / {}", profile.name, sub));
+        }
+        (limits, profile.name.clone(), sub)
+    }
+
+    // The sub-profile the battery asks for right now, with hysteresis
+    fn power_source_sub_profile(&mut self, profile: &Profile, on_ac: Option<bool>, battery_percent: i32) -> String {
+        // Unknown (no adapter and an "na" battery status): keep the last source
+        if let Some(on_ac) = on_ac {
+            self.on_battery = !on_ac;
         }
-        "custom" => {
-            let is_on_battery = battery_status == "discharging";
-            let is_low_battery =
-                is_on_battery && battery_percent < profile_settings.low_batt_threshold_percent;
-
-            if is_low_battery {
-                (
-                    profile_settings.custom.low_batt.clone(),
-                    "custom".to_string(),
-                    "low_batt".to_string(),
-                )
-            } else if is_on_battery {
-                (
-                    profile_settings.custom.batt.clone(),
-                    "custom".to_string(),
-                    "batt".to_string(),
-                )
+
+        let enter_below = profile.low_batt_threshold_percent;
+        let leave_above = enter_below + profile.low_batt_hysteresis_percent.max(0);
+        self.low_battery = self.on_battery
+            && if self.low_battery {
+                battery_percent <= leave_above
             } else {
-                (
-                    profile_settings.custom.ac.clone(),
-                    "custom".to_string(),
-                    "ac".to_string(),
-                )
+                battery_percent < enter_below
+            };
+
+        if self.low_battery {
+            "low_batt".to_string()
+        } else if self.on_battery {
+            "batt".to_string()
+        } else {
+            "ac".to_string()
+        }
+    }
+
+    // Applies `wanted` once it has been asked for continuously for the dwell
+    // time. Switching profiles (or the first tick) applies it straight away.
+    fn settle(&mut self, profile: &Profile, wanted: String, now: Instant) -> String {
+        let current = match &self.current {
+            Some((name, sub)) if *name == profile.name => sub.clone(),
+            _ => {
+                self.pending = None;
+                return wanted;
+            }
+        };
+        if wanted == current {
+            self.pending = None;
+            return current;
+        }
+
+        let since = match &self.pending {
+            Some((sub, since)) if *sub == wanted => *since,
+            _ => {
+                self.pending = Some((wanted.clone(), now));
+                now
             }
+        };
+        let dwell = Duration::from_secs(profile.min_dwell_s as u64);
+        if now.duration_since(since) >= dwell {
+            self.pending = None;
+            wanted
+        } else {
+            current
         }
-        "turbo" => (
-            profile_settings.turbo.turbo.clone(),
-            "turbo".to_string(),
-            "turbo".to_string(),
-        ),
-        "fixed" => (
-            profile_settings.fixed.fixed.clone(),
-            "fixed".to_string(),
-            "fixed".to_string(),
-        ),
-        _ => (
-            profile_settings.system.balanced.clone(),
-            "system".to_string(),
-            "balanced".to_string(),
-        ),
     }
 }
 
 // Reads the OS-level power profile. Tries sysfs first, falls back to
-// powerprofilesctl, defaults to "balanced".
-fn read_system_power_profile() -> String {
+// powerprofilesctl (only for the live /sys), defaults to "balanced".
+pub fn read_system_power_profile(sysfs: &SysfsRoot) -> String {
     // Try sysfs platform_profile first (works on most AMD laptops)
-    if let Ok(content) = fs::read_to_string("/sys/firmware/acpi/platform_profile") {
+    if let Ok(content) = fs::read_to_string(sysfs.platform_profile_file()) {
         let profile = content.trim().to_lowercase();
         return match profile.as_str() {
             "low-power" => "power_saver".to_string(),
@@ -270,6 +488,9 @@ fn read_system_power_profile() -> String {
     }
 
     // Fallback: query power-profiles-daemon via CLI
+    if !sysfs.is_live() {
+        return "balanced".to_string();
+    }
     if let Ok(output) = Command::new("powerprofilesctl").arg("get").output() {
         if output.status.success() {
             let profile = String::from_utf8_lossy(&output.stdout)
@@ -290,41 +511,130 @@ fn read_system_power_profile() -> String {
 
 // ─── Profile enforcement ────────────────────────────────────────────────────
 // Compares current ryzenadj limits against target. Applies via set_* if they differ.
-fn enforce_profile_limits(
-    adj: &RyzenAdj,
+// Limits missing from the target are left alone.
+pub fn enforce_profile_limits<B: PowerBackend + ?Sized>(
+    backend: &B,
     stats: &CurrentStats,
     target: &PowerLimits,
 ) -> Result<(), String> {
+    target
+        .validate()
+        .map_err(|errors| format!("refusing invalid limits: {}", format_limit_errors(&errors)))?;
+
     let tolerance_mw = 500; // 500 mW tolerance for power limits
     let tolerance_c = 1; // 1 °C tolerance for temperature
 
-    let fast_diff = (stats.curr_fast_limit_mw - target.fast_mw).abs() > tolerance_mw;
-    let slow_diff = (stats.curr_slow_limit_mw - target.slow_mw).abs() > tolerance_mw;
-    let stapm_diff = (stats.curr_stapm_limit_mw - target.stapm_mw).abs() > tolerance_mw;
-    let tctl_diff = (stats.curr_tctl_limit_c - target.temp_c).abs() > tolerance_c;
+    // Unset limits are never written
+    let differs = |current: i32, target: Option<i32>, tolerance: i32| {
+        target.filter(|t| (current - t).abs() > tolerance)
+    };
 
-    if !(fast_diff || slow_diff || stapm_diff || tctl_diff) {
-        return Ok(()); // All within tolerance, nothing to do
+    if let Some(mw) = differs(stats.curr_fast_limit_mw, target.fast_mw, tolerance_mw) {
+        backend.set_fast_limit(mw as u32)?;
+    }
+    if let Some(mw) = differs(stats.curr_slow_limit_mw, target.slow_mw, tolerance_mw) {
+        backend.set_slow_limit(mw as u32)?;
     }
+    if let Some(mw) = differs(stats.curr_stapm_limit_mw, target.stapm_mw, tolerance_mw) {
+        backend.set_stapm_limit(mw as u32)?;
+    }
+    if let Some(c) = differs(stats.curr_tctl_limit_c, target.temp_c, tolerance_c) {
+        backend.set_tctl_temp(c as u32)?;
+    }
+
+    Ok(())
+}
 
-    if fast_diff {
-        adj.set_fast_limit(target.fast_mw as u32)
-            .map_err(|e| format!("set_fast_limit: {:?}", e))?;
+// ─── Charge threshold enforcement ────────────────────────────────────────────
+// Puts the configured thresholds on every battery that supports them, and
+// ends a "charge to 100% once" override when the battery is full. A target
+// the firmware refused is not retried until the settings change, but keeps
+// being reported.
+
+type ChargeTarget = (String, Option<i32>, i32); // battery, start, end
+
+#[derive(Default)]
+pub struct RefusedChargeTargets {
+    thresholds: ChargeThresholds, // settings the refusals were made under
+    targets: Vec<(ChargeTarget, String)>,
+}
+
+pub fn enforce_charge_thresholds<B: PowerBackend + ?Sized>(
+    backend: &B,
+    sysfs: &SysfsRoot,
+    profile_settings: &ProfileSettings,
+    stats: &CurrentStats,
+    refused: &mut RefusedChargeTargets,
+) -> Result<(), String> {
+    let thresholds = &profile_settings.charge_thresholds;
+    if refused.thresholds != *thresholds {
+        *refused = RefusedChargeTargets { thresholds: thresholds.clone(), targets: Vec::new() };
     }
-    if slow_diff {
-        adj.set_slow_limit(target.slow_mw as u32)
-            .map_err(|e| format!("set_slow_limit: {:?}", e))?;
+    if thresholds.full_charge_once && reached_full_charge(sysfs, stats) {
+        // The configured thresholds come back once the file is reloaded
+        return finish_full_charge().map_err(|e| e.to_string());
     }
-    if stapm_diff {
-        adj.set_stapm_limit(target.stapm_mw as u32)
-            .map_err(|e| format!("set_stapm_limit: {:?}", e))?;
+
+    let Some((start, end)) = thresholds.target() else {
+        return Ok(());
+    };
+    thresholds.validate()?;
+
+    let supported: Vec<_> = stats
+        .batteries
+        .iter()
+        .filter(|b| b.charge_end_threshold_percent.is_some())
+        .collect();
+    if supported.is_empty() {
+        return Err("this hardware doesn't support charge thresholds".to_string());
     }
-    if tctl_diff {
-        adj.set_tctl_temp(target.temp_c as u32)
-            .map_err(|e| format!("set_tctl_temp: {:?}", e))?;
+
+    let mut errors = Vec::new();
+    for battery in supported {
+        // Batteries without a start threshold only get the end one
+        let start_ok = start.is_none()
+            || battery.charge_start_threshold_percent.is_none()
+            || battery.charge_start_threshold_percent == start;
+        if battery.charge_end_threshold_percent == Some(end) && start_ok {
+            continue;
+        }
+
+        let target = (battery.name.clone(), start, end);
+        if let Some((_, message)) = refused.targets.iter().find(|(t, _)| *t == target) {
+            errors.push(message.clone());
+            continue;
+        }
+        if let Err(e) = backend.set_charge_thresholds(&battery.name, start, end) {
+            log_error("battery", &e);
+            refused.targets.push((target, e.clone()));
+            errors.push(e);
+        }
     }
 
-    Ok(())
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+// "Not charging" also normalizes to full, but it's what a battery held at its
+// end threshold reports. Only 100%, or the driver's own "Full" once the
+// lifted thresholds are in place, counts as a full charge.
+fn reached_full_charge(sysfs: &SysfsRoot, stats: &CurrentStats) -> bool {
+    if stats.batt_charge_percent >= 100 {
+        return true;
+    }
+    let mut batteries = stats
+        .batteries
+        .iter()
+        .filter(|b| b.charge_end_threshold_percent.is_some())
+        .peekable();
+    batteries.peek().is_some()
+        && batteries.all(|b| {
+            let status = fs::read_to_string(sysfs.power_supply_dir().join(&b.name).join("status"));
+            b.charge_end_threshold_percent == Some(100) && status.is_ok_and(|s| s.trim() == "Full")
+        })
 }
 
 // ─── Helpers ─────────────────────────────────────────────────────────────────
@@ -340,3 +650,282 @@ fn safe_percent(value: i32, limit: i32) -> i32 {
         ((value as i64 * 100) / limit as i64).clamp(0, 100) as i32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::utils::backend::{BackendWrite, FakeBackend, Telemetry};
+    use crate::utils::schedule::LocalTime;
+    use crate::utils::types::{BatteryInfo, Weekday};
+
+    fn stats_with_limits(fast_mw: i32, slow_mw: i32, stapm_mw: i32, tctl_c: i32) -> CurrentStats {
+        CurrentStats {
+            curr_fast_limit_mw: fast_mw,
+            curr_slow_limit_mw: slow_mw,
+            curr_stapm_limit_mw: stapm_mw,
+            curr_tctl_limit_c: tctl_c,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unset_limits_are_not_written() {
+        let backend = FakeBackend::default();
+        let stats = stats_with_limits(30_000, 25_000, 20_000, 95);
+        let target = PowerLimits { fast_mw: Some(20_000), ..Default::default() };
+
+        enforce_profile_limits(&backend, &stats, &target).unwrap();
+        assert_eq!(backend.writes(), vec![BackendWrite::FastLimit(20_000)]);
+
+        backend.clear_writes();
+        enforce_profile_limits(&backend, &stats, &PowerLimits::default()).unwrap();
+        assert!(backend.writes().is_empty());
+    }
+
+    #[test]
+    fn values_within_tolerance_are_not_rewritten() {
+        let backend = FakeBackend::default();
+        let stats = stats_with_limits(30_000, 25_000, 20_000, 95);
+        let target = PowerLimits {
+            fast_mw: Some(30_400),
+            slow_mw: Some(24_600),
+            stapm_mw: Some(20_500),
+            temp_c: Some(94),
+        };
+        enforce_profile_limits(&backend, &stats, &target).unwrap();
+        assert!(backend.writes().is_empty());
+
+        let target = PowerLimits { stapm_mw: Some(20_501), temp_c: Some(93), ..target };
+        enforce_profile_limits(&backend, &stats, &target).unwrap();
+        assert_eq!(
+            backend.writes(),
+            vec![BackendWrite::StapmLimit(20_501), BackendWrite::TctlTemp(93)]
+        );
+    }
+
+    #[test]
+    fn invalid_targets_are_refused() {
+        let backend = FakeBackend::default();
+        let stats = stats_with_limits(30_000, 25_000, 20_000, 95);
+
+        let out_of_range = PowerLimits { fast_mw: Some(500_000), ..Default::default() };
+        assert!(enforce_profile_limits(&backend, &stats, &out_of_range).is_err());
+
+        let slow_above_fast = PowerLimits { fast_mw: Some(15_000), slow_mw: Some(20_000), ..Default::default() };
+        assert!(enforce_profile_limits(&backend, &stats, &slow_above_fast).is_err());
+
+        let too_hot = PowerLimits { temp_c: Some(110), ..Default::default() };
+        assert!(enforce_profile_limits(&backend, &stats, &too_hot).is_err());
+
+        assert!(backend.writes().is_empty());
+    }
+
+    #[test]
+    fn parked_cores_keep_their_slot() {
+        let backend = FakeBackend::default();
+        let sysfs = SysfsRoot::new("/nonexistent"); // no topology
+        let mut stats = CurrentStats::default();
+
+        for clocks in [vec![3000.0, 2800.0, 0.0, 0.0], vec![3000.0, 0.0, 0.0, 0.0]] {
+            backend.set_telemetry(Telemetry { core_clks_mhz: clocks, ..Default::default() });
+            fill_ryzenadj_stats(&backend, &sysfs, &mut stats).unwrap();
+            assert_eq!(stats.core_clocks_mhz.len(), 4);
+        }
+        assert_eq!(stats.core_clocks_mhz, vec![3000, 0, 0, 0]);
+    }
+
+    // ─── Charge thresholds ───────────────────────────────────────────────────
+
+    fn settings_with_end_threshold(end_percent: i32) -> ProfileSettings {
+        ProfileSettings {
+            charge_thresholds: ChargeThresholds { end_percent: Some(end_percent), ..Default::default() },
+            ..power_source_settings()
+        }
+    }
+
+    fn stats_with_battery(percent: i32, end_threshold: i32) -> CurrentStats {
+        CurrentStats {
+            batt_charge_status: "full".to_string(), // what "Not charging" normalizes to
+            batt_charge_percent: percent,
+            batteries: vec![BatteryInfo {
+                name: "BAT0".to_string(),
+                charge_percent: percent,
+                charge_end_threshold_percent: Some(end_threshold),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn refused_charge_target_is_retried_after_settings_change() {
+        let backend = FakeBackend::default();
+        let sysfs = SysfsRoot::new("/nonexistent");
+        let stats = stats_with_battery(70, 100);
+        let mut refused = RefusedChargeTargets::default();
+
+        backend.set_fail_writes(true);
+        let settings = settings_with_end_threshold(80);
+        assert!(enforce_charge_thresholds(&backend, &sysfs, &settings, &stats, &mut refused).is_err());
+
+        // Still reported, but not retried while the settings stay the same
+        backend.set_fail_writes(false);
+        assert!(enforce_charge_thresholds(&backend, &sysfs, &settings, &stats, &mut refused).is_err());
+        assert!(backend.writes().is_empty());
+
+        // Changing them (even back again) tries afresh
+        let other = settings_with_end_threshold(60);
+        enforce_charge_thresholds(&backend, &sysfs, &other, &stats, &mut refused).unwrap();
+        enforce_charge_thresholds(&backend, &sysfs, &settings, &stats, &mut refused).unwrap();
+        let ends: Vec<_> = backend
+            .writes()
+            .into_iter()
+            .map(|write| match write {
+                BackendWrite::ChargeThresholds { end_percent, .. } => end_percent,
+                other => panic!("unexpected write {:?}", other),
+            })
+            .collect();
+        assert_eq!(ends, vec![60, 80]);
+    }
+
+    #[test]
+    fn not_charging_does_not_end_full_charge() {
+        let sysfs = SysfsRoot::new("/nonexistent");
+        // Held below 100% with the thresholds lifted: still charging up
+        assert!(!reached_full_charge(&sysfs, &stats_with_battery(97, 100)));
+        assert!(reached_full_charge(&sysfs, &stats_with_battery(100, 100)));
+    }
+
+    // ─── Profile resolver ────────────────────────────────────────────────────
+
+    // Wall time stays put; the monotonic clock only moves when told to
+    struct FixedClock {
+        start: Instant,
+        elapsed: Cell<Duration>,
+    }
+
+    impl FixedClock {
+        fn new() -> Self {
+            Self { start: Instant::now(), elapsed: Cell::new(Duration::ZERO) }
+        }
+
+        fn advance(&self, secs: u64) {
+            self.elapsed.set(self.elapsed.get() + Duration::from_secs(secs));
+        }
+    }
+
+    impl Clock for FixedClock {
+        fn now(&self) -> LocalTime {
+            LocalTime { weekday: Weekday::Mon, minute: 12 * 60 }
+        }
+
+        fn instant(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+    }
+
+    // One power-source profile: threshold 20%, hysteresis 5%, dwell 30 s
+    fn power_source_settings() -> ProfileSettings {
+        ProfileSettings {
+            active_profile: "auto".to_string(),
+            profiles: vec![Profile::new("auto", "", ProfileMode::PowerSource)],
+            app_rules: Vec::new(),
+            schedule: Vec::new(),
+            charge_thresholds: Default::default(),
+        }
+    }
+
+    fn resolve(resolver: &mut ProfileResolver, clock: &FixedClock, on_ac: Option<bool>, percent: i32) -> String {
+        let settings = power_source_settings();
+        let sysfs = SysfsRoot::new("/nonexistent");
+        resolver.resolve_target_profile(&settings, &sysfs, on_ac, percent, &[], clock).2
+    }
+
+    #[test]
+    fn first_tick_applies_immediately() {
+        let clock = FixedClock::new();
+        let mut resolver = ProfileResolver::default();
+        assert_eq!(resolve(&mut resolver, &clock, Some(false), 50), "batt");
+    }
+
+    #[test]
+    fn power_source_flapping_is_ignored() {
+        let clock = FixedClock::new();
+        let mut resolver = ProfileResolver::default();
+        assert_eq!(resolve(&mut resolver, &clock, Some(true), 80), "ac");
+
+        // A charger hiccup shorter than the dwell time never switches
+        for _ in 0..5 {
+            clock.advance(5);
+            assert_eq!(resolve(&mut resolver, &clock, Some(false), 80), "ac");
+            clock.advance(5);
+            assert_eq!(resolve(&mut resolver, &clock, Some(true), 80), "ac");
+        }
+
+        // An unknown power source keeps the last known one
+        clock.advance(60);
+        assert_eq!(resolve(&mut resolver, &clock, None, 80), "ac");
+    }
+
+    #[test]
+    fn switch_waits_for_dwell_time() {
+        let clock = FixedClock::new();
+        let mut resolver = ProfileResolver::default();
+        assert_eq!(resolve(&mut resolver, &clock, Some(true), 80), "ac");
+
+        assert_eq!(resolve(&mut resolver, &clock, Some(false), 80), "ac");
+        clock.advance(29);
+        assert_eq!(resolve(&mut resolver, &clock, Some(false), 80), "ac");
+        clock.advance(1);
+        assert_eq!(resolve(&mut resolver, &clock, Some(false), 80), "batt");
+
+        // Going back restarts the wait
+        clock.advance(10);
+        assert_eq!(resolve(&mut resolver, &clock, Some(true), 80), "batt");
+        clock.advance(30);
+        assert_eq!(resolve(&mut resolver, &clock, Some(true), 80), "ac");
+    }
+
+    #[test]
+    fn low_battery_has_hysteresis() {
+        let clock = FixedClock::new();
+        let mut resolver = ProfileResolver::default();
+        // Ask twice, a dwell time apart, to see where the resolver settles
+        let mut settled = |on_ac: bool, percent: i32| {
+            resolve(&mut resolver, &clock, Some(on_ac), percent);
+            clock.advance(30);
+            resolve(&mut resolver, &clock, Some(on_ac), percent)
+        };
+
+        assert_eq!(settled(false, 20), "batt");
+        assert_eq!(settled(false, 19), "low_batt");
+        // Climbing back over the threshold isn't enough...
+        assert_eq!(settled(false, 21), "low_batt");
+        assert_eq!(settled(false, 25), "low_batt");
+        // ...it has to clear threshold + hysteresis
+        assert_eq!(settled(false, 26), "batt");
+        // low_batt only applies on battery
+        assert_eq!(settled(true, 5), "ac");
+    }
+
+    fn fixture(name: &str) -> SysfsRoot {
+        SysfsRoot::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/sysfs").join(name))
+    }
+
+    // The "OS profile" and "GPU load" columns of fixtures/README.md
+    #[test]
+    fn fixture_profile_and_gpu_load() {
+        for (name, profile, gpu_load) in [
+            ("energy-power-now", "power_saver", 37),
+            ("charge-no-power-now", "performance", 100),
+            ("no-power-reading", "balanced", 0),
+            ("dual-battery", "balanced", 0),
+        ] {
+            let sysfs = fixture(name);
+            assert_eq!(read_system_power_profile(&sysfs), profile, "fixture {}", name);
+            assert_eq!(read_gpu_load_percent(&sysfs), gpu_load, "fixture {}", name);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+
+// Every sysfs read in the app goes through a SysfsRoot so the readers can be
+// pointed at a dump of another machine (see fixtures/sysfs) instead of /sys.
+const SYSFS_ROOT_ENV: &str = "RYZONE_SYSFS_ROOT";
+const DEFAULT_SYSFS_ROOT: &str = "/sys";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SysfsRoot {
+    path: PathBuf,
+}
+
+impl SysfsRoot {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    // Uses RYZONE_SYSFS_ROOT when set, /sys otherwise.
+    pub fn from_env() -> Self {
+        match std::env::var(SYSFS_ROOT_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::new(path.trim()),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // True for the real /sys. Readings that don't come from sysfs (e.g.
+    // powerprofilesctl) describe this machine, so they only make sense then.
+    pub fn is_live(&self) -> bool {
+        self.path == Path::new(DEFAULT_SYSFS_ROOT)
+    }
+
+    // Joins a path relative to the sysfs mount, e.g. "class/power_supply".
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.path.join(relative)
+    }
+
+    pub fn power_supply_dir(&self) -> PathBuf {
+        self.join("class/power_supply")
+    }
+
+    pub fn drm_dir(&self) -> PathBuf {
+        self.join("class/drm")
+    }
+
+    pub fn platform_profile_file(&self) -> PathBuf {
+        self.join("firmware/acpi/platform_profile")
+    }
+
+    pub fn cpu_dir(&self) -> PathBuf {
+        self.join("devices/system/cpu")
+    }
+
+    pub fn ryzen_smu_dir(&self) -> PathBuf {
+        self.join("kernel/ryzen_smu_drv")
+    }
+}
+
+impl Default for SysfsRoot {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYSFS_ROOT)
+    }
+}
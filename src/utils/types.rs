@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 // App Settings
@@ -6,6 +8,8 @@ pub struct AppSettings {
     pub units: Units,
     pub style: Style,
     pub app: App,
+    #[serde(default)]
+    pub metrics: MetricsRetention,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -28,58 +32,665 @@ pub struct App {
     pub enable_logging: bool,
     pub update_frequency_ms: i32, // milliseconds
     pub logging_frequency_ms: i32, // milliseconds
+    #[serde(default)]
+    pub log_format: LogFormat,
+}
+
+// File format of the stats log in ~/.ryzone/logs
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+// How long ~/.ryzone/metrics keeps each resolution; 0 stops recording it
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MetricsRetention {
+    pub raw_retention_hours: u32, // samples at logging_frequency_ms
+    pub minute_retention_days: u32, // 1-minute min/avg/max
+    pub hour_retention_days: u32, // 1-hour min/avg/max
+}
+
+impl Default for MetricsRetention {
+    fn default() -> Self {
+        Self {
+            raw_retention_hours: 24,
+            minute_retention_days: 7,
+            hour_retention_days: 90,
+        }
+    }
 }
 
 // Profile Settings
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ProfileSettings {
     pub active_profile: String,
-    pub low_batt_threshold_percent: i32,
-    pub system: SystemProfiles,
-    pub custom: CustomProfiles,
-    pub turbo: TurboProfile,
-    pub fixed: FixedProfile,
+    pub profiles: Vec<Profile>, // in display order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub app_rules: Vec<AppRule>, // checked in order, first match wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduleEntry>, // checked in order, first match wins
+    #[serde(default, skip_serializing_if = "ChargeThresholds::is_unset")]
+    pub charge_thresholds: ChargeThresholds,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct SystemProfiles {
-    pub performance: PowerLimits,
-    pub balanced: PowerLimits,
-    pub power_saver: PowerLimits,
+// Battery charge thresholds, applied to every battery that supports them.
+// Unset thresholds leave the battery alone.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ChargeThresholds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_percent: Option<i32>, // start charging below this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_percent: Option<i32>, // stop charging at this
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub full_charge_once: bool, // lift the thresholds until the battery is next full
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CustomProfiles {
-    pub ac: PowerLimits,
-    pub batt: PowerLimits,
-    pub low_batt: PowerLimits,
+impl ChargeThresholds {
+    // While charging to 100% once: start charging right away, stop when full
+    pub const FULL_CHARGE_START_PERCENT: i32 = 95;
+
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(start) = self.start_percent {
+            if !(0..=99).contains(&start) {
+                return Err(format!("start threshold {}% is outside 0-99%", start));
+            }
+            if self.end_percent.is_none() {
+                return Err("a start threshold needs an end threshold".to_string());
+            }
+        }
+        if let Some(end) = self.end_percent {
+            if !(1..=100).contains(&end) {
+                return Err(format!("end threshold {}% is outside 1-100%", end));
+            }
+            if self.start_percent.is_some_and(|start| start >= end) {
+                return Err("start threshold must be below the end threshold".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // (start, end) to write, or None to leave the batteries alone
+    pub fn target(&self) -> Option<(Option<i32>, i32)> {
+        if self.full_charge_once {
+            return Some((Some(Self::FULL_CHARGE_START_PERCENT), 100));
+        }
+        self.end_percent.map(|end| (self.start_percent, end))
+    }
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct TurboProfile {
-    pub turbo: PowerLimits,
+// Switches to `profile` while a matching program runs, overriding the active
+// profile. Matching lives in utils::processes.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct AppRule {
+    pub name: String,
+    pub profile: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>, // executable name, e.g. "blender"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<String>, // substring of the command line
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct FixedProfile {
-    pub fixed: PowerLimits,
+// Uses `profile` on the given days between `start` and `end` (local "HH:MM").
+// An `end` at or before `start` runs past midnight into the next day.
+// Evaluated in utils::schedule.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ScheduleEntry {
+    pub days: Vec<Weekday>,
+    pub start: String,
+    pub end: String,
+    pub profile: String,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    // 0 = Monday
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn previous(self) -> Weekday {
+        Weekday::ALL[(self.index() + 6) % 7]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Weekday::Mon => "Mon",
+            Weekday::Tue => "Tue",
+            Weekday::Wed => "Wed",
+            Weekday::Thu => "Thu",
+            Weekday::Fri => "Fri",
+            Weekday::Sat => "Sat",
+            Weekday::Sun => "Sun",
+        }
+    }
+}
+
+// A user-defined profile. `mode` decides which sub-profiles it has and which
+// one is in effect at any moment.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub mode: ProfileMode,
+    #[serde(default = "default_low_batt_threshold_percent")]
+    pub low_batt_threshold_percent: i32, // percentage, power_source mode only
+    #[serde(default = "default_low_batt_hysteresis_percent")]
+    pub low_batt_hysteresis_percent: i32, // low_batt is left above threshold + this
+    #[serde(default = "default_min_dwell_s")]
+    pub min_dwell_s: u32, // seconds a new sub-profile must hold before it's applied
+    #[serde(default)]
+    pub limits: BTreeMap<String, PowerLimits>, // keyed by sub-profile name
+}
+
+fn default_low_batt_threshold_percent() -> i32 {
+    20
+}
+
+fn default_low_batt_hysteresis_percent() -> i32 {
+    5
+}
+
+fn default_min_dwell_s() -> u32 {
+    30
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileMode {
+    PlatformProfile, // follows the OS power profile
+    PowerSource,     // AC, battery, low battery
+    Fixed,           // one set of limits all the time
+}
+
+impl ProfileMode {
+    pub const ALL: [ProfileMode; 3] = [
+        ProfileMode::PlatformProfile,
+        ProfileMode::PowerSource,
+        ProfileMode::Fixed,
+    ];
+
+    // Sub-profiles of each mode, in display order
+    pub fn sub_profile_names(self) -> &'static [&'static str] {
+        match self {
+            ProfileMode::PlatformProfile => &["performance", "balanced", "power_saver"],
+            ProfileMode::PowerSource => &["ac", "batt", "low_batt"],
+            ProfileMode::Fixed => &["fixed"],
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProfileMode::PlatformProfile => "platform_profile",
+            ProfileMode::PowerSource => "power_source",
+            ProfileMode::Fixed => "fixed",
+        }
+    }
+
+    pub fn from_name(mode: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == mode)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProfileMode::PlatformProfile => "Follow OS power profile",
+            ProfileMode::PowerSource => "AC / battery",
+            ProfileMode::Fixed => "Fixed",
+        }
+    }
+}
+
+impl Profile {
+    pub fn new(name: &str, description: &str, mode: ProfileMode) -> Self {
+        let mut profile = Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            mode,
+            low_batt_threshold_percent: default_low_batt_threshold_percent(),
+            low_batt_hysteresis_percent: default_low_batt_hysteresis_percent(),
+            min_dwell_s: default_min_dwell_s(),
+            limits: BTreeMap::new(),
+        };
+        profile.set_mode(mode);
+        profile
+    }
+
+    pub fn sub_profile_names(&self) -> &'static [&'static str] {
+        self.mode.sub_profile_names()
+    }
+
+    // Switching mode keeps the limits of sub-profiles that exist in both
+    pub fn set_mode(&mut self, mode: ProfileMode) {
+        self.mode = mode;
+        let subs = mode.sub_profile_names();
+        self.limits.retain(|sub, _| subs.contains(&sub.as_str()));
+        for sub in subs {
+            self.limits.entry(sub.to_string()).or_default();
+        }
+    }
+
+    pub fn limits(&self, sub_profile: &str) -> Option<&PowerLimits> {
+        if !self.sub_profile_names().contains(&sub_profile) {
+            return None;
+        }
+        self.limits.get(sub_profile)
+    }
+
+    pub fn limits_mut(&mut self, sub_profile: &str) -> Option<&mut PowerLimits> {
+        if !self.sub_profile_names().contains(&sub_profile) {
+            return None;
+        }
+        Some(self.limits.entry(sub_profile.to_string()).or_default())
+    }
+
+    // Invalid sub-profiles with their errors, in display order
+    pub fn limit_errors(&self) -> Vec<(&'static str, Vec<LimitError>)> {
+        self.sub_profile_names()
+            .iter()
+            .filter_map(|sub| {
+                let limits = self.limits.get(*sub).cloned().unwrap_or_default();
+                limits.validate().err().map(|errors| (*sub, errors))
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.limit_errors().first() {
+            None => Ok(()),
+            Some((sub, errors)) => Err(format!(
+                "{}.{}: {}",
+                self.name,
+                sub,
+                format_limit_errors(errors)
+            )),
+        }
+    }
+}
+
+impl ProfileSettings {
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn profile_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        self.profile(name).is_some()
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    // Falls back to the first profile if the active one no longer exists
+    pub fn active(&self) -> Option<&Profile> {
+        self.profile(&self.active_profile).or(self.profiles.first())
+    }
+
+    pub fn limits(&self, profile: &str, sub_profile: &str) -> Option<&PowerLimits> {
+        self.profile(profile)?.limits(sub_profile)
+    }
+
+    pub fn limits_mut(&mut self, profile: &str, sub_profile: &str) -> Option<&mut PowerLimits> {
+        self.profile_mut(profile)?.limits_mut(sub_profile)
+    }
+
+    // ─── Editing ─────────────────────────────────────────────────────────────
+    pub fn add_profile(&mut self, profile: Profile) -> Result<(), String> {
+        self.check_new_name(&profile.name)?;
+        self.profiles.push(profile);
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        if name == new_name {
+            return Ok(());
+        }
+        self.check_new_name(new_name)?;
+        let profile = self
+            .profile_mut(name)
+            .ok_or_else(|| format!("unknown profile '{}'", name))?;
+        profile.name = new_name.to_string();
+        if self.active_profile == name {
+            self.active_profile = new_name.to_string();
+        }
+        for rule in self.app_rules.iter_mut().filter(|r| r.profile == name) {
+            rule.profile = new_name.to_string();
+        }
+        for entry in self.schedule.iter_mut().filter(|e| e.profile == name) {
+            entry.profile = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    // Replaces profile `name` with `updated`, renaming it if the name changed
+    pub fn update_profile(&mut self, name: &str, updated: Profile) -> Result<(), String> {
+        self.rename_profile(name, &updated.name)?;
+        let profile = self
+            .profile_mut(&updated.name)
+            .ok_or_else(|| format!("unknown profile '{}'", name))?;
+        *profile = updated;
+        Ok(())
+    }
+
+    // The copy is inserted right after the original
+    pub fn duplicate_profile(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        self.check_new_name(new_name)?;
+        let index = self
+            .profiles
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| format!("unknown profile '{}'", name))?;
+        let mut copy = self.profiles[index].clone();
+        copy.name = new_name.to_string();
+        self.profiles.insert(index + 1, copy);
+        Ok(())
+    }
+
+    // The active profile has to be switched away from before it can go
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), String> {
+        if !self.has_profile(name) {
+            return Err(format!("unknown profile '{}'", name));
+        }
+        if self.profiles.len() == 1 {
+            return Err("can't delete the last profile".to_string());
+        }
+        if self.active_profile == name {
+            return Err(format!("can't delete the active profile '{}'; activate another one first", name));
+        }
+        self.profiles.retain(|p| p.name != name);
+        self.app_rules.retain(|r| r.profile != name);
+        self.schedule.retain(|e| e.profile != name);
+        Ok(())
+    }
+
+    // "base", then "base 2", "base 3", ... whichever is free first
+    pub fn unused_name(&self, base: &str) -> String {
+        if !self.has_profile(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} {}", base, n))
+            .find(|name| !self.has_profile(name))
+            .unwrap()
+    }
+
+    // Names end up in `<profile>.<sub>` targets for ryzonectl, so no dots
+    fn check_new_name(&self, name: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("profile name can't be empty".to_string());
+        }
+        if name.trim() != name || name.contains('.') {
+            return Err(format!(
+                "invalid profile name '{}': no dots or surrounding spaces",
+                name
+            ));
+        }
+        if self.has_profile(name) {
+            return Err(format!("a profile named '{}' already exists", name));
+        }
+        Ok(())
+    }
+}
+
+// A missing limit is left as it is on the hardware. Files from before limits
+// were optional used 0 for "not set", so 0 is read as missing too.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
 pub struct PowerLimits {
-    pub fast_mw: i32, // milliwatts
-    pub slow_mw: i32, // milliwatts
-    pub stapm_mw: i32, // milliwatts
-    pub temp_c: i32, // celsius
+    #[serde(default, deserialize_with = "zero_as_unset", skip_serializing_if = "Option::is_none")]
+    pub fast_mw: Option<i32>, // milliwatts
+    #[serde(default, deserialize_with = "zero_as_unset", skip_serializing_if = "Option::is_none")]
+    pub slow_mw: Option<i32>, // milliwatts
+    #[serde(default, deserialize_with = "zero_as_unset", skip_serializing_if = "Option::is_none")]
+    pub stapm_mw: Option<i32>, // milliwatts
+    #[serde(default, deserialize_with = "zero_as_unset", skip_serializing_if = "Option::is_none")]
+    pub temp_c: Option<i32>, // celsius
+}
+
+fn zero_as_unset<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+    let value = Option::<i32>::deserialize(deserializer)?;
+    Ok(value.filter(|v| *v != 0))
+}
+
+// ─── Limit validation ────────────────────────────────────────────────────────
+// Applied before limits are saved (GUI, ryzonectl), when profile_settings.toml
+// is loaded, and before enforcement or the helper writes them to the hardware.
+// Only the limits that are set are checked.
+
+pub const MIN_POWER_MW: i32 = 1_000;
+pub const MAX_POWER_MW: i32 = 120_000;
+pub const MIN_TEMP_C: i32 = 30;
+pub const MAX_TEMP_C: i32 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitField {
+    Fast,
+    Slow,
+    Stapm,
+    Temp,
+}
+
+impl LimitField {
+    pub fn label(self) -> &'static str {
+        match self {
+            LimitField::Fast => "Fast limit",
+            LimitField::Slow => "Slow limit",
+            LimitField::Stapm => "STAPM limit",
+            LimitField::Temp => "TCTL temp limit",
+        }
+    }
+}
+
+// Inclusive bounds in mW (power) or °C (temp)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl LimitRange {
+    pub const POWER: LimitRange = LimitRange { min: MIN_POWER_MW, max: MAX_POWER_MW };
+    pub const TEMP: LimitRange = LimitRange { min: MIN_TEMP_C, max: MAX_TEMP_C };
+
+    pub fn contains(self, value: i32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LimitError {
+    PowerOutOfRange { field: LimitField, mw: i32 },
+    TempOutOfRange { temp_c: i32 },
+    SlowAboveFast { slow_mw: i32, fast_mw: i32 },
+    StapmAboveFast { stapm_mw: i32, fast_mw: i32 },
+    OutsideFamilyRange { field: LimitField, value: i32, range: LimitRange, family: &'static str },
+}
+
+impl LimitError {
+    // The input the error is shown next to
+    pub fn field(&self) -> LimitField {
+        match self {
+            LimitError::PowerOutOfRange { field, .. } => *field,
+            LimitError::TempOutOfRange { .. } => LimitField::Temp,
+            LimitError::SlowAboveFast { .. } => LimitField::Slow,
+            LimitError::StapmAboveFast { .. } => LimitField::Stapm,
+            LimitError::OutsideFamilyRange { field, .. } => *field,
+        }
+    }
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::PowerOutOfRange { field, mw } => write!(
+                f,
+                "{} must be between {} and {} W (got {} mW)",
+                field.label(),
+                MIN_POWER_MW / 1000,
+                MAX_POWER_MW / 1000,
+                mw
+            ),
+            LimitError::TempOutOfRange { temp_c } => write!(
+                f,
+                "TCTL temp limit must be between {} and {} °C (got {} °C)",
+                MIN_TEMP_C, MAX_TEMP_C, temp_c
+            ),
+            LimitError::SlowAboveFast { slow_mw, fast_mw } => write!(
+                f,
+                "Slow limit ({} mW) must not be above the fast limit ({} mW)",
+                slow_mw, fast_mw
+            ),
+            LimitError::StapmAboveFast { stapm_mw, fast_mw } => write!(
+                f,
+                "STAPM limit ({} mW) must not be above the fast limit ({} mW)",
+                stapm_mw, fast_mw
+            ),
+            LimitError::OutsideFamilyRange { field: LimitField::Temp, value, range, family } => write!(
+                f,
+                "TCTL temp limit must be between {} and {} °C on {} (got {} °C)",
+                range.min, range.max, family, value
+            ),
+            LimitError::OutsideFamilyRange { field, value, range, family } => write!(
+                f,
+                "{} must be between {} and {} W on {} (got {} mW)",
+                field.label(),
+                range.min / 1000,
+                range.max / 1000,
+                family,
+                value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl PowerLimits {
+    // Every rule that fails, so the UI can mark all offending inputs at once
+    pub fn validate(&self) -> Result<(), Vec<LimitError>> {
+        let mut errors: Vec<LimitError> = [
+            self.fast_mw.map(|mw| Self::check_power(LimitField::Fast, mw)),
+            self.slow_mw.map(|mw| Self::check_power(LimitField::Slow, mw)),
+            self.stapm_mw.map(|mw| Self::check_power(LimitField::Stapm, mw)),
+            self.temp_c.map(Self::check_temp),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(Result::err)
+        .collect();
+
+        if let Some(fast_mw) = self.fast_mw {
+            if let Some(slow_mw) = self.slow_mw.filter(|slow| *slow > fast_mw) {
+                errors.push(LimitError::SlowAboveFast { slow_mw, fast_mw });
+            }
+            if let Some(stapm_mw) = self.stapm_mw.filter(|stapm| *stapm > fast_mw) {
+                errors.push(LimitError::StapmAboveFast { stapm_mw, fast_mw });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn check_power(field: LimitField, mw: i32) -> Result<(), LimitError> {
+        if (MIN_POWER_MW..=MAX_POWER_MW).contains(&mw) {
+            Ok(())
+        } else {
+            Err(LimitError::PowerOutOfRange { field, mw })
+        }
+    }
+
+    pub fn check_temp(temp_c: i32) -> Result<(), LimitError> {
+        if (MIN_TEMP_C..=MAX_TEMP_C).contains(&temp_c) {
+            Ok(())
+        } else {
+            Err(LimitError::TempOutOfRange { temp_c })
+        }
+    }
+}
+
+pub fn format_limit_errors(errors: &[LimitError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// One system battery, listed individually on the Battery tab
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct BatteryInfo {
+    pub name: String, // power_supply name, e.g. BAT0
+    pub manufacturer: String,
+    pub model: String,
+    pub serial: String,
+    pub technology: String, // e.g. Li-ion
+    pub charge_status: String, // charging | discharging | full | empty | na
+    pub charge_percent: i32, // percentage
+    pub design_capacity_mwh: i32, // milliwatt-hours, -1 if unknown
+    pub full_charge_capacity_mwh: i32, // milliwatt-hours, -1 if unknown
+    pub current_capacity_mwh: i32, // milliwatt-hours
+    pub health_percent: i32, // percentage, -1 if unknown
+    pub voltage_millivolt: i32, // millivolts
+    pub cycle_count_cycles: i32, // cycles
+    pub temperature_c: i32, // celsius, -1 if unknown
+    pub power_draw_mw: i32, // milliwatts
+    pub charge_start_threshold_percent: Option<i32>, // None if not supported
+    pub charge_end_threshold_percent: Option<i32>, // None if not supported
 }
 
 // Current stats type
-#[derive(Default)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct CurrentStats {
+    // RyzenAdj
+    pub ryzenadj_available: bool, // false if it didn't initialise or the read failed; CPU, GPU and limit fields are then 0
+
     // CPU
     pub cpu_frequency_mhz: i32, // megahertz
     pub cpu_temperature_c: i32, // celsius
     pub cpu_load_percent: i32, // percentage
+    pub core_clocks_mhz: Vec<i32>, // per physical core, megahertz; 0 when parked
+    pub core_powers_mw: Vec<i32>, // per physical core, milliwatts; empty if not reported
+    pub core_temperatures_c: Vec<i32>, // per physical core, celsius; empty if not reported
+    pub core_ccx: Vec<i32>, // per physical core, L3 complex (a CCD on Zen 3 and later); empty if unknown
     
     // GPU
     pub gpu_frequency_mhz: i32, // megahertz
@@ -90,6 +701,8 @@ pub struct CurrentStats {
     pub power_draw_mw: i32, // milliwatts
     pub profile: String, // profile name
     pub sub_profile: String, // sub-profile name
+    pub on_ac: bool, // adapter online; without one, battery not discharging
+    pub ac_adapter_power_mw: i32, // milliwatts, -1 if unknown
 
     // Battery
     pub batt_charge_status: String, // charging | discharging | full | empty | na
@@ -101,6 +714,8 @@ pub struct CurrentStats {
     pub batt_voltage_millivolt: i32, // millivolts
     pub batt_cycle_count_cycles: i32, // cycles
     pub batt_temperature_c: i32, // celsius
+    pub batteries: Vec<BatteryInfo>, // per battery; the batt_* fields are their totals
+    pub charge_threshold_error: String, // last failure applying charge thresholds
 
     // Runtime estimates
     pub current_load_min: i32, // minutes
@@ -124,4 +739,110 @@ pub struct CurrentStats {
     pub curr_slow_percent: i32, // percentage
     pub curr_stapm_percent: i32, // percentage
     pub curr_tctl_percent: i32, // percentage
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Profiles "a", "b" and "c" with "b" active, a rule and a schedule entry for "b"
+    fn settings() -> ProfileSettings {
+        let profile = |name: &str| {
+            let mut profile = Profile::new(name, "", ProfileMode::Fixed);
+            profile.limits.insert("fixed".to_string(), PowerLimits { fast_mw: Some(20_000), ..Default::default() });
+            profile
+        };
+        ProfileSettings {
+            active_profile: "b".to_string(),
+            profiles: vec![profile("a"), profile("b"), profile("c")],
+            app_rules: vec![AppRule {
+                name: "blender".to_string(),
+                profile: "b".to_string(),
+                exe: Some("blender".to_string()),
+                cmdline: None,
+            }],
+            schedule: vec![ScheduleEntry {
+                days: vec![Weekday::Mon],
+                start: "09:00".to_string(),
+                end: "17:00".to_string(),
+                profile: "b".to_string(),
+            }],
+            charge_thresholds: ChargeThresholds::default(),
+        }
+    }
+
+    #[test]
+    fn zero_limits_read_as_unset() {
+        let limits: PowerLimits = toml::from_str("fast_mw = 0\nslow_mw = 25000\ntemp_c = 0").unwrap();
+        assert_eq!(limits, PowerLimits { slow_mw: Some(25_000), ..Default::default() });
+
+        // Unset limits aren't written back as 0
+        assert_eq!(toml::to_string(&limits).unwrap().trim(), "slow_mw = 25000");
+    }
+
+    #[test]
+    fn new_names_are_checked() {
+        let mut settings = settings();
+        for name in ["", "  ", "gaming.ac", " gaming", "gaming ", "a"] {
+            let profile = Profile::new(name, "", ProfileMode::Fixed);
+            assert!(settings.add_profile(profile).is_err(), "name {:?}", name);
+            assert!(settings.rename_profile("c", name).is_err(), "name {:?}", name);
+            assert!(settings.duplicate_profile("c", name).is_err(), "name {:?}", name);
+        }
+        assert_eq!(settings.profile_names(), vec!["a", "b", "c"]);
+
+        settings.add_profile(Profile::new("gaming mode", "", ProfileMode::Fixed)).unwrap();
+        assert_eq!(settings.profile_names(), vec!["a", "b", "c", "gaming mode"]);
+    }
+
+    #[test]
+    fn rename_follows_references() {
+        let mut settings = settings();
+        settings.rename_profile("b", "work").unwrap();
+        assert_eq!(settings.profile_names(), vec!["a", "work", "c"]);
+        assert_eq!(settings.active_profile, "work");
+        assert_eq!(settings.app_rules[0].profile, "work");
+        assert_eq!(settings.schedule[0].profile, "work");
+
+        // Other profiles' references are left alone
+        settings.rename_profile("a", "idle").unwrap();
+        assert_eq!(settings.active_profile, "work");
+        assert_eq!(settings.app_rules[0].profile, "work");
+
+        assert!(settings.rename_profile("missing", "x").is_err());
+        // Renaming to its own name is a no-op, not a duplicate
+        settings.rename_profile("c", "c").unwrap();
+        assert_eq!(settings.profile_names(), vec!["idle", "work", "c"]);
+    }
+
+    #[test]
+    fn duplicate_is_inserted_after_original() {
+        let mut settings = settings();
+        settings.duplicate_profile("a", "a copy").unwrap();
+        assert_eq!(settings.profile_names(), vec!["a", "a copy", "b", "c"]);
+        assert_eq!(settings.profile("a copy").unwrap().limits, settings.profile("a").unwrap().limits);
+        assert!(settings.duplicate_profile("missing", "x").is_err());
+
+        assert_eq!(settings.unused_name("a copy"), "a copy 2");
+        assert_eq!(settings.unused_name("b copy"), "b copy");
+    }
+
+    #[test]
+    fn delete_removes_references() {
+        let mut settings = settings();
+        // The active one stays until another is activated
+        assert!(settings.delete_profile("b").is_err());
+        assert_eq!(settings.profile_names(), vec!["a", "b", "c"]);
+
+        settings.active_profile = "a".to_string();
+        settings.delete_profile("b").unwrap();
+        assert_eq!(settings.profile_names(), vec!["a", "c"]);
+        assert!(settings.app_rules.is_empty());
+        assert!(settings.schedule.is_empty());
+
+        assert!(settings.delete_profile("missing").is_err());
+        settings.delete_profile("c").unwrap();
+        assert!(settings.delete_profile("a").is_err());
+        assert_eq!(settings.profile_names(), vec!["a"]);
+    }
+}